@@ -0,0 +1,116 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+// The settings auction-cli shares with the Solana CLI config.yml, see
+// `solana config get`. Empty values count as unset, like they do in `solana`.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SolanaConfig {
+    pub json_rpc_url: String,
+    pub websocket_url: String,
+    pub keypair_path: String,
+    pub commitment: String,
+}
+
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config/solana/cli/config.yml"))
+}
+
+// a missing config file leaves everything unset
+pub fn load_config(path: &Path) -> Result<SolanaConfig, String> {
+    match std::fs::read_to_string(path) {
+        Ok(config) => serde_yaml::from_str(&config).map_err(|err| err.to_string()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(SolanaConfig::default()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+// Accepts the same cluster monikers as `solana --url`.
+pub fn normalize_url(url: &str) -> String {
+    match url {
+        "localnet" | "localhost" | "l" => "http://localhost:8899",
+        "devnet" | "d" => "https://api.devnet.solana.com",
+        "testnet" | "t" => "https://api.testnet.solana.com",
+        "mainnet" | "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+        url => url,
+    }
+    .to_string()
+}
+
+// The pubsub endpoint of an RPC url: ws(s) instead of http(s), one port up
+// when the port is given.
+pub fn websocket_url(rpc_url: &str) -> String {
+    let Some((scheme, rest)) = rpc_url.split_once("://") else {
+        return rpc_url.to_string();
+    };
+    let scheme = match scheme {
+        "https" => "wss",
+        "http" => "ws",
+        scheme => scheme,
+    };
+    let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let port = authority
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?.checked_add(1)?)));
+    match port {
+        Some((host, port)) => format!("{scheme}://{host}:{port}{path}"),
+        None => format!("{scheme}://{authority}{path}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(normalize_url("localnet"), "http://localhost:8899");
+        assert_eq!(normalize_url("d"), "https://api.devnet.solana.com");
+        assert_eq!(
+            normalize_url("mainnet"),
+            "https://api.mainnet-beta.solana.com"
+        );
+        assert_eq!(
+            normalize_url("https://rpc.example.com"),
+            "https://rpc.example.com"
+        );
+    }
+
+    #[test]
+    fn test_websocket_url() {
+        assert_eq!(
+            websocket_url("https://api.devnet.solana.com"),
+            "wss://api.devnet.solana.com"
+        );
+        assert_eq!(
+            websocket_url("http://localhost:8899"),
+            "ws://localhost:8900"
+        );
+        assert_eq!(
+            websocket_url("https://rpc.example.com:443/key"),
+            "wss://rpc.example.com:444/key"
+        );
+        assert_eq!(websocket_url("http://[::1]"), "ws://[::1]");
+    }
+
+    #[test]
+    fn test_load_config() {
+        let path = std::env::temp_dir().join("auction-cli-test-config.yml");
+        std::fs::write(
+            &path,
+            "---\njson_rpc_url: \"http://localhost:8899\"\nwebsocket_url: \"\"\nkeypair_path: /tmp/id.json\naddress_labels:\n  \"11111111111111111111111111111111\": System Program\ncommitment: processed\n",
+        )
+        .unwrap();
+        assert_eq!(
+            load_config(&path),
+            Ok(SolanaConfig {
+                json_rpc_url: "http://localhost:8899".to_string(),
+                websocket_url: String::new(),
+                keypair_path: "/tmp/id.json".to_string(),
+                commitment: "processed".to_string(),
+            })
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(load_config(&path), Ok(SolanaConfig::default()));
+    }
+}
@@ -34,6 +34,20 @@ pub fn decimal_to_u64(value: &str, decimals: u8) -> Result<u64, &'static str> {
     Ok(value)
 }
 
+// The inverse of `decimal_to_u64`, trailing zeros of the fraction dropped.
+pub fn u64_to_decimal(value: u64, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    let digits = format!("{:0>width$}", value, width = decimals + 1);
+    let (integer_part, fractional_part) = digits.split_at(digits.len() - decimals);
+
+    let fractional_part = fractional_part.trim_end_matches('0');
+    if fractional_part.is_empty() {
+        integer_part.to_string()
+    } else {
+        format!("{}.{}", integer_part, fractional_part)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -46,4 +60,15 @@ mod tests {
         assert_eq!(decimal_to_u64("45.678", 3), Ok(45678));
         assert!(decimal_to_u64("3.1415", 2).is_err());
     }
+
+    #[test]
+    fn test_u64_to_decimal() {
+        assert_eq!(u64_to_decimal(1234, 2), "12.34");
+        assert_eq!(u64_to_decimal(500, 3), "0.5");
+        assert_eq!(u64_to_decimal(10000000, 5), "100");
+        assert_eq!(u64_to_decimal(7, 9), "0.000000007");
+        assert_eq!(u64_to_decimal(0, 6), "0");
+        assert_eq!(u64_to_decimal(42, 0), "42");
+        assert_eq!(decimal_to_u64(&u64_to_decimal(45678, 3), 3), Ok(45678));
+    }
 }
@@ -1,18 +1,74 @@
-use crate::idl::AuctionProgram;
+use auction_client::{
+    errors::AuctionError,
+    events::decode_event,
+    instructions::{
+        self, allowlist_proof, allowlist_root, hidden_reserve_hash, sealed_bid_commitment,
+        secret_salt, BatchBidArgs, BidArgs, BuyNowArgs, CommitBidArgs, InitAuctionArgs,
+        InitBatchAuctionArgs, InitDutchAuctionArgs, InitHouseArgs, InitSealedAuctionArgs,
+        KeeperPoolArgs, PauseHouseArgs, ProposeAdminArgs, RaiseBidArgs, RevealBidArgs,
+        RevealReserveArgs, SetKeeperRewardArgs, SweepLamportsArgs, UpdateHouseFeeArgs,
+    },
+    pda::{
+        derive_auction, derive_auction_keys, derive_bidder_keys, derive_gate_keys, derive_house,
+        derive_keeper_pool, derive_metadata, derive_nft_keys, derive_treasury, derive_user_stats,
+        AuctionKeys, GateKeys, NftKeys,
+    },
+    state::{
+        batch_bid_order, deserialize_metadata, Auction, AuctionAccount, AuctionHouse, AuctionKind,
+        BidState, EndCondition, Gate, KeeperReward, UserStats, AUCTION_MINT_A_OFFSET,
+        AUCTION_MINT_B_OFFSET, AUCTION_SELLER_OFFSET, BID_STATE_AUCTION_OFFSET,
+        BID_STATE_BIDDER_OFFSET,
+    },
+    TOKEN_2022_PROGRAM_ID, TOKEN_METADATA_PROGRAM_ID,
+};
+use base64::prelude::{Engine, BASE64_STANDARD};
 use clap::{Parser, Subcommand};
-use decimal::decimal_to_u64;
-use idl::{BidArgs, InitAuctionArgs, InitHouseArgs};
-use solana_client::rpc_client::RpcClient;
+use config::{default_config_path, load_config, normalize_url, websocket_url};
+use decimal::{decimal_to_u64, u64_to_decimal};
+use proxy::{Proxy, ProxyAction};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    client_error::ClientError,
+    pubsub_client::PubsubClient,
+    rpc_client::RpcClient,
+    rpc_config::{
+        RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionLogsConfig,
+        RpcTransactionLogsFilter,
+    },
+    rpc_filter::{Memcmp, RpcFilterType},
+};
 use solana_sdk::{
+    account::{from_account, Account},
+    clock::Clock,
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
     program_pack::Pack,
     pubkey::Pubkey,
-    signer::{keypair::read_keypair_file, Signer},
+    signature::Signature,
+    signer::{
+        keypair::{read_keypair_file, Keypair},
+        Signer,
+    },
+    sysvar,
+    transaction::Transaction,
 };
-use spl_token::state::Mint;
-use std::path::PathBuf;
+use spl_token::state::{Account as TokenAccount, Mint};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use time::end_time_to_unix;
+use view::{AuctionStatus, AuctionView, BidView, HouseView, OutputFormat, StatsView};
 
+mod config;
 mod decimal;
-mod idl;
+mod proxy;
+mod time;
+mod view;
+
+// rpc errors are large, boxing them keeps the results small
+type ClientResult<T> = Result<T, Box<ClientError>>;
 
 #[derive(Parser)]
 #[command(name = "auction-cli")]
@@ -30,6 +86,43 @@ struct Cli {
     /// #[clap(long, short)]
     #[arg(short, long, value_name = "NAME", default_value = "auction_house")]
     auction_house_name: String,
+
+    /// Solana CLI config file the defaults below are read from.
+    #[arg(short = 'C', long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// RPC url or moniker of the cluster: localnet, devnet, testnet or mainnet.
+    #[arg(short, long, value_name = "URL_OR_MONIKER")]
+    url: Option<String>,
+
+    /// Commitment level of the reads and confirmations.
+    #[arg(long, value_parser = ["processed", "confirmed", "finalized"])]
+    commitment: Option<String>,
+
+    /// Keypair file paying the transaction fees instead of the signer.
+    #[arg(long, value_name = "PATH")]
+    fee_payer: Option<PathBuf>,
+
+    /// Priority fee in micro-lamports per compute unit.
+    #[arg(long, value_name = "MICRO_LAMPORTS")]
+    compute_unit_price: Option<u64>,
+
+    /// The most compute units a transaction may use.
+    #[arg(long, value_name = "UNITS")]
+    compute_unit_limit: Option<u32>,
+
+    /// Output format of the show and list commands.
+    #[arg(long, value_enum, default_value = "text")]
+    output: OutputFormat,
+}
+
+// the connection and signers every transaction is sent with
+struct Context {
+    client: RpcClient,
+    keypair: Keypair,
+    fee_payer: Option<Keypair>,
+    // compute budget instructions put in front of every transaction
+    compute_budget: Vec<Instruction>,
 }
 
 #[derive(Subcommand)]
@@ -39,6 +132,176 @@ enum Command {
         /// Fee in basis points taken for successful auctions.
         #[arg(value_name = "FEE_IN_BASIS_POINTS")]
         fee: u16,
+        /// Share of the highest bid in basis points a seller pays the highest bidder to cancel early.
+        #[clap(long, default_value = "0")]
+        cancel_penalty_bps: u16,
+    },
+
+    /// admin changes the house fee
+    UpdateHouseFee {
+        /// Fee in basis points taken for successful auctions.
+        #[arg(value_name = "FEE_IN_BASIS_POINTS")]
+        fee: u16,
+    },
+
+    /// admin proposes a new admin, who has to accept it
+    ProposeAdmin {
+        /// The proposed admin.
+        #[arg(value_name = "NEW_ADMIN")]
+        new_admin: Pubkey,
+    },
+
+    /// proposed admin takes over the house
+    AcceptAdmin,
+
+    /// admin stops the house from taking new auctions and bids
+    PauseHouse {
+        /// Resume a paused house instead.
+        #[clap(long)]
+        unpause: bool,
+    },
+
+    /// admin sets what keepers earn for settling ended auctions
+    SetKeeperReward {
+        /// Pay keepers this share of the house fee in basis points.
+        #[clap(long, conflicts_with = "lamports")]
+        fee_share_bps: Option<u16>,
+        /// Pay keepers a fixed tip in lamports out of the SOL sent to the house account.
+        #[clap(long)]
+        lamports: Option<u64>,
+    },
+
+    /// admin moves the collected fees of a mint out of the house treasury
+    SweepFees {
+        /// Mint the fees were paid in.
+        #[clap(long, short)]
+        mint: Pubkey,
+        /// Token account receiving the fees, defaults to the admin's token account.
+        #[clap(long, short)]
+        destination: Option<Pubkey>,
+    },
+
+    /// admin moves SOL fees out of the house account
+    SweepLamports {
+        /// The amount of SOL to move.
+        #[clap(long, short)]
+        amount: String,
+        /// Wallet receiving the SOL, defaults to the admin.
+        #[clap(long, short)]
+        destination: Option<Pubkey>,
+    },
+
+    /// admin adds SOL to the pool lamport keeper tips are paid from
+    FundKeeperPool {
+        /// The amount of SOL to add.
+        #[clap(long, short)]
+        amount: String,
+    },
+
+    /// admin takes SOL back out of the keeper pool
+    WithdrawKeeperPool {
+        /// The amount of SOL to take out.
+        #[clap(long, short)]
+        amount: String,
+    },
+
+    /// show the fees collected by the house for every mint
+    Fees,
+
+    /// show the settings of the auction house
+    ShowHouse,
+
+    /// show an auction with its high bid and the time left
+    ShowAuction {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+        /// The seller in the auction.
+        #[clap(long, short)]
+        seller: Pubkey,
+    },
+
+    /// list the open auctions of the house
+    ListAuctions {
+        /// Only auctions of this seller.
+        #[clap(long, short)]
+        seller: Option<Pubkey>,
+        /// Only auctions listing this mint.
+        #[clap(long, short)]
+        listing_mint: Option<Pubkey>,
+        /// Only auctions bidding in this mint.
+        #[clap(long, short)]
+        purchase_mint: Option<Pubkey>,
+        /// Only auctions in this phase.
+        #[clap(long, value_enum)]
+        status: Option<AuctionStatus>,
+    },
+
+    /// list the open bids of a bidder
+    ListBids {
+        /// The bidder, defaults to the signer.
+        #[clap(long, short)]
+        bidder: Option<Pubkey>,
+    },
+
+    /// show a wallet's auctions, bids and volume in the house
+    ShowStats {
+        /// The wallet, defaults to the signer.
+        #[clap(long, short)]
+        wallet: Option<Pubkey>,
+    },
+
+    /// print auction program events as they happen
+    Watch,
+
+    /// follow an auction as it changes, optionally bidding for the signer up to a ceiling
+    Follow {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+        /// The seller in the auction.
+        #[clap(long, short)]
+        seller: Pubkey,
+        /// Outbid everyone else up to this price, in the auction's decimals. English auctions only.
+        #[clap(long)]
+        max_price: Option<String>,
+        /// How far above the minimum bid to bid each time, in the auction's decimals.
+        #[clap(long, requires = "max_price")]
+        increment: Option<String>,
+        /// Seconds between status lines while the auction doesn't change.
+        #[clap(long, default_value = "10")]
+        interval: u64,
+        /// The allowlist of an allowlist-gated auction, to prove the bidder is on it.
+        #[clap(long)]
+        allowlist: Option<PathBuf>,
+        /// The bidder's NFT of a collection-gated auction's collection.
+        #[clap(long)]
+        gate_nft: Option<Pubkey>,
+    },
+
+    /// settle every auction of the house once it has ended, forever
+    Crank {
+        /// Seconds to wait between scans.
+        #[clap(long, default_value = "30")]
+        interval: u64,
+        /// Scan once and exit.
+        #[clap(long)]
+        once: bool,
+        /// The number of batch auction bids filled per transaction.
+        #[clap(long, default_value = "3")]
+        chunk: usize,
     },
 
     /// Initialize a new auction
@@ -49,13 +312,231 @@ enum Command {
         /// Mint of the token used for bidding in auction.
         #[clap(long, short)]
         purchase_mint: Pubkey,
+        /// Lot number, lets a seller run several auctions of the same mints at once.
+        #[clap(long, default_value = "0")]
+        seed: u64,
 
         /// The starting price.
         #[clap(long, short)]
         starting_price: String,
+        /// A bid at or above this price wins the auction immediately.
+        #[clap(long)]
+        buy_now_price: Option<String>,
+        /// The slot the auction will end on.
+        #[clap(long, short, required_unless_present = "end_time")]
+        end_slot: Option<u64>,
+        /// End at a date (RFC 3339) or after a duration like 2h30m instead of a slot.
+        #[clap(long, conflicts_with = "end_slot")]
+        end_time: Option<String>,
+        /// The number of tokens to auction off.
+        #[clap(long, short)]
+        amount: String,
+
+        /// The number of decimals to be used for the price.
+        #[clap(long, short, default_value = "9")]
+        decimals: u8,
+
+        /// Bids placed within this many slots (seconds with --end-time) of the end extend the auction (0 disables).
+        #[clap(long, default_value = "0")]
+        extension_window: u64,
+        /// The number of slots (or seconds) each late bid extends the auction by.
+        #[clap(long, default_value = "0")]
+        extension: u64,
+        /// The maximum number of slots (or seconds) the auction can be extended by in total.
+        #[clap(long, default_value = "0")]
+        max_extension: u64,
+
+        /// The minimum amount a new bid must beat the highest price by.
+        #[clap(long, default_value = "0")]
+        min_increment: String,
+        /// The minimum increment in basis points of the highest price.
+        #[clap(long, default_value = "0")]
+        min_increment_bps: u16,
+        /// The lowest price the seller is willing to sell for.
+        #[clap(long)]
+        reserve_price: Option<String>,
+        /// Secret used to hide the reserve price until it is revealed.
+        #[clap(long, requires = "reserve_price")]
+        reserve_secret: Option<String>,
+
+        /// Only take bids from the wallets listed in this file, one per line.
+        #[clap(long, group = "gate")]
+        allowlist: Option<PathBuf>,
+        /// Only take bids from holders of this token.
+        #[clap(long, group = "gate")]
+        gate_mint: Option<Pubkey>,
+        /// The amount of the gate token bidders must hold.
+        #[clap(long, default_value = "1", requires = "gate_mint")]
+        gate_min_amount: String,
+        /// Only take bids from holders of an NFT of this verified collection.
+        #[clap(long, group = "gate")]
+        gate_collection: Option<Pubkey>,
+    },
+
+    /// Initialize a new dutch auction with a falling price
+    InitDutchAuction {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number, lets a seller run several auctions of the same mints at once.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+
+        /// The price the auction starts at.
+        #[clap(long, short)]
+        start_price: String,
+        /// The price the auction falls to by the end.
+        #[clap(long, short)]
+        floor_price: String,
+        /// The price drops every this many slots (seconds with --end-time).
+        #[clap(long, default_value = "1")]
+        step: u64,
+        /// The slot the auction will end on.
+        #[clap(long, short, required_unless_present = "end_time")]
+        end_slot: Option<u64>,
+        /// End at a date (RFC 3339) or after a duration like 2h30m instead of a slot.
+        #[clap(long, conflicts_with = "end_slot")]
+        end_time: Option<String>,
+        /// The number of tokens to auction off.
+        #[clap(long, short)]
+        amount: String,
+
+        /// The number of decimals to be used for the price.
+        #[clap(long, short, default_value = "9")]
+        decimals: u8,
+    },
+
+    /// Initialize a uniform-price auction of many units with many winners
+    InitBatchAuction {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number, lets a seller run several auctions of the same mints at once.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+
+        /// The lowest unit price accepted.
+        #[clap(long, short)]
+        min_price: String,
         /// The slot the auction will end on.
+        #[clap(long, short, required_unless_present = "end_time")]
+        end_slot: Option<u64>,
+        /// End at a date (RFC 3339) or after a duration like 2h30m instead of a slot.
+        #[clap(long, conflicts_with = "end_slot")]
+        end_time: Option<String>,
+        /// The number of tokens to auction off.
+        #[clap(long, short)]
+        amount: String,
+
+        /// The number of decimals to be used for the price.
+        #[clap(long, short, default_value = "9")]
+        decimals: u8,
+    },
+
+    /// bidder asks for a quantity of a batch auction at a unit price
+    BatchBid {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+        /// The seller in the auction.
+        #[clap(long, short)]
+        seller: Pubkey,
+        /// The number of tokens wanted.
+        #[clap(long, short)]
+        quantity: String,
+        /// The unit price offered.
+        #[clap(long)]
+        price: String,
+        /// The number of decimals to be used for the price.
+        #[clap(long, short, default_value = "9")]
+        decimals: u8,
+    },
+
+    /// crank a finished batch auction until every winner is filled
+    SettleBatch {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+        /// The seller in the auction.
+        #[clap(long, short)]
+        seller: Pubkey,
+        /// The number of bids filled per transaction.
+        #[clap(long, default_value = "3")]
+        chunk: usize,
+    },
+
+    /// buyer takes a dutch auction at its current price
+    BuyNow {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+        /// The seller in the auction.
+        #[clap(long, short)]
+        seller: Pubkey,
+        /// The most the buyer is willing to pay.
+        #[clap(long, short)]
+        max_price: String,
+        /// The number of decimals to be used for the price.
+        #[clap(long, short, default_value = "9")]
+        decimals: u8,
+    },
+
+    /// Initialize a new sealed-bid auction
+    InitSealedAuction {
+        /// Mint of the token being listed for auction.
         #[clap(long, short)]
-        end_slot: u64,
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number, lets a seller run several auctions of the same mints at once.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+
+        /// The starting price.
+        #[clap(long, short)]
+        starting_price: String,
+        /// The slot the commit phase ends on.
+        #[clap(long, short, required_unless_present = "end_time")]
+        end_slot: Option<u64>,
+        /// End the commit phase at a date (RFC 3339) or after a duration like 2h30m.
+        #[clap(long, conflicts_with = "end_slot")]
+        end_time: Option<String>,
+        /// The slot the reveal phase ends on.
+        #[clap(
+            long,
+            short,
+            requires = "end_slot",
+            required_unless_present = "reveal_end_time"
+        )]
+        reveal_end_slot: Option<u64>,
+        /// End the reveal phase at a date (RFC 3339) or after a duration like 2h30m.
+        #[clap(long, requires = "end_time", conflicts_with = "reveal_end_slot")]
+        reveal_end_time: Option<String>,
         /// The number of tokens to auction off.
         #[clap(long, short)]
         amount: String,
@@ -63,6 +544,104 @@ enum Command {
         /// The number of decimals to be used for the price.
         #[clap(long, short, default_value = "9")]
         decimals: u8,
+
+        /// The winner pays the second highest revealed price.
+        #[clap(long)]
+        second_price: bool,
+        /// Share of the deposit in basis points taken from bids never revealed.
+        #[clap(long, default_value = "0")]
+        penalty_bps: u16,
+    },
+
+    /// bidder commits a sealed bid
+    CommitBid {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+        /// The seller in the auction.
+        #[clap(long, short)]
+        seller: Pubkey,
+        /// The sealed bid price.
+        #[clap(long)]
+        price: String,
+        /// Secret the bid is sealed with, needed again to reveal it.
+        #[clap(long, short = 'x')]
+        secret: String,
+        /// Tokens deposited to cover the bid, must be at least the price times the amount.
+        #[clap(long)]
+        deposit: String,
+        /// The number of decimals to be used for the price.
+        #[clap(long, short, default_value = "9")]
+        decimals: u8,
+    },
+
+    /// bidder reveals a sealed bid
+    RevealBid {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+        /// The seller in the auction.
+        #[clap(long, short)]
+        seller: Pubkey,
+        /// The sealed bid price.
+        #[clap(long)]
+        price: String,
+        /// Secret the bid was sealed with.
+        #[clap(long, short = 'x')]
+        secret: String,
+        /// The number of decimals to be used for the price.
+        #[clap(long, short, default_value = "9")]
+        decimals: u8,
+    },
+
+    /// seller takes the penalty from a sealed bid that was never revealed
+    PenalizeBid {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+        /// The bidder who did not reveal.
+        #[clap(long, short)]
+        bidder: Pubkey,
+    },
+
+    /// seller reveals a hidden reserve price
+    RevealReserve {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+        /// The hidden reserve price.
+        #[clap(long, short)]
+        reserve_price: String,
+        /// Secret the reserve price was hidden with.
+        #[clap(long, short = 'x')]
+        reserve_secret: String,
+        /// The number of decimals to be used for the price.
+        #[clap(long, short, default_value = "9")]
+        decimals: u8,
     },
 
     /// bidder place bid
@@ -73,12 +652,48 @@ enum Command {
         /// Mint of the token used for bidding in auction.
         #[clap(long, short)]
         purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
         /// The seller in the auction.
         #[clap(long, short)]
         seller: Pubkey,
         /// bidder bid price, requring price higher than the current highest price
+        #[clap(long)]
+        price: String,
+        /// Let the program outbid lower bids up to this price, the escrow covers it.
+        #[clap(long)]
+        max_price: Option<String>,
+        /// The number of decimals to be used for the price.
+        #[clap(long, short, default_value = "9")]
+        decimals: u8,
+        /// The allowlist of an allowlist-gated auction, to prove the bidder is on it.
+        #[clap(long)]
+        allowlist: Option<PathBuf>,
+        /// The bidder's NFT of a collection-gated auction's collection.
+        #[clap(long)]
+        gate_nft: Option<Pubkey>,
+    },
+    /// bidder raises the price of their existing bid
+    RaiseBid {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+        /// The seller in the auction.
         #[clap(long, short)]
+        seller: Pubkey,
+        /// new bid price, only the difference to the existing bid is deposited
+        #[clap(long)]
         price: String,
+        /// The new proxy max price, left out the bid is no longer a proxy bid.
+        #[clap(long)]
+        max_price: Option<String>,
         /// The number of decimals to be used for the price.
         #[clap(long, short, default_value = "9")]
         decimals: u8,
@@ -91,20 +706,23 @@ enum Command {
         /// Mint of the token used for bidding in auction.
         #[clap(long, short)]
         purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
         /// The seller in the auction.
         #[clap(long, short)]
         seller: Pubkey,
     },
     Finalize {
-        /// auction house admin
-        #[clap(long, short)]
-        admin: Pubkey,
         /// Mint of the token being listed for auction.
         #[clap(long, short)]
         listing_mint: Pubkey,
         /// Mint of the token used for bidding in auction.
         #[clap(long, short)]
         purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
         /// The seller in the auction.
         #[clap(long, short)]
         seller: Pubkey,
@@ -119,81 +737,1006 @@ enum Command {
         /// Mint of the token used for bidding in auction.
         #[clap(long, short)]
         purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
     },
-}
 
-fn main() {
-    // let cli = Cli::parse();
-    let Cli {
-        keypair_path,
+    /// seller cancels a live auction, bidders withdraw their bids afterwards
+    CancelEarly {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+        /// The highest bidder, who receives the house cancellation penalty.
+        #[clap(long, short)]
+        bidder: Option<Pubkey>,
+    },
+
+    /// seller closes a cancelled auction once every bidder withdrew
+    CloseCancelled {
+        /// Mint of the token being listed for auction.
+        #[clap(long, short)]
+        listing_mint: Pubkey,
+        /// Mint of the token used for bidding in auction.
+        #[clap(long, short)]
+        purchase_mint: Pubkey,
+        /// Lot number of the auction, for sellers running several of the same mints.
+        #[clap(long, default_value = "0")]
+        seed: u64,
+    },
+}
+
+fn main() {
+    // let cli = Cli::parse();
+    let Cli {
+        keypair_path,
         command,
         auction_house_name,
+        config,
+        url,
+        commitment,
+        fee_payer,
+        compute_unit_price,
+        compute_unit_limit,
+        output,
     } = Cli::parse();
 
-    let (auction_house, _auction_house_bump) = Pubkey::find_program_address(
-        &[b"house", auction_house_name.as_bytes()],
-        &AuctionProgram::id(),
-    );
+    let auction_house = derive_house(&auction_house_name);
 
-    // let rpc_url = "http://127.0.0.1:8899";
-    let rpc_url = "https://api.devnet.solana.com";
-    let client = RpcClient::new(rpc_url);
+    // flags take precedence over the Solana CLI config, like in `solana`
+    let config = config
+        .or_else(default_config_path)
+        .map(|path| load_config(&path).expect("invalid Solana CLI config"))
+        .unwrap_or_default();
+    let non_empty = |value: String| Some(value).filter(|value| !value.is_empty());
 
-    let keypair_path = keypair_path.unwrap_or_else(|| {
-        dirs::home_dir()
-            .expect("Failed to get home directory")
-            .join(".config/solana/id.json")
-    });
+    let rpc_url = url
+        .as_deref()
+        .map(normalize_url)
+        .or_else(|| non_empty(config.json_rpc_url.clone()))
+        .unwrap_or_else(|| normalize_url("devnet"));
+    // the config's websocket url belongs to the config's cluster
+    let ws_url = match url {
+        Some(_) => None,
+        None => non_empty(config.websocket_url),
+    }
+    .unwrap_or_else(|| websocket_url(&rpc_url));
+    let commitment = commitment
+        .or_else(|| non_empty(config.commitment))
+        .map(|commitment| {
+            CommitmentConfig::from_str(&commitment).expect("invalid commitment level")
+        })
+        .unwrap_or_else(CommitmentConfig::confirmed);
+
+    let keypair_path = keypair_path
+        .or_else(|| non_empty(config.keypair_path).map(PathBuf::from))
+        .unwrap_or_else(|| {
+            dirs::home_dir()
+                .expect("Failed to get home directory")
+                .join(".config/solana/id.json")
+        });
     let keypair = read_keypair_file(&keypair_path).expect("Couldn't find wallet file");
+    let fee_payer =
+        fee_payer.map(|path| read_keypair_file(path).expect("Couldn't find fee payer file"));
+
+    let mut compute_budget = vec![];
+    if let Some(units) = compute_unit_limit {
+        compute_budget.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+    }
+    if let Some(micro_lamports) = compute_unit_price {
+        compute_budget.push(ComputeBudgetInstruction::set_compute_unit_price(
+            micro_lamports,
+        ));
+    }
+
+    let context = Context {
+        client: RpcClient::new_with_commitment(rpc_url, commitment),
+        keypair,
+        fee_payer,
+        compute_budget,
+    };
+    let client = &context.client;
+    let keypair = &context.keypair;
+
+    match command {
+        Command::InitHouse {
+            fee,
+            cancel_penalty_bps,
+        } => {
+            println!(
+                "Initializing house with fee: {} and name: {}",
+                fee, auction_house_name
+            );
+
+            let instruction = instructions::init_house(
+                &keypair.pubkey(),
+                &InitHouseArgs {
+                    fee,
+                    cancel_penalty_bps,
+                    name: auction_house_name,
+                },
+            );
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Initialized auction house account: {} at {}",
+                signature, auction_house
+            )
+        }
+
+        Command::UpdateHouseFee { fee } => {
+            let instruction = instructions::update_house_fee(
+                &keypair.pubkey(),
+                &auction_house,
+                &UpdateHouseFeeArgs { fee },
+            );
+            let signature = run(&context, &[instruction]);
+            println!("Updated fee of {} to {}: {}", auction_house, fee, signature)
+        }
+
+        Command::ProposeAdmin { new_admin } => {
+            let instruction = instructions::propose_admin(
+                &keypair.pubkey(),
+                &auction_house,
+                &ProposeAdminArgs {
+                    pending_admin: new_admin,
+                },
+            );
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Proposed {} as admin of {}: {}",
+                new_admin, auction_house, signature
+            )
+        }
+
+        Command::AcceptAdmin => {
+            let instruction = instructions::accept_admin(&keypair.pubkey(), &auction_house);
+            let signature = run(&context, &[instruction]);
+            println!(
+                "{} is now admin of {}: {}",
+                keypair.pubkey(),
+                auction_house,
+                signature
+            )
+        }
+
+        Command::PauseHouse { unpause } => {
+            let instruction = instructions::pause_house(
+                &keypair.pubkey(),
+                &auction_house,
+                &PauseHouseArgs { paused: !unpause },
+            );
+            let signature = run(&context, &[instruction]);
+            println!(
+                "{} {}: {}",
+                if unpause { "Unpaused" } else { "Paused" },
+                auction_house,
+                signature
+            )
+        }
+
+        Command::SetKeeperReward {
+            fee_share_bps,
+            lamports,
+        } => {
+            let keeper_reward = match (fee_share_bps, lamports) {
+                (Some(bps), _) => KeeperReward::FeeShare { bps },
+                (_, Some(amount)) => KeeperReward::Lamports { amount },
+                (None, None) => KeeperReward::None,
+            };
+            let instruction = instructions::set_keeper_reward(
+                &keypair.pubkey(),
+                &auction_house,
+                &SetKeeperRewardArgs { keeper_reward },
+            );
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Set keeper reward of {} to {:?}: {}",
+                auction_house, keeper_reward, signature
+            )
+        }
+
+        Command::SweepFees { mint, destination } => {
+            let admin = keypair.pubkey();
+            let treasury = derive_treasury(&auction_house, &mint);
+            let token_program =
+                fetch_token_program(client, &mint).unwrap_or_else(|err| exit_with(&err));
+            let destination = destination.unwrap_or_else(|| {
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &admin,
+                    &mint,
+                    &token_program,
+                )
+            });
+
+            let instruction = instructions::sweep_fees(
+                &admin,
+                &auction_house,
+                &mint,
+                &treasury,
+                &destination,
+                &token_program,
+            );
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Swept fees from {} to {}: {}",
+                treasury, destination, signature
+            )
+        }
+
+        Command::SweepLamports {
+            amount,
+            destination,
+        } => {
+            let admin = keypair.pubkey();
+            let destination = destination.unwrap_or(admin);
+            let amount = decimal_to_u64(&amount, 9).expect("invalid amount");
+
+            let instruction = instructions::sweep_lamports(
+                &admin,
+                &auction_house,
+                &destination,
+                &SweepLamportsArgs { amount },
+            );
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Swept {} lamports from {} to {}: {}",
+                amount, auction_house, destination, signature
+            )
+        }
+
+        Command::FundKeeperPool { amount } => {
+            let amount = decimal_to_u64(&amount, 9).expect("invalid amount");
+            let instruction = instructions::fund_keeper_pool(
+                &keypair.pubkey(),
+                &auction_house,
+                &KeeperPoolArgs { amount },
+            );
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Added {} lamports to the keeper pool {}: {}",
+                amount,
+                derive_keeper_pool(&auction_house),
+                signature
+            )
+        }
+
+        Command::WithdrawKeeperPool { amount } => {
+            let amount = decimal_to_u64(&amount, 9).expect("invalid amount");
+            let instruction = instructions::withdraw_keeper_pool(
+                &keypair.pubkey(),
+                &auction_house,
+                &KeeperPoolArgs { amount },
+            );
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Took {} lamports out of the keeper pool {}: {}",
+                amount,
+                derive_keeper_pool(&auction_house),
+                signature
+            )
+        }
+
+        Command::Fees => {
+            // the house owns a treasury per purchase mint, under either token
+            // program. Token-2022 accounts are longer than `TokenAccount::LEN`
+            // but start with the same layout.
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    32,
+                    auction_house.as_ref(),
+                ))]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            };
+            let mut treasuries = vec![];
+            for token_program in [spl_token::ID, TOKEN_2022_PROGRAM_ID] {
+                let accounts = client
+                    .get_program_accounts_with_config(&token_program, config.clone())
+                    .expect("could not get treasury accounts");
+                treasuries.extend(accounts.into_iter().filter_map(|(address, account)| {
+                    let account = account
+                        .data
+                        .get(..TokenAccount::LEN)
+                        .and_then(|data| TokenAccount::unpack_from_slice(data).ok())?;
+                    (derive_treasury(&auction_house, &account.mint) == address)
+                        .then_some((address, account))
+                }));
+            }
+
+            println!("Fees collected by {}:", auction_house);
+
+            // native SOL fees are kept in the house account itself
+            let house_account = client
+                .get_account(&auction_house)
+                .expect("could not get auction house account");
+            let rent = client
+                .get_minimum_balance_for_rent_exemption(house_account.data.len())
+                .expect("could not get rent");
+            println!(
+                "  SOL: {}",
+                solana_sdk::native_token::lamports_to_sol(
+                    house_account.lamports.saturating_sub(rent)
+                )
+            );
+            for (treasury, treasury_account) in treasuries {
+                let mint_account = client
+                    .get_account(&treasury_account.mint)
+                    .expect("could not get mint account");
+                let mint_account = mint_account
+                    .data
+                    .get(..Mint::LEN)
+                    .and_then(|data| Mint::unpack_from_slice(data).ok())
+                    .expect("invalid mint account data");
+                println!(
+                    "  mint {}: {} (treasury {})",
+                    treasury_account.mint,
+                    spl_token::amount_to_ui_amount_string(
+                        treasury_account.amount,
+                        mint_account.decimals
+                    ),
+                    treasury
+                );
+            }
+        }
+
+        Command::ShowHouse => {
+            let house_account = client
+                .get_account(&auction_house)
+                .expect("could not get auction house account");
+            let house = AuctionHouse::from_account_data(&house_account.data)
+                .expect("invalid auction house account data");
+            view::print(&HouseView::new(&auction_house, &house), output);
+        }
+
+        Command::ShowAuction {
+            listing_mint,
+            purchase_mint,
+            seed,
+            seller,
+        } => {
+            let address =
+                derive_auction(&auction_house, &listing_mint, &purchase_mint, &seller, seed);
+            let auction = fetch_auction(client, &address);
+            let clock = fetch_clock(client);
+            view::print(&AuctionView::new(&address, &auction, &clock), output);
+        }
+
+        Command::ListAuctions {
+            seller,
+            listing_mint,
+            purchase_mint,
+            status,
+        } => {
+            let filters = [
+                (AUCTION_SELLER_OFFSET, seller),
+                (AUCTION_MINT_A_OFFSET, listing_mint),
+                (AUCTION_MINT_B_OFFSET, purchase_mint),
+            ]
+            .into_iter()
+            .filter_map(|(offset, key)| {
+                Some(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    offset,
+                    key?.as_ref(),
+                )))
+            })
+            .collect();
+            let clock = fetch_clock(client);
+
+            let auctions: Vec<_> = fetch_program_accounts::<Auction>(client, filters)
+                .unwrap_or_else(|err| exit_with(&err))
+                .into_iter()
+                // auctions don't store their house, only their address tells
+                .filter(|(address, auction)| {
+                    derive_auction(
+                        &auction_house,
+                        &auction.mint_a,
+                        &auction.mint_b,
+                        &auction.seller,
+                        auction.seed,
+                    ) == *address
+                })
+                .map(|(address, auction)| AuctionView::new(&address, &auction, &clock))
+                .filter(|auction| status.is_none_or(|status| auction.status == status))
+                .collect();
+            view::print_list(&auctions, output, "No auctions found");
+        }
+
+        Command::ListBids { bidder } => {
+            let bidder = bidder.unwrap_or_else(|| keypair.pubkey());
+            let bids = fetch_program_accounts::<BidState>(
+                client,
+                vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    BID_STATE_BIDDER_OFFSET,
+                    bidder.as_ref(),
+                ))],
+            )
+            .unwrap_or_else(|err| exit_with(&err));
+
+            // the auctions of settled bids are gone, those show up as closed
+            let auction_addresses: Vec<_> = bids.iter().map(|(_, bid)| bid.auction).collect();
+            let auctions: Vec<_> = auction_addresses
+                .chunks(100)
+                .flat_map(|chunk| {
+                    client
+                        .get_multiple_accounts(chunk)
+                        .expect("could not get auction accounts")
+                })
+                .collect();
+            let clock = fetch_clock(client);
+
+            let bids: Vec<_> = bids
+                .iter()
+                .zip(auctions)
+                .map(|((address, bid), account)| {
+                    let auction =
+                        account.and_then(|account| Auction::from_account_data(&account.data));
+                    BidView::new(address, bid, auction.as_ref(), &clock)
+                })
+                .collect();
+            view::print_list(&bids, output, "No bids found");
+        }
+
+        Command::ShowStats { wallet } => {
+            let wallet = wallet.unwrap_or_else(|| keypair.pubkey());
+            let address = derive_user_stats(&auction_house, &wallet);
+            let stats_account = client
+                .get_account(&address)
+                .unwrap_or_else(|err| exit_with(&err));
+            let stats = UserStats::from_account_data(&stats_account.data)
+                .expect("invalid user stats account data");
+
+            let mints: Vec<_> = stats.volume.iter().map(|volume| volume.mint).collect();
+            let decimals: HashMap<_, _> = mints
+                .iter()
+                .zip(
+                    client
+                        .get_multiple_accounts(&mints)
+                        .expect("could not get mint accounts"),
+                )
+                .filter_map(|(mint, account)| {
+                    let mint_account = Mint::unpack(&account?.data).ok()?;
+                    Some((*mint, mint_account.decimals))
+                })
+                .collect();
+            view::print(&StatsView::new(&address, &stats, &decimals), output);
+        }
+
+        Command::Watch => {
+            let (_subscription, receiver) = PubsubClient::logs_subscribe(
+                &ws_url,
+                RpcTransactionLogsFilter::Mentions(vec![auction_client::ID.to_string()]),
+                RpcTransactionLogsConfig {
+                    commitment: Some(client.commitment()),
+                },
+            )
+            .expect("could not subscribe to program logs");
+
+            println!("Watching auction events on {}", ws_url);
+            for response in receiver {
+                let logs = response.value;
+                if logs.err.is_some() {
+                    continue;
+                }
+                for data in logs
+                    .logs
+                    .iter()
+                    .filter_map(|log| log.strip_prefix("Program data: "))
+                    .filter_map(|data| BASE64_STANDARD.decode(data).ok())
+                {
+                    if let Some(event) = decode_event(&data) {
+                        println!("{}: {:#?}", logs.signature, event);
+                    }
+                }
+            }
+        }
+
+        Command::Follow {
+            listing_mint,
+            purchase_mint,
+            seed,
+            seller,
+            max_price,
+            increment,
+            interval,
+            allowlist,
+            gate_nft,
+        } => {
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+            let bidder = derive_bidder_keys(&keypair.pubkey(), &keys);
+            let mut auction = fetch_auction(client, &keys.auction);
+            let proxy = max_price.map(|max_price| {
+                assert!(
+                    auction.kind == AuctionKind::English,
+                    "only english auctions can be bid on by proxy"
+                );
+                let price = |price: &str| decimal_to_u64(price, auction.decimal);
+                Proxy {
+                    max_price: price(&max_price).expect("invalid max price"),
+                    increment: increment
+                        .as_deref()
+                        .map_or(Ok(0), price)
+                        .expect("invalid increment"),
+                }
+            });
+            let (proof, gate_keys) = gate_accounts(
+                client,
+                &auction,
+                &bidder.bidder,
+                allowlist.as_deref(),
+                gate_nft,
+            );
+
+            let (_subscription, receiver) = PubsubClient::account_subscribe(
+                &ws_url,
+                &keys.auction,
+                Some(RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(client.commitment()),
+                    ..RpcAccountInfoConfig::default()
+                }),
+            )
+            .expect("could not subscribe to the auction account");
+
+            println!("Following auction {} on {}", keys.auction, ws_url);
+            let mut priced_out = false;
+            loop {
+                let clock = fetch_clock(client);
+                let view = AuctionView::new(&keys.auction, &auction, &clock);
+                println!("slot {}: {}", clock.slot, view.summary());
+                if matches!(view.status, AuctionStatus::Ended | AuctionStatus::Cancelled) {
+                    break;
+                }
+
+                match proxy
+                    .map(|proxy| proxy.next_action(&auction, &bidder.bidder, auction.now(&clock)))
+                {
+                    Some(ProxyAction::Bid(price)) => {
+                        // an outbid bid is raised, a withdrawn or missing one placed anew
+                        let has_bid = client
+                            .get_account_with_commitment(&bidder.bid_state, client.commitment())
+                            .is_ok_and(|response| response.value.is_some());
+                        let instruction = if has_bid {
+                            instructions::raise_bid(
+                                &keys,
+                                &bidder,
+                                &RaiseBidArgs {
+                                    price,
+                                    max_price: None,
+                                },
+                            )
+                        } else {
+                            instructions::bid(
+                                &keys,
+                                &bidder,
+                                gate_keys.as_ref(),
+                                &BidArgs {
+                                    price,
+                                    proof: proof.clone(),
+                                    max_price: None,
+                                },
+                            )
+                        };
+                        let price = u64_to_decimal(price, auction.decimal);
+                        match send(&context, &[instruction]) {
+                            Ok(signature) => {
+                                println!("Bid {}: {}", price, signature);
+                                // don't bid again on the state from before the bid
+                                auction = fetch_auction(client, &keys.auction);
+                                continue;
+                            }
+                            Err(err) => eprintln!("could not bid {}: {}", price, describe(&err)),
+                        }
+                    }
+                    Some(ProxyAction::PricedOut(min_bid)) if !priced_out => {
+                        priced_out = true;
+                        println!(
+                            "Min bid {} is over the max price, no longer bidding",
+                            u64_to_decimal(min_bid, auction.decimal)
+                        );
+                    }
+                    _ => {}
+                }
+
+                match receiver.recv_timeout(Duration::from_secs(interval)) {
+                    Ok(response) => match response
+                        .value
+                        .decode::<Account>()
+                        .and_then(|account| Auction::from_account_data(&account.data))
+                    {
+                        Some(update) => auction = update,
+                        None => {
+                            println!("Auction {} was closed", keys.auction);
+                            break;
+                        }
+                    },
+                    Err(err) if err.is_timeout() => {}
+                    Err(err) => panic!("lost the auction subscription: {}", err),
+                }
+            }
+        }
+
+        Command::Crank {
+            interval,
+            once,
+            chunk,
+        } => loop {
+            crank(&context, &auction_house, chunk);
+            if once {
+                break;
+            }
+            std::thread::sleep(Duration::from_secs(interval));
+        },
+
+        Command::InitAuction {
+            listing_mint,
+            purchase_mint,
+            seed,
+            starting_price,
+            buy_now_price,
+            end_slot,
+            end_time,
+            amount,
+            decimals,
+            extension_window,
+            extension,
+            max_extension,
+            min_increment,
+            min_increment_bps,
+            reserve_price,
+            reserve_secret,
+            allowlist,
+            gate_mint,
+            gate_min_amount,
+            gate_collection,
+        } => {
+            let seller = keypair.pubkey();
+            let (end_condition, end) = auction_end(end_slot, end_time);
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+
+            let starting_price = decimal_to_u64(&starting_price, decimals).expect("invalid price");
+            let buy_now_price = buy_now_price
+                .map(|price| decimal_to_u64(&price, decimals).expect("invalid buy now price"))
+                .unwrap_or_default();
+            let min_increment =
+                decimal_to_u64(&min_increment, decimals).expect("invalid min increment");
+            let reserve_price = reserve_price
+                .map(|price| decimal_to_u64(&price, decimals).expect("invalid reserve price"))
+                .unwrap_or_default();
+            // a hidden reserve is only published as a hash until it is revealed
+            let (reserve_price, reserve_hash) = match reserve_secret {
+                Some(secret) => (0, hidden_reserve_hash(reserve_price, &secret)),
+                None => (reserve_price, [0; 32]),
+            };
+
+            let listing_mint_account = client
+                .get_account(&listing_mint)
+                .expect("could not get listing mint account");
+            let listing_mint_account =
+                Mint::unpack(&listing_mint_account.data).expect("invalid mint account data");
+            let listing_mint_decimals = listing_mint_account.decimals;
+            let amount = decimal_to_u64(&amount, listing_mint_decimals).expect("invalid amount");
+
+            let gate = if let Some(allowlist) = allowlist {
+                let root = allowlist_root(&read_allowlist(&allowlist));
+                Some(Gate::Allowlist { root })
+            } else if let Some(mint) = gate_mint {
+                let gate_mint_account = client
+                    .get_account(&mint)
+                    .expect("could not get gate mint account");
+                let gate_mint_account =
+                    Mint::unpack(&gate_mint_account.data).expect("invalid mint account data");
+                let min_amount = decimal_to_u64(&gate_min_amount, gate_mint_account.decimals)
+                    .expect("invalid gate min amount");
+                Some(Gate::Token { mint, min_amount })
+            } else {
+                gate_collection.map(|collection| Gate::Collection { collection })
+            };
+
+            let nft = fetch_nft_keys(
+                client,
+                &listing_mint,
+                &keys.seller_listing_mint_ata,
+                &keys.vault,
+            )
+            .expect("could not get listing mint metadata");
+
+            let instruction = instructions::init_auction(
+                &keys,
+                &nft,
+                &InitAuctionArgs {
+                    starting_price,
+                    buy_now_price,
+                    end_condition,
+                    end,
+                    amount,
+                    decimal: decimals,
+                    extension_window,
+                    extension,
+                    max_extension,
+                    min_increment,
+                    min_increment_bps,
+                    reserve_price,
+                    reserve_hash,
+                    gate,
+                },
+            );
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Initialized auction account: {}, auction:{}, vault: {}",
+                signature, keys.auction, keys.vault,
+            );
+        }
+
+        Command::InitDutchAuction {
+            listing_mint,
+            purchase_mint,
+            seed,
+            start_price,
+            floor_price,
+            step,
+            end_slot,
+            end_time,
+            amount,
+            decimals,
+        } => {
+            let seller = keypair.pubkey();
+            let (end_condition, end) = auction_end(end_slot, end_time);
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+
+            let start_price = decimal_to_u64(&start_price, decimals).expect("invalid price");
+            let floor_price = decimal_to_u64(&floor_price, decimals).expect("invalid price");
+
+            let listing_mint_account = client
+                .get_account(&listing_mint)
+                .expect("could not get listing mint account");
+            let listing_mint_account =
+                Mint::unpack(&listing_mint_account.data).expect("invalid mint account data");
+            let amount =
+                decimal_to_u64(&amount, listing_mint_account.decimals).expect("invalid amount");
+
+            let nft = fetch_nft_keys(
+                client,
+                &listing_mint,
+                &keys.seller_listing_mint_ata,
+                &keys.vault,
+            )
+            .expect("could not get listing mint metadata");
+
+            let instruction = instructions::init_dutch_auction(
+                &keys,
+                &nft,
+                &InitDutchAuctionArgs {
+                    start_price,
+                    floor_price,
+                    step,
+                    end_condition,
+                    end,
+                    amount,
+                    decimal: decimals,
+                },
+            );
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Initialized dutch auction account: {}, auction:{}, vault: {}",
+                signature, keys.auction, keys.vault,
+            );
+        }
+
+        Command::InitBatchAuction {
+            listing_mint,
+            purchase_mint,
+            seed,
+            min_price,
+            end_slot,
+            end_time,
+            amount,
+            decimals,
+        } => {
+            let seller = keypair.pubkey();
+            let (end_condition, end) = auction_end(end_slot, end_time);
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+
+            let min_price = decimal_to_u64(&min_price, decimals).expect("invalid price");
+
+            let listing_mint_account = client
+                .get_account(&listing_mint)
+                .expect("could not get listing mint account");
+            let listing_mint_account =
+                Mint::unpack(&listing_mint_account.data).expect("invalid mint account data");
+            let amount =
+                decimal_to_u64(&amount, listing_mint_account.decimals).expect("invalid amount");
+
+            let nft = fetch_nft_keys(
+                client,
+                &listing_mint,
+                &keys.seller_listing_mint_ata,
+                &keys.vault,
+            )
+            .expect("could not get listing mint metadata");
+
+            let instruction = instructions::init_batch_auction(
+                &keys,
+                &nft,
+                &InitBatchAuctionArgs {
+                    min_price,
+                    end_condition,
+                    end,
+                    amount,
+                    decimal: decimals,
+                },
+            );
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Initialized batch auction account: {}, auction:{}, vault: {}",
+                signature, keys.auction, keys.vault,
+            );
+        }
+
+        Command::BatchBid {
+            listing_mint,
+            purchase_mint,
+            seed,
+            seller,
+            quantity,
+            price,
+            decimals,
+        } => {
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+            let bidder = derive_bidder_keys(&keypair.pubkey(), &keys);
+
+            let price = decimal_to_u64(&price, decimals).expect("invalid price");
+            let listing_mint_account = client
+                .get_account(&listing_mint)
+                .expect("could not get listing mint account");
+            let listing_mint_account =
+                Mint::unpack(&listing_mint_account.data).expect("invalid mint account data");
+            let quantity =
+                decimal_to_u64(&quantity, listing_mint_account.decimals).expect("invalid quantity");
+
+            // the new bid goes after every bid offering at least as much
+            let AuctionKind::Batch { head, .. } = fetch_auction(client, &keys.auction).kind else {
+                panic!("{} is not a batch auction", keys.auction);
+            };
+            let bids = fetch_bids(client, &keys.auction);
+            let prev = batch_bid_order(&bids, head)
+                .into_iter()
+                .take_while(|key| bids[key].price >= price)
+                .last();
+            let next = match prev {
+                Some(prev) => bids[&prev].next,
+                None => head,
+            };
+
+            let instruction = instructions::batch_bid(
+                &keys,
+                &bidder,
+                prev.as_ref(),
+                next.as_ref(),
+                &BatchBidArgs { quantity, price },
+            );
+            let signature = run(&context, &[instruction]);
+            println!("Placed batch bid {}: {}", bidder.bid_state, signature);
+        }
+
+        Command::SettleBatch {
+            listing_mint,
+            purchase_mint,
+            seed,
+            seller,
+            chunk,
+        } => {
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+            settle_batch(&context, &keys, chunk).unwrap_or_else(|err| exit_with(&err));
+        }
 
-    match command {
-        Command::InitHouse { fee } => {
-            println!(
-                "Initializing house with fee: {} and name: {}",
-                fee, auction_house_name
+        Command::BuyNow {
+            listing_mint,
+            purchase_mint,
+            seed,
+            seller,
+            max_price,
+            decimals,
+        } => {
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
             );
+            let buyer = derive_bidder_keys(&keypair.pubkey(), &keys);
 
-            let recent_blockhash = client
-                .get_latest_blockhash()
-                .expect("recent blockhash exists");
-            let transaction = AuctionProgram::init_house(
-                &[
-                    &keypair.pubkey(),
-                    &auction_house,
-                    &solana_sdk::system_program::id(),
-                ],
-                &InitHouseArgs {
-                    fee,
-                    name: auction_house_name,
+            let instruction = instructions::buy_now(
+                &keys,
+                &buyer,
+                &BuyNowArgs {
+                    max_price: decimal_to_u64(&max_price, decimals).expect("invalid price"),
                 },
-                Some(&keypair.pubkey()),
-                &[&keypair],
-                recent_blockhash,
             );
-            let signature = client
-                .send_and_confirm_transaction(&transaction)
-                .expect("confirmed transaction");
+            let signature = run(&context, &[instruction]);
             println!(
-                "Initialized auction house account: {} at {}",
-                signature, auction_house
-            )
+                "Bought auction {}: {}, tokens at {}",
+                keys.auction, signature, buyer.bidder_listing_mint_ata
+            );
         }
 
-        Command::InitAuction {
+        Command::InitSealedAuction {
             listing_mint,
             purchase_mint,
+            seed,
             starting_price,
             end_slot,
+            end_time,
+            reveal_end_slot,
+            reveal_end_time,
             amount,
             decimals,
+            second_price,
+            penalty_bps,
         } => {
             let seller = keypair.pubkey();
-            let AuctionSellerKeys {
-                auction,
-                vault,
-                seller_listing_mint_ata,
-                ..
-            } = derive_auction_keys(&auction_house, &listing_mint, &purchase_mint, &seller);
+            let (end_condition, end) = auction_end(end_slot, end_time);
+            let (_, reveal_end) = auction_end(reveal_end_slot, reveal_end_time);
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
 
             let starting_price = decimal_to_u64(&starting_price, decimals).expect("invalid price");
 
@@ -202,318 +1745,780 @@ fn main() {
                 .expect("could not get listing mint account");
             let listing_mint_account =
                 Mint::unpack(&listing_mint_account.data).expect("invalid mint account data");
-            let listing_mint_decimals = listing_mint_account.decimals;
-            let amount = decimal_to_u64(&amount, listing_mint_decimals).expect("invalid amount");
+            let amount =
+                decimal_to_u64(&amount, listing_mint_account.decimals).expect("invalid amount");
 
-            let recent_blockhash = client
-                .get_latest_blockhash()
-                .expect("recent blockhash exists");
-            let transaction = AuctionProgram::init_auction(
-                &[
-                    &seller,
-                    &auction_house,
-                    &auction,
-                    &listing_mint,
-                    &purchase_mint,
-                    &seller_listing_mint_ata,
-                    &vault,
-                    &spl_associated_token_account::ID,
-                    &solana_sdk::system_program::ID,
-                    &spl_token::ID,
-                ],
-                &InitAuctionArgs {
+            let nft = fetch_nft_keys(
+                client,
+                &listing_mint,
+                &keys.seller_listing_mint_ata,
+                &keys.vault,
+            )
+            .expect("could not get listing mint metadata");
+
+            let instruction = instructions::init_sealed_auction(
+                &keys,
+                &nft,
+                &InitSealedAuctionArgs {
                     starting_price,
-                    end: end_slot,
+                    end_condition,
+                    end,
+                    reveal_end,
                     amount,
                     decimal: decimals,
+                    second_price,
+                    penalty_bps,
                 },
-                Some(&seller),
-                &[&keypair],
-                recent_blockhash,
             );
-            let signature = client
-                .send_and_confirm_transaction(&transaction)
-                .expect("confirmed transaction");
+            let signature = run(&context, &[instruction]);
             println!(
-                "Initialized auction account: {}, auction:{}, vault: {}",
-                signature, auction, vault,
+                "Initialized sealed auction account: {}, auction:{}, vault: {}",
+                signature, keys.auction, keys.vault,
+            );
+        }
+
+        Command::CommitBid {
+            listing_mint,
+            purchase_mint,
+            seed,
+            seller,
+            price,
+            secret,
+            deposit,
+            decimals,
+        } => {
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+            let bidder = derive_bidder_keys(&keypair.pubkey(), &keys);
+
+            let price = decimal_to_u64(&price, decimals).expect("invalid price");
+            let purchase_mint_account = client
+                .get_account(&purchase_mint)
+                .expect("could not get purchase mint account");
+            let purchase_mint_account =
+                Mint::unpack(&purchase_mint_account.data).expect("invalid mint account data");
+            let deposit =
+                decimal_to_u64(&deposit, purchase_mint_account.decimals).expect("invalid deposit");
+
+            let instruction = instructions::commit_bid(
+                &keys,
+                &bidder,
+                &CommitBidArgs {
+                    commitment: sealed_bid_commitment(price, &secret, &bidder.bidder),
+                    deposit,
+                },
+            );
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Committed sealed bid: {} at {}",
+                signature, bidder.bid_escrow
+            )
+        }
+
+        Command::RevealBid {
+            listing_mint,
+            purchase_mint,
+            seed,
+            seller,
+            price,
+            secret,
+            decimals,
+        } => {
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+            let bidder = derive_bidder_keys(&keypair.pubkey(), &keys);
+
+            let instruction = instructions::reveal_bid(
+                &keys,
+                &bidder,
+                &RevealBidArgs {
+                    price: decimal_to_u64(&price, decimals).expect("invalid price"),
+                    salt: secret_salt(&secret),
+                },
+            );
+            let signature = run(&context, &[instruction]);
+            println!("Revealed sealed bid: {} for {}", signature, keys.auction)
+        }
+
+        Command::PenalizeBid {
+            listing_mint,
+            purchase_mint,
+            seed,
+            bidder,
+        } => {
+            let seller = keypair.pubkey();
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+            let bidder = derive_bidder_keys(&bidder, &keys);
+
+            let instruction = instructions::penalize_bid(&seller, &keys, &bidder);
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Penalized unrevealed bid: {} into {}",
+                signature, keys.seller_purchase_mint_ata
+            )
+        }
+
+        Command::RevealReserve {
+            listing_mint,
+            purchase_mint,
+            seed,
+            reserve_price,
+            reserve_secret,
+            decimals,
+        } => {
+            let seller = keypair.pubkey();
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+
+            let instruction = instructions::reveal_reserve(
+                &keys,
+                &RevealReserveArgs {
+                    reserve_price: decimal_to_u64(&reserve_price, decimals)
+                        .expect("invalid reserve price"),
+                    salt: secret_salt(&reserve_secret),
+                },
             );
+            let signature = run(&context, &[instruction]);
+            println!("Revealed reserve price: {} for {}", signature, keys.auction);
         }
 
         Command::Bid {
             listing_mint,
             purchase_mint,
+            seed,
             seller,
             price,
+            max_price,
             decimals,
+            allowlist,
+            gate_nft,
         } => {
             println!("Placing bid with price: {} (decimal={})", price, decimals);
-            let AuctionSellerKeys { auction, vault, .. } =
-                derive_auction_keys(&auction_house, &listing_mint, &purchase_mint, &seller);
-
-            let bidder = keypair.pubkey();
-            let BidderKeys {
-                bidder_purchase_mint_ata,
-                bid_state,
-                bid_escrow,
-                ..
-            } = derive_bidder_keys(&bidder, &purchase_mint, &listing_mint, &auction);
-
-            let recent_blockhash = client
-                .get_latest_blockhash()
-                .expect("recent blockhash exists");
-            let transaction = AuctionProgram::bid(
-                &[
-                    &bidder,
-                    &listing_mint,
-                    &purchase_mint,
-                    &auction_house,
-                    &auction,
-                    &bidder_purchase_mint_ata,
-                    &bid_state,
-                    &bid_escrow,
-                    &vault,
-                    &spl_associated_token_account::ID,
-                    &spl_token::ID,
-                    &solana_sdk::system_program::ID,
-                ],
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+            let bidder = derive_bidder_keys(&keypair.pubkey(), &keys);
+
+            let auction = fetch_auction(client, &keys.auction);
+            let (proof, gate_keys) = gate_accounts(
+                client,
+                &auction,
+                &bidder.bidder,
+                allowlist.as_deref(),
+                gate_nft,
+            );
+
+            let instruction = instructions::bid(
+                &keys,
+                &bidder,
+                gate_keys.as_ref(),
                 &BidArgs {
                     price: decimal_to_u64(&price, decimals).expect("invalid price"), // TODO: make sure decimals matches auction decimals.
+                    proof,
+                    max_price: max_price.map(|max_price| {
+                        decimal_to_u64(&max_price, decimals).expect("invalid max price")
+                    }),
+                },
+            );
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Placed bid and bid state: {} at {}",
+                signature, bidder.bid_escrow
+            )
+        }
+
+        Command::RaiseBid {
+            listing_mint,
+            purchase_mint,
+            seed,
+            seller,
+            price,
+            max_price,
+            decimals,
+        } => {
+            println!("Raising bid to price: {} (decimal={})", price, decimals);
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+            let bidder = derive_bidder_keys(&keypair.pubkey(), &keys);
+
+            let instruction = instructions::raise_bid(
+                &keys,
+                &bidder,
+                &RaiseBidArgs {
+                    price: decimal_to_u64(&price, decimals).expect("invalid price"),
+                    max_price: max_price.map(|max_price| {
+                        decimal_to_u64(&max_price, decimals).expect("invalid max price")
+                    }),
                 },
-                Some(&bidder),
-                &[&keypair],
-                recent_blockhash,
             );
-            let signature = client
-                .send_and_confirm_transaction(&transaction)
-                .expect("confirmed transaction");
-            println!("Placed bid and bid state: {} at {}", signature, bid_escrow)
+            let signature = run(&context, &[instruction]);
+            println!("Raised bid: {} at {}", signature, bidder.bid_escrow)
         }
 
         Command::Withdraw {
             purchase_mint,
+            seed,
             listing_mint,
             seller,
         } => {
-            let AuctionSellerKeys { auction, .. } =
-                derive_auction_keys(&auction_house, &listing_mint, &purchase_mint, &seller);
-
-            let bidder = keypair.pubkey();
-            let BidderKeys {
-                bidder_purchase_mint_ata,
-                bid_state,
-                bid_escrow,
-                ..
-            } = derive_bidder_keys(&bidder, &purchase_mint, &listing_mint, &auction);
-            println!("bid_purchase_mint_ata={bidder_purchase_mint_ata}");
-            println!("bid_state={bid_state}");
-            println!("bid_escrow={bid_escrow}");
-
-            let recent_blockhash = client
-                .get_latest_blockhash()
-                .expect("recent blockhash exists");
-
-            let transaction = AuctionProgram::withdraw(
-                &[
-                    &bidder,
-                    &purchase_mint,
-                    &auction_house,
-                    &auction,
-                    &bidder_purchase_mint_ata,
-                    &bid_escrow,
-                    &bid_state,
-                    &spl_associated_token_account::ID,
-                    &spl_token::ID,
-                    &solana_sdk::system_program::ID,
-                ],
-                Some(&bidder),
-                &[&keypair],
-                recent_blockhash,
-            );
-            let signature = client
-                .send_and_confirm_transaction(&transaction)
-                .expect("confirmed transaction");
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+            let bidder = derive_bidder_keys(&keypair.pubkey(), &keys);
+            println!("bid_state={}", bidder.bid_state);
+            println!("bid_escrow={}", bidder.bid_escrow);
+
+            let instruction = instructions::withdraw(&keys, &bidder);
+            let signature = run(&context, &[instruction]);
             println!(
                 "Withdrawed bid:  {} from {} to {}",
-                signature, bid_escrow, bidder_purchase_mint_ata
+                signature, bidder.bid_escrow, bidder.bidder_purchase_mint_ata
             );
         }
 
         Command::Finalize {
-            admin,
             listing_mint,
             purchase_mint,
+            seed,
             seller,
             bidder,
         } => {
             println!("Finalizing and close auction");
-            let AuctionSellerKeys {
-                auction,
-                vault,
-                seller_purchase_mint_ata,
-                ..
-            } = derive_auction_keys(&auction_house, &listing_mint, &purchase_mint, &seller);
-
-            let BidderKeys {
-                bidder_listing_mint_ata,
-                bid_state,
-                bid_escrow,
-                ..
-            } = derive_bidder_keys(&bidder, &purchase_mint, &listing_mint, &auction);
-
-            let house_purchase_mint_ata =
-                spl_associated_token_account::get_associated_token_address(&admin, &purchase_mint);
-
-            let recent_blockhash = client
-                .get_latest_blockhash()
-                .expect("recent blockhash exists");
-
-            let accounts = &[
-                &keypair.pubkey(),
-                &seller,
-                &bidder,
-                &admin,
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
                 &listing_mint,
                 &purchase_mint,
-                &auction_house,
-                &auction,
-                &bid_state,
-                &bid_escrow,
-                &vault,
-                &bidder_listing_mint_ata,
-                &seller_purchase_mint_ata,
-                &house_purchase_mint_ata,
-                &spl_associated_token_account::ID,
-                &spl_token::ID,
-                &solana_sdk::system_program::ID,
-            ];
-            println!("accounts={accounts:#?}");
-            let transaction = AuctionProgram::finalize(
-                accounts,
-                Some(&keypair.pubkey()),
-                &[&keypair],
-                recent_blockhash,
-            );
-
-            let signature = client
-                .send_and_confirm_transaction(&transaction)
-                .expect("confirmed transaction");
-            println!("finalize bid {}, auctioneer token account {}, bidder token {}, auction house fee {} ",
-            signature, seller_purchase_mint_ata, bidder_listing_mint_ata, house_purchase_mint_ata);
+                &seller,
+                seed,
+            );
+            let signature =
+                finalize(&context, &keys, &bidder).unwrap_or_else(|err| exit_with(&err));
+            println!("finalize bid {}", signature);
         }
 
         Command::Cancel {
             listing_mint,
             purchase_mint,
+            seed,
         } => {
             println!("Auctioneer withdraw and cancel auction due to unsuccessful auction");
             let seller = keypair.pubkey();
-            let AuctionSellerKeys {
-                auction,
-                vault,
-                seller_listing_mint_ata,
-                ..
-            } = derive_auction_keys(&auction_house, &listing_mint, &purchase_mint, &seller);
-
-            let recent_blockhash = client
-                .get_latest_blockhash()
-                .expect("recent blockhash exists");
-
-            let transaction = AuctionProgram::cancel(
-                &[
-                    &seller,
-                    &auction_house,
-                    &auction,
-                    &listing_mint,
-                    &purchase_mint,
-                    &seller_listing_mint_ata,
-                    &vault,
-                    &spl_associated_token_account::ID,
-                    &solana_sdk::system_program::ID,
-                    &spl_token::ID,
-                ],
-                Some(&seller),
-                &[&keypair],
-                recent_blockhash,
-            );
-
-            let signature = client
-                .send_and_confirm_transaction(&transaction)
-                .expect("confirmed transaction");
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+
+            let nft = fetch_nft_keys(
+                client,
+                &listing_mint,
+                &keys.vault,
+                &keys.seller_listing_mint_ata,
+            )
+            .expect("could not get listing mint metadata");
 
+            let instruction = instructions::cancel(&keys, &nft);
+            let signature = run(&context, &[instruction]);
             println!(
                 "Canceled Auction {} at {}",
-                signature, seller_listing_mint_ata
+                signature, keys.seller_listing_mint_ata
             );
         }
+
+        Command::CancelEarly {
+            listing_mint,
+            purchase_mint,
+            seed,
+            bidder,
+        } => {
+            let seller = keypair.pubkey();
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+            let bidder = bidder.map(|bidder| derive_bidder_keys(&bidder, &keys));
+            let nft = fetch_nft_keys(
+                client,
+                &listing_mint,
+                &keys.vault,
+                &keys.seller_listing_mint_ata,
+            )
+            .expect("could not get listing mint metadata");
+
+            let instruction = instructions::cancel_early(&keys, &nft, bidder.as_ref());
+            let signature = run(&context, &[instruction]);
+            println!(
+                "Canceled live auction {}, bidders can now withdraw from {}",
+                signature, keys.auction
+            );
+        }
+
+        Command::CloseCancelled {
+            listing_mint,
+            purchase_mint,
+            seed,
+        } => {
+            let seller = keypair.pubkey();
+            let keys = fetch_auction_keys(
+                client,
+                &auction_house,
+                &listing_mint,
+                &purchase_mint,
+                &seller,
+                seed,
+            );
+
+            let instruction = instructions::close_cancelled(&keys);
+            let signature = run(&context, &[instruction]);
+            println!("Closed cancelled auction {}: {}", keys.auction, signature);
+        }
     }
 }
 
-struct AuctionSellerKeys {
-    auction: Pubkey,
-    vault: Pubkey,
-    seller_listing_mint_ata: Pubkey,
-    seller_purchase_mint_ata: Pubkey,
+fn send(context: &Context, instructions: &[Instruction]) -> ClientResult<Signature> {
+    let mut signers: Vec<&dyn Signer> = vec![&context.keypair];
+    let payer = match &context.fee_payer {
+        Some(fee_payer) if fee_payer.pubkey() != context.keypair.pubkey() => {
+            signers.push(fee_payer);
+            fee_payer.pubkey()
+        }
+        _ => context.keypair.pubkey(),
+    };
+    let instructions = [&context.compute_budget, instructions].concat();
+
+    let recent_blockhash = context.client.get_latest_blockhash()?;
+    let transaction =
+        Transaction::new_signed_with_payer(&instructions, Some(&payer), &signers, recent_blockhash);
+    Ok(context.client.send_and_confirm_transaction(&transaction)?)
 }
 
-fn derive_auction_keys(
-    auction_house: &Pubkey,
-    listing_mint: &Pubkey,
-    purchase_mint: &Pubkey,
-    seller: &Pubkey,
-) -> AuctionSellerKeys {
-    let (auction, _auction_bump) = Pubkey::find_program_address(
-        &[
-            b"auction",
-            auction_house.as_ref(),
-            seller.as_ref(),
-            listing_mint.as_ref(),
-            purchase_mint.as_ref(),
-        ],
-        &AuctionProgram::id(),
+fn run(context: &Context, instructions: &[Instruction]) -> Signature {
+    send(context, instructions).unwrap_or_else(|err| exit_with(&err))
+}
+
+// names the auction program error behind a failed transaction when there is one
+fn describe(err: &ClientError) -> String {
+    match err
+        .get_transaction_error()
+        .as_ref()
+        .and_then(AuctionError::from_transaction_error)
+    {
+        Some(auction_error) => format!("auction program error: {auction_error}"),
+        None => err.to_string(),
+    }
+}
+
+fn exit_with(err: &ClientError) -> ! {
+    eprintln!("{}", describe(err));
+    std::process::exit(1)
+}
+
+// one pass of the keeper: settles every ended auction of `auction_house`.
+// Failures are only reported, the next pass retries them.
+fn crank(context: &Context, auction_house: &Pubkey, chunk: usize) {
+    let client = &context.client;
+    let clock: Clock = match client
+        .get_account(&sysvar::clock::ID)
+        .map(|account| from_account(&account))
+    {
+        Ok(Some(clock)) => clock,
+        Ok(None) => return eprintln!("invalid clock sysvar"),
+        Err(err) => return eprintln!("could not get clock: {}", err),
+    };
+
+    let auctions = match fetch_program_accounts::<Auction>(client, vec![]) {
+        Ok(auctions) => auctions,
+        Err(err) => return eprintln!("could not get auction accounts: {}", err),
+    };
+
+    for (auction, state) in auctions {
+        // auctions don't store their house, only their address tells
+        let address = derive_auction(
+            auction_house,
+            &state.mint_a,
+            &state.mint_b,
+            &state.seller,
+            state.seed,
+        );
+        let now = state.now(&clock);
+        if auction != address || state.cancelled || now < state.settles_at() {
+            continue;
+        }
+        let keys = match fetch_token_program(client, &state.mint_a) {
+            Ok(token_program) => derive_auction_keys(
+                auction_house,
+                &state.mint_a,
+                &state.mint_b,
+                &state.seller,
+                state.seed,
+                &token_program,
+            ),
+            Err(err) => {
+                eprintln!(
+                    "could not get mint of auction {}: {}",
+                    auction,
+                    describe(&err)
+                );
+                continue;
+            }
+        };
+
+        let result = match (state.kind, state.bidder) {
+            // the seller may still reveal a hidden reserve
+            (_, Some(_)) if now < state.finalizes_at() => continue,
+            // nothing settles before every unrevealed bid is penalized
+            (AuctionKind::Sealed { unrevealed, .. }, _) if unrevealed > 0 => {
+                penalize_unrevealed(context, &keys)
+                    .map(|count| format!("penalized {} unrevealed bids", count))
+            }
+            (AuctionKind::Batch { head: Some(_), .. }, _) => {
+                settle_batch(context, &keys, chunk).map(|()| "settled batch".to_string())
+            }
+            (_, Some(bidder)) => finalize(context, &keys, &bidder)
+                .map(|signature| format!("finalized: {}", signature)),
+            (_, None) => {
+                cancel_expired(context, &keys).map(|signature| format!("cancelled: {}", signature))
+            }
+        };
+        match result {
+            Ok(outcome) => println!("Auction {} {}", auction, outcome),
+            Err(err) => eprintln!("could not settle auction {}: {}", auction, describe(&err)),
+        }
+    }
+}
+
+fn finalize(context: &Context, keys: &AuctionKeys, bidder: &Pubkey) -> ClientResult<Signature> {
+    let Context {
+        client, keypair, ..
+    } = context;
+    let bidder = derive_bidder_keys(bidder, keys);
+    let payer_purchase_mint_ata = fetch_payer_purchase_mint_ata(context, keys)?;
+
+    // the NFT goes back to the seller when the reserve isn't met
+    let state = Auction::from_account_data(&client.get_account(&keys.auction)?.data)
+        .expect("invalid auction account data");
+    let destination = if state.reserve_met(state.now(&fetch_clock(client))) {
+        bidder.bidder_listing_mint_ata
+    } else {
+        keys.seller_listing_mint_ata
+    };
+    let nft = fetch_nft_keys(client, &keys.listing_mint, &keys.vault, &destination)?;
+
+    let instruction = instructions::finalize(
+        &keypair.pubkey(),
+        payer_purchase_mint_ata.as_ref(),
+        keys,
+        &bidder,
+        &nft,
     );
-    let vault = spl_associated_token_account::get_associated_token_address(&auction, listing_mint);
-    let seller_listing_mint_ata =
-        spl_associated_token_account::get_associated_token_address(seller, listing_mint);
-    let seller_purchase_mint_ata =
-        spl_associated_token_account::get_associated_token_address(seller, purchase_mint);
-
-    AuctionSellerKeys {
-        auction,
-        vault,
-        seller_listing_mint_ata,
-        seller_purchase_mint_ata,
+    send(context, &[instruction])
+}
+
+// takes the penalty for the seller from every sealed bid left unrevealed,
+// returns how many there were
+fn penalize_unrevealed(context: &Context, keys: &AuctionKeys) -> ClientResult<usize> {
+    let Context {
+        client, keypair, ..
+    } = context;
+    let unrevealed: Vec<_> = fetch_bids(client, &keys.auction)
+        .into_values()
+        .filter(|bid| bid.commitment.is_some())
+        .collect();
+    for bid in &unrevealed {
+        let bidder = derive_bidder_keys(&bid.bidder, keys);
+        send(
+            context,
+            &[instructions::penalize_bid(&keypair.pubkey(), keys, &bidder)],
+        )?;
+    }
+    Ok(unrevealed.len())
+}
+
+// tallies and then fills a batch auction chunk by chunk until it is closed
+fn settle_batch(context: &Context, keys: &AuctionKeys, chunk: usize) -> ClientResult<()> {
+    let Context {
+        client, keypair, ..
+    } = context;
+    let bids = fetch_bids(client, &keys.auction);
+    let payer_purchase_mint_ata = fetch_payer_purchase_mint_ata(context, keys)?;
+
+    loop {
+        let Some(auction_account) = client
+            .get_account_with_commitment(&keys.auction, client.commitment())?
+            .value
+        else {
+            println!("Batch auction {} is settled", keys.auction);
+            return Ok(());
+        };
+        let auction_account = Auction::from_account_data(&auction_account.data)
+            .expect("invalid auction account data");
+        let AuctionKind::Batch {
+            cursor, filling, ..
+        } = auction_account.kind
+        else {
+            panic!("{} is not a batch auction", keys.auction);
+        };
+        let order = batch_bid_order(&bids, cursor);
+
+        if !filling {
+            let bid_states = &order[..order.len().min(chunk * 4)];
+            let signature = send(context, &[instructions::tally_batch(keys, bid_states)])?;
+            println!("Tallied bids: {}", signature);
+            continue;
+        }
+
+        let bidders: Vec<_> = order
+            .iter()
+            .take(chunk)
+            .map(|bid_state| derive_bidder_keys(&bids[bid_state].bidder, keys))
+            .collect();
+        let signature = send(
+            context,
+            &[instructions::settle_batch(
+                &keypair.pubkey(),
+                payer_purchase_mint_ata.as_ref(),
+                keys,
+                &bidders,
+            )],
+        )?;
+        println!("Filled bids: {}", signature);
     }
 }
 
-struct BidderKeys {
-    bidder_purchase_mint_ata: Pubkey,
-    bidder_listing_mint_ata: Pubkey,
-    bid_state: Pubkey,
-    bid_escrow: Pubkey,
+fn fetch_auction(client: &RpcClient, auction: &Pubkey) -> Auction {
+    let auction_account = client
+        .get_account(auction)
+        .expect("could not get auction account");
+    Auction::from_account_data(&auction_account.data).expect("invalid auction account data")
+}
+
+// every bid placed in `auction`, keyed by bid state
+fn fetch_bids(client: &RpcClient, auction: &Pubkey) -> HashMap<Pubkey, BidState> {
+    fetch_program_accounts(
+        client,
+        vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            BID_STATE_AUCTION_OFFSET,
+            auction.as_ref(),
+        ))],
+    )
+    .expect("could not get bid accounts")
+    .into_iter()
+    .collect()
 }
 
-fn derive_bidder_keys(
+// every `T` account of the auction program that matches `filters`
+fn fetch_program_accounts<T: AuctionAccount>(
+    client: &RpcClient,
+    filters: Vec<RpcFilterType>,
+) -> ClientResult<Vec<(Pubkey, T)>> {
+    let discriminator = RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &T::discriminator()));
+    let config = RpcProgramAccountsConfig {
+        filters: Some([vec![discriminator], filters].concat()),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    Ok(client
+        .get_program_accounts_with_config(&auction_client::ID, config)?
+        .into_iter()
+        .filter_map(|(address, account)| Some((address, T::from_account_data(&account.data)?)))
+        .collect())
+}
+
+fn fetch_clock(client: &RpcClient) -> Clock {
+    let clock_account = client
+        .get_account(&sysvar::clock::ID)
+        .expect("could not get clock");
+    from_account(&clock_account).expect("invalid clock sysvar")
+}
+
+// `from` and `to` are the token accounts a programmable NFT moves between
+fn cancel_expired(context: &Context, keys: &AuctionKeys) -> ClientResult<Signature> {
+    let nft = fetch_nft_keys(
+        &context.client,
+        &keys.listing_mint,
+        &keys.vault,
+        &keys.seller_listing_mint_ata,
+    )?;
+    let instruction = instructions::cancel_expired(&context.keypair.pubkey(), keys, &nft);
+    send(context, &[instruction])
+}
+
+// the keeper's share of the house fee needs a token account to go to
+fn fetch_payer_purchase_mint_ata(
+    context: &Context,
+    keys: &AuctionKeys,
+) -> ClientResult<Option<Pubkey>> {
+    let Context {
+        client, keypair, ..
+    } = context;
+    let payer_purchase_mint_ata =
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            &keypair.pubkey(),
+            &keys.purchase_mint,
+            &keys.token_program,
+        );
+    Ok(client
+        .get_account_with_commitment(&payer_purchase_mint_ata, client.commitment())?
+        .value
+        .map(|_| payer_purchase_mint_ata))
+}
+
+fn fetch_nft_keys(
+    client: &RpcClient,
+    listing_mint: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+) -> ClientResult<NftKeys> {
+    let metadata = client
+        .get_account_with_commitment(&derive_metadata(listing_mint), client.commitment())?
+        .value
+        .filter(|account| account.owner == TOKEN_METADATA_PROGRAM_ID)
+        .and_then(|account| deserialize_metadata(&account.data));
+    Ok(derive_nft_keys(listing_mint, metadata.as_ref(), from, to))
+}
+
+// the allowlist proof and gate accounts a bid on a gated `auction` carries
+fn gate_accounts(
+    client: &RpcClient,
+    auction: &Auction,
     bidder: &Pubkey,
-    purchase_mint: &Pubkey,
+    allowlist: Option<&Path>,
+    gate_nft: Option<Pubkey>,
+) -> (Vec<[u8; 32]>, Option<GateKeys>) {
+    let proof = match (auction.gate, allowlist) {
+        (Some(Gate::Allowlist { root }), Some(allowlist)) => {
+            let members = read_allowlist(allowlist);
+            assert!(
+                allowlist_root(&members) == root,
+                "the allowlist is not the auction's"
+            );
+            allowlist_proof(&members, bidder).expect("not on the allowlist")
+        }
+        _ => vec![],
+    };
+    let gate_keys = auction.gate.and_then(|gate| {
+        let mint = match gate {
+            Gate::Token { mint, .. } => mint,
+            Gate::Collection { .. } => gate_nft?,
+            Gate::Allowlist { .. } => return None,
+        };
+        let token_program = fetch_token_program(client, &mint).expect("could not get gate mint");
+        derive_gate_keys(bidder, &gate, gate_nft.as_ref(), &token_program)
+    });
+    (proof, gate_keys)
+}
+
+// the token program that owns `mint`, spl-token or Token-2022
+fn fetch_token_program(client: &RpcClient, mint: &Pubkey) -> ClientResult<Pubkey> {
+    Ok(client.get_account(mint)?.owner)
+}
+
+// the program only takes one token program, which owns both mints
+fn fetch_auction_keys(
+    client: &RpcClient,
+    auction_house: &Pubkey,
     listing_mint: &Pubkey,
-    auction: &Pubkey,
-) -> BidderKeys {
-    let bidder_purchase_mint_ata =
-        spl_associated_token_account::get_associated_token_address(bidder, purchase_mint);
-    let bidder_listing_mint_ata =
-        spl_associated_token_account::get_associated_token_address(bidder, listing_mint);
-    let (bid_state, _bid_state_bump) = Pubkey::find_program_address(
-        &[b"bid", auction.as_ref(), bidder.as_ref()],
-        &AuctionProgram::id(),
-    );
-    let bid_escrow =
-        spl_associated_token_account::get_associated_token_address(&bid_state, purchase_mint);
-
-    BidderKeys {
-        bidder_purchase_mint_ata,
-        bidder_listing_mint_ata,
-        bid_state,
-        bid_escrow,
+    purchase_mint: &Pubkey,
+    seller: &Pubkey,
+    seed: u64,
+) -> AuctionKeys {
+    let token_program =
+        fetch_token_program(client, listing_mint).expect("could not get listing mint");
+    derive_auction_keys(
+        auction_house,
+        listing_mint,
+        purchase_mint,
+        seller,
+        seed,
+        &token_program,
+    )
+}
+
+// one wallet per line, blank lines and `#` comments are skipped
+fn read_allowlist(path: &Path) -> Vec<Pubkey> {
+    std::fs::read_to_string(path)
+        .expect("could not read allowlist")
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| Pubkey::from_str(line).expect("invalid allowlist wallet"))
+        .collect()
+}
+
+// an end time switches the auction from slots to unix timestamps
+fn auction_end(end_slot: Option<u64>, end_time: Option<String>) -> (EndCondition, u64) {
+    match end_time {
+        Some(end_time) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("system time before unix epoch")
+                .as_secs() as i64;
+            let end = end_time_to_unix(&end_time, now).expect("invalid end time");
+            (EndCondition::UnixTimestamp, end)
+        }
+        None => (EndCondition::Slot, end_slot.expect("missing end slot")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_arguments() {
+        Cli::command().debug_assert();
     }
 }
@@ -0,0 +1,120 @@
+use auction_client::state::{Auction, AuctionKind};
+use solana_sdk::pubkey::Pubkey;
+
+// What `follow` bids for its signer: the minimum bid plus `increment`, never
+// more than `max_price`. Prices are in the auction's decimals.
+#[derive(Clone, Copy, Debug)]
+pub struct Proxy {
+    pub max_price: u64,
+    pub increment: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProxyAction {
+    // leading, or nothing left to bid on
+    Wait,
+    Bid(u64),
+    // the minimum bid is above the ceiling
+    PricedOut(u64),
+}
+
+impl Proxy {
+    pub fn next_action(&self, auction: &Auction, bidder: &Pubkey, now: u64) -> ProxyAction {
+        if auction.kind != AuctionKind::English
+            || now >= auction.end
+            || auction.bidder == Some(*bidder)
+        {
+            return ProxyAction::Wait;
+        }
+        match auction.min_bid() {
+            Some(min_bid) if min_bid <= self.max_price => {
+                ProxyAction::Bid(min_bid.saturating_add(self.increment).min(self.max_price))
+            }
+            Some(min_bid) => ProxyAction::PricedOut(min_bid),
+            None => ProxyAction::Wait,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use auction_client::state::EndCondition;
+
+    fn auction(highest_price: u64, bidder: Option<Pubkey>) -> Auction {
+        Auction {
+            seller: Pubkey::new_unique(),
+            mint_a: Pubkey::new_unique(),
+            mint_b: Pubkey::new_unique(),
+            seed: 0,
+            bump: 255,
+            end: 1_000,
+            highest_price,
+            decimal: 6,
+            bidder,
+            end_condition: EndCondition::Slot,
+            extension_window: 0,
+            extension: 0,
+            max_end: 1_000,
+            min_increment: 100,
+            min_increment_bps: 0,
+            reserve_price: 0,
+            reserve_hash: None,
+            kind: AuctionKind::English,
+            buy_now_price: None,
+            gate: None,
+            proxy_max: None,
+            cancelled: false,
+            open_bids: 0,
+        }
+    }
+
+    #[test]
+    fn test_next_action() {
+        let me = Pubkey::new_unique();
+        let rival = Some(Pubkey::new_unique());
+        let proxy = Proxy {
+            max_price: 2_000,
+            increment: 50,
+        };
+
+        // the first bid only has to beat the starting price
+        assert_eq!(
+            proxy.next_action(&auction(1_000, None), &me, 0),
+            ProxyAction::Bid(1_051)
+        );
+        assert_eq!(
+            proxy.next_action(&auction(1_000, rival), &me, 0),
+            ProxyAction::Bid(1_150)
+        );
+        // the increment stops at the ceiling, the ceiling itself is fine
+        assert_eq!(
+            proxy.next_action(&auction(1_880, rival), &me, 0),
+            ProxyAction::Bid(2_000)
+        );
+        assert_eq!(
+            proxy.next_action(&auction(1_950, rival), &me, 0),
+            ProxyAction::PricedOut(2_050)
+        );
+
+        assert_eq!(
+            proxy.next_action(&auction(1_000, Some(me)), &me, 0),
+            ProxyAction::Wait
+        );
+        assert_eq!(
+            proxy.next_action(&auction(1_000, rival), &me, 1_000),
+            ProxyAction::Wait
+        );
+        let sealed = Auction {
+            kind: AuctionKind::Sealed {
+                reveal_end: 2_000,
+                second_price: false,
+                second_highest: 0,
+                penalty_bps: 0,
+                unrevealed: 0,
+            },
+            ..auction(1_000, rival)
+        };
+        assert_eq!(proxy.next_action(&sealed, &me, 0), ProxyAction::Wait);
+    }
+}
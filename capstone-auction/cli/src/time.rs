@@ -0,0 +1,96 @@
+use chrono::DateTime;
+
+// Accepts either an RFC 3339 date (2025-03-01T18:00:00Z) or a duration
+// from `now` made of d/h/m/s parts (1d, 2h30m, 90s).
+pub fn end_time_to_unix(value: &str, now: i64) -> Result<u64, &'static str> {
+    let timestamp = match DateTime::parse_from_rfc3339(value) {
+        Ok(date) => date.timestamp(),
+        Err(_) => now
+            .checked_add(duration_to_seconds(value)?)
+            .ok_or("End time too far away")?,
+    };
+
+    u64::try_from(timestamp).map_err(|_| "End time before 1970")
+}
+
+fn duration_to_seconds(value: &str) -> Result<i64, &'static str> {
+    if value.is_empty() {
+        return Err("Empty duration");
+    }
+
+    let mut seconds: i64 = 0;
+    let mut number = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            'd' => 86_400,
+            'h' => 3_600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err("Invalid duration unit"),
+        };
+        let part: i64 = number.parse().map_err(|_| "Invalid duration")?;
+        seconds = part
+            .checked_mul(unit)
+            .and_then(|part| seconds.checked_add(part))
+            .ok_or("Duration too long")?;
+        number.clear();
+    }
+
+    // every number needs a unit after it
+    if !number.is_empty() {
+        return Err("Missing duration unit");
+    }
+
+    Ok(seconds)
+}
+
+// Formats seconds the way durations are given above, largest unit first.
+pub fn format_duration(seconds: u64) -> String {
+    let mut rest = seconds;
+    let mut formatted = String::new();
+    for (unit, name) in [(86_400, 'd'), (3_600, 'h'), (60, 'm'), (1, 's')] {
+        if rest >= unit {
+            formatted.push_str(&format!("{}{}", rest / unit, name));
+            rest %= unit;
+        }
+    }
+
+    if formatted.is_empty() {
+        formatted.push_str("0s");
+    }
+    formatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_end_time_to_unix() {
+        assert_eq!(end_time_to_unix("1970-01-02T00:00:00Z", 0), Ok(86_400));
+        assert_eq!(
+            end_time_to_unix("2025-03-01T18:00:00+01:00", 0),
+            Ok(1_740_848_400)
+        );
+        assert_eq!(end_time_to_unix("90s", 1_000), Ok(1_090));
+        assert_eq!(end_time_to_unix("2h30m", 0), Ok(9_000));
+        assert_eq!(end_time_to_unix("1d", 10), Ok(86_410));
+        assert!(end_time_to_unix("30", 0).is_err());
+        assert!(end_time_to_unix("2w", 0).is_err());
+        assert!(end_time_to_unix("", 0).is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(90), "1m30s");
+        assert_eq!(format_duration(9_000), "2h30m");
+        assert_eq!(format_duration(86_401), "1d1s");
+        assert_eq!(end_time_to_unix(&format_duration(200_000), 0), Ok(200_000));
+    }
+}
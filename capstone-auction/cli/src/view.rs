@@ -0,0 +1,760 @@
+use auction_client::state::{
+    Auction, AuctionHouse, AuctionKind, BidState, EndCondition, Gate, KeeperReward, UserStats,
+};
+use clap::ValueEnum;
+use serde::Serialize;
+use solana_sdk::{clock::Clock, hash::Hash, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::decimal::u64_to_decimal;
+use crate::time::format_duration;
+
+// What the read-only commands print, decoded for people and scripts alike.
+// Keys are base58 and amounts decimal strings, so the json loses nothing.
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+pub fn print<T: Serialize + fmt::Display>(value: &T, output: OutputFormat) {
+    match output {
+        OutputFormat::Text => print!("{}", value),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(value).expect("could not serialize output")
+        ),
+    }
+}
+
+pub fn print_list<T: Serialize + fmt::Display>(values: &[T], output: OutputFormat, empty: &str) {
+    match output {
+        OutputFormat::Text if values.is_empty() => println!("{}", empty),
+        OutputFormat::Text => {
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                print!("{}", value);
+            }
+        }
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(values).expect("could not serialize output")
+        ),
+    }
+}
+
+fn field(f: &mut fmt::Formatter, name: &str, value: impl fmt::Display) -> fmt::Result {
+    writeln!(f, "  {:<16}{}", format!("{}:", name), value)
+}
+
+#[derive(Serialize)]
+pub struct HouseView {
+    pub address: String,
+    pub name: String,
+    pub admin: String,
+    pub pending_admin: Option<String>,
+    pub fee_bps: u16,
+    pub cancel_penalty_bps: u16,
+    pub paused: bool,
+    pub keeper_reward: KeeperRewardView,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KeeperRewardView {
+    None,
+    FeeShare { bps: u16 },
+    Lamports { sol: String },
+}
+
+impl HouseView {
+    pub fn new(address: &Pubkey, house: &AuctionHouse) -> Self {
+        HouseView {
+            address: address.to_string(),
+            name: house.name.clone(),
+            admin: house.admin.to_string(),
+            pending_admin: house.pending_admin.map(|admin| admin.to_string()),
+            fee_bps: house.fee,
+            cancel_penalty_bps: house.cancel_penalty_bps,
+            paused: house.paused,
+            keeper_reward: match house.keeper_reward {
+                KeeperReward::None => KeeperRewardView::None,
+                KeeperReward::FeeShare { bps } => KeeperRewardView::FeeShare { bps },
+                KeeperReward::Lamports { amount } => KeeperRewardView::Lamports {
+                    sol: u64_to_decimal(amount, 9),
+                },
+            },
+        }
+    }
+}
+
+impl fmt::Display for HouseView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Auction house {} ({})", self.name, self.address)?;
+        field(f, "admin", &self.admin)?;
+        if let Some(pending_admin) = &self.pending_admin {
+            field(f, "pending admin", pending_admin)?;
+        }
+        field(f, "fee", format!("{} bps", self.fee_bps))?;
+        field(
+            f,
+            "cancel penalty",
+            format!("{} bps", self.cancel_penalty_bps),
+        )?;
+        field(f, "paused", self.paused)?;
+        match &self.keeper_reward {
+            KeeperRewardView::None => field(f, "keeper reward", "none"),
+            KeeperRewardView::FeeShare { bps } => {
+                field(f, "keeper reward", format!("{} bps of the fee", bps))
+            }
+            KeeperRewardView::Lamports { sol } => field(f, "keeper reward", format!("{} SOL", sol)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum AuctionStatus {
+    /// Taking bids (or sealed commitments).
+    Live,
+    /// Sealed auctions between the commit and the reveal end.
+    Revealing,
+    /// Waiting to be finalized or cancelled.
+    Ended,
+    /// Cancelled early by the seller, bids can be withdrawn.
+    Cancelled,
+}
+
+impl AuctionStatus {
+    pub fn of(auction: &Auction, now: u64) -> Self {
+        if auction.cancelled {
+            AuctionStatus::Cancelled
+        } else if now < auction.end {
+            AuctionStatus::Live
+        } else if now < auction.settles_at() {
+            AuctionStatus::Revealing
+        } else {
+            AuctionStatus::Ended
+        }
+    }
+}
+
+impl fmt::Display for AuctionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            AuctionStatus::Live => "live",
+            AuctionStatus::Revealing => "revealing",
+            AuctionStatus::Ended => "ended",
+            AuctionStatus::Cancelled => "cancelled",
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct AuctionView {
+    pub address: String,
+    pub seller: String,
+    pub listing_mint: String,
+    pub purchase_mint: String,
+    pub seed: u64,
+    #[serde(flatten)]
+    pub kind: KindView,
+    pub status: AuctionStatus,
+    pub end_condition: &'static str,
+    pub end: u64,
+    // slots or seconds until the current phase is over
+    pub remaining: u64,
+    pub high_bid: Option<String>,
+    pub high_bidder: Option<String>,
+    pub min_bid: Option<String>,
+    // hidden reserves are only known once revealed
+    pub reserve_price: Option<String>,
+    pub reserve_met: bool,
+    pub buy_now_price: Option<String>,
+    pub gate: Option<GateView>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GateView {
+    Allowlist { root: String },
+    // in base units of the gate mint, its decimals aren't fetched
+    Token { mint: String, min_amount: String },
+    Collection { collection: String },
+}
+
+impl GateView {
+    pub fn new(gate: &Gate) -> Self {
+        match gate {
+            Gate::Allowlist { root } => GateView::Allowlist {
+                root: Hash::new_from_array(*root).to_string(),
+            },
+            Gate::Token { mint, min_amount } => GateView::Token {
+                mint: mint.to_string(),
+                min_amount: min_amount.to_string(),
+            },
+            Gate::Collection { collection } => GateView::Collection {
+                collection: collection.to_string(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for GateView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GateView::Allowlist { root } => write!(f, "allowlist with root {}", root),
+            GateView::Token { mint, min_amount } => {
+                write!(f, "holders of {} base units of {}", min_amount, mint)
+            }
+            GateView::Collection { collection } => {
+                write!(f, "holders of an NFT of collection {}", collection)
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum KindView {
+    English,
+    Dutch {
+        start_price: String,
+        floor_price: String,
+        current_price: Option<String>,
+    },
+    Sealed {
+        reveal_end: u64,
+        second_price: bool,
+        penalty_bps: u16,
+    },
+    Batch {
+        supply: u64,
+        clearing_price: Option<String>,
+    },
+}
+
+impl AuctionView {
+    pub fn new(address: &Pubkey, auction: &Auction, clock: &Clock) -> Self {
+        let now = auction.now(clock);
+        let status = AuctionStatus::of(auction, now);
+        let price = |price: u64| u64_to_decimal(price, auction.decimal);
+
+        let kind = match auction.kind {
+            AuctionKind::English => KindView::English,
+            AuctionKind::Dutch {
+                start_price,
+                floor_price,
+                ..
+            } => KindView::Dutch {
+                start_price: price(start_price),
+                floor_price: price(floor_price),
+                current_price: auction
+                    .dutch_price(now)
+                    .filter(|_| status == AuctionStatus::Live)
+                    .map(price),
+            },
+            AuctionKind::Sealed {
+                reveal_end,
+                second_price,
+                penalty_bps,
+                ..
+            } => KindView::Sealed {
+                reveal_end,
+                second_price,
+                penalty_bps,
+            },
+            AuctionKind::Batch {
+                supply,
+                clearing_price,
+                filling,
+                ..
+            } => KindView::Batch {
+                supply,
+                clearing_price: filling.then(|| price(clearing_price)),
+            },
+        };
+        let remaining = match status {
+            AuctionStatus::Live => auction.end - now,
+            AuctionStatus::Revealing => auction.settles_at() - now,
+            AuctionStatus::Ended | AuctionStatus::Cancelled => 0,
+        };
+        let min_bid = match (auction.kind, status) {
+            (AuctionKind::English, AuctionStatus::Live) => auction.min_bid().map(price),
+            _ => None,
+        };
+
+        AuctionView {
+            address: address.to_string(),
+            seller: auction.seller.to_string(),
+            listing_mint: auction.mint_a.to_string(),
+            purchase_mint: auction.mint_b.to_string(),
+            seed: auction.seed,
+            kind,
+            status,
+            end_condition: match auction.end_condition {
+                EndCondition::Slot => "slot",
+                EndCondition::UnixTimestamp => "unix_timestamp",
+            },
+            end: auction.end,
+            remaining,
+            high_bid: auction.bidder.map(|_| price(auction.highest_price)),
+            high_bidder: auction.bidder.map(|bidder| bidder.to_string()),
+            min_bid,
+            reserve_price: auction
+                .reserve_hash
+                .is_none()
+                .then(|| price(auction.reserve_price)),
+            reserve_met: auction.reserve_met(now),
+            buy_now_price: auction.buy_now_price.map(price),
+            gate: auction.gate.as_ref().map(GateView::new),
+        }
+    }
+
+    fn remaining(&self) -> String {
+        match self.end_condition {
+            "slot" => format!("{} slots", self.remaining),
+            _ => format_duration(self.remaining),
+        }
+    }
+
+    // one line per update for `follow`
+    pub fn summary(&self) -> String {
+        let high_bid = match (&self.high_bid, &self.high_bidder) {
+            (Some(high_bid), Some(high_bidder)) => {
+                format!("high bid {} by {}", high_bid, high_bidder)
+            }
+            _ => "no bids".to_string(),
+        };
+        match self.status {
+            AuctionStatus::Live => format!("{}, {} left", high_bid, self.remaining()),
+            AuctionStatus::Revealing => format!("{}, revealing for {}", high_bid, self.remaining()),
+            AuctionStatus::Ended => format!("{}, ended", high_bid),
+            AuctionStatus::Cancelled => format!("{}, cancelled", high_bid),
+        }
+    }
+}
+
+impl fmt::Display for AuctionView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            KindView::English => "english",
+            KindView::Dutch { .. } => "dutch",
+            KindView::Sealed { .. } => "sealed",
+            KindView::Batch { .. } => "batch",
+        };
+        writeln!(f, "Auction {} ({}, {})", self.address, kind, self.status)?;
+        field(f, "seller", &self.seller)?;
+        field(f, "listing mint", &self.listing_mint)?;
+        field(f, "purchase mint", &self.purchase_mint)?;
+        if self.seed != 0 {
+            field(f, "seed", self.seed)?;
+        }
+
+        match &self.kind {
+            KindView::English => {}
+            KindView::Dutch {
+                start_price,
+                floor_price,
+                current_price,
+            } => {
+                field(
+                    f,
+                    "price range",
+                    format!("{} to {}", start_price, floor_price),
+                )?;
+                if let Some(current_price) = current_price {
+                    field(f, "current price", current_price)?;
+                }
+            }
+            KindView::Sealed {
+                reveal_end,
+                second_price,
+                penalty_bps,
+            } => {
+                field(f, "reveal end", reveal_end)?;
+                field(f, "second price", second_price)?;
+                field(f, "penalty", format!("{} bps", penalty_bps))?;
+            }
+            KindView::Batch {
+                supply,
+                clearing_price,
+            } => {
+                field(f, "supply", supply)?;
+                if let Some(clearing_price) = clearing_price {
+                    field(f, "clearing price", clearing_price)?;
+                }
+            }
+        }
+
+        match (&self.high_bid, &self.high_bidder) {
+            (Some(high_bid), Some(high_bidder)) => {
+                field(f, "high bid", format!("{} by {}", high_bid, high_bidder))?
+            }
+            _ => field(f, "high bid", "none")?,
+        }
+        if let Some(min_bid) = &self.min_bid {
+            field(f, "min bid", min_bid)?;
+        }
+        match &self.reserve_price {
+            Some(reserve_price) => field(
+                f,
+                "reserve",
+                format!(
+                    "{} ({})",
+                    reserve_price,
+                    if self.reserve_met { "met" } else { "not met" }
+                ),
+            )?,
+            None => field(f, "reserve", "hidden")?,
+        }
+        if let Some(buy_now_price) = &self.buy_now_price {
+            field(f, "buy now price", buy_now_price)?;
+        }
+        if let Some(gate) = &self.gate {
+            field(f, "bidders", gate)?;
+        }
+
+        let end = format!("{} {}", self.end_condition, self.end);
+        match self.status {
+            AuctionStatus::Live => field(f, "ends", format!("in {} ({})", self.remaining(), end)),
+            AuctionStatus::Revealing => field(f, "reveal ends", format!("in {}", self.remaining())),
+            AuctionStatus::Ended => field(f, "ended", end),
+            AuctionStatus::Cancelled => field(f, "cancelled", "by the seller"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct BidView {
+    pub address: String,
+    pub auction: String,
+    pub bidder: String,
+    // sealed bids show no price until revealed
+    pub price: Option<String>,
+    // proxy bids: the ceiling the program bids up to
+    pub max_price: Option<String>,
+    pub sealed: bool,
+    pub quantity: u64,
+    pub highest: bool,
+    // the auction is closed once it settles, the bid may still be withdrawn
+    pub auction_status: Option<AuctionStatus>,
+}
+
+impl BidView {
+    pub fn new(address: &Pubkey, bid: &BidState, auction: Option<&Auction>, clock: &Clock) -> Self {
+        // closed auctions leave no decimals behind, the raw price is all there is
+        let decimals = auction.map_or(0, |auction| auction.decimal);
+        let highest = auction.filter(|auction| auction.bidder == Some(bid.bidder));
+        // a leading proxy bid has been raised past its own price
+        let price = highest.map_or(bid.price, |auction| auction.highest_price.max(bid.price));
+        BidView {
+            address: address.to_string(),
+            auction: bid.auction.to_string(),
+            bidder: bid.bidder.to_string(),
+            price: bid
+                .commitment
+                .is_none()
+                .then(|| u64_to_decimal(price, decimals)),
+            max_price: bid
+                .max_price
+                .map(|max_price| u64_to_decimal(max_price, decimals)),
+            sealed: bid.commitment.is_some(),
+            quantity: bid.quantity,
+            highest: highest.is_some(),
+            auction_status: auction.map(|auction| AuctionStatus::of(auction, auction.now(clock))),
+        }
+    }
+}
+
+impl fmt::Display for BidView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Bid {}", self.address)?;
+        field(f, "auction", &self.auction)?;
+        field(f, "bidder", &self.bidder)?;
+        match &self.price {
+            Some(price) if self.highest => field(f, "price", format!("{} (highest)", price))?,
+            Some(price) => field(f, "price", price)?,
+            None => field(f, "price", "sealed")?,
+        }
+        if let Some(max_price) = &self.max_price {
+            field(f, "max price", max_price)?;
+        }
+        if self.quantity > 0 {
+            field(f, "quantity", self.quantity)?;
+        }
+        match self.auction_status {
+            Some(status) => field(f, "auction status", status),
+            None => field(f, "auction status", "closed"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct StatsView {
+    pub address: String,
+    pub wallet: String,
+    pub auctions_created: u64,
+    pub auctions_completed: u64,
+    pub auctions_cancelled: u64,
+    pub bids_placed: u64,
+    pub auctions_won: u64,
+    pub volume: Vec<VolumeView>,
+}
+
+#[derive(Serialize)]
+pub struct VolumeView {
+    pub mint: String,
+    pub amount: String,
+}
+
+impl StatsView {
+    // `decimals` of the traded mints, the raw amount is shown for the others
+    pub fn new(address: &Pubkey, stats: &UserStats, decimals: &HashMap<Pubkey, u8>) -> Self {
+        StatsView {
+            address: address.to_string(),
+            wallet: stats.wallet.to_string(),
+            auctions_created: stats.auctions_created,
+            auctions_completed: stats.auctions_completed,
+            auctions_cancelled: stats.auctions_cancelled,
+            bids_placed: stats.bids_placed,
+            auctions_won: stats.auctions_won,
+            volume: stats
+                .volume
+                .iter()
+                .map(|volume| VolumeView {
+                    mint: volume.mint.to_string(),
+                    amount: u64_to_decimal(
+                        volume.amount,
+                        decimals.get(&volume.mint).copied().unwrap_or(0),
+                    ),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for StatsView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Stats of {} ({})", self.wallet, self.address)?;
+        field(
+            f,
+            "auctions",
+            format!(
+                "{} created, {} completed, {} cancelled",
+                self.auctions_created, self.auctions_completed, self.auctions_cancelled
+            ),
+        )?;
+        field(
+            f,
+            "bids",
+            format!("{} placed, {} won", self.bids_placed, self.auctions_won),
+        )?;
+        for volume in &self.volume {
+            field(f, "volume", format!("{} of {}", volume.amount, volume.mint))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use auction_client::state::MintVolume;
+
+    fn auction(kind: AuctionKind) -> Auction {
+        Auction {
+            seller: Pubkey::new_unique(),
+            mint_a: Pubkey::new_unique(),
+            mint_b: Pubkey::new_unique(),
+            seed: 0,
+            bump: 255,
+            end: 1_000,
+            highest_price: 1_500_000,
+            decimal: 6,
+            bidder: Some(Pubkey::new_unique()),
+            end_condition: EndCondition::UnixTimestamp,
+            extension_window: 0,
+            extension: 0,
+            max_end: 1_000,
+            min_increment: 0,
+            min_increment_bps: 1_000,
+            reserve_price: 1_000_000,
+            reserve_hash: None,
+            kind,
+            buy_now_price: None,
+            gate: None,
+            proxy_max: None,
+            cancelled: false,
+            open_bids: 0,
+        }
+    }
+
+    fn clock(unix_timestamp: i64) -> Clock {
+        Clock {
+            unix_timestamp,
+            ..Clock::default()
+        }
+    }
+
+    #[test]
+    fn test_auction_view() {
+        let address = Pubkey::new_unique();
+        let view = AuctionView::new(&address, &auction(AuctionKind::English), &clock(910));
+        assert_eq!(view.status, AuctionStatus::Live);
+        assert_eq!(view.remaining, 90);
+        assert_eq!(view.high_bid.as_deref(), Some("1.5"));
+        assert_eq!(view.min_bid.as_deref(), Some("1.65"));
+        assert_eq!(view.reserve_price.as_deref(), Some("1"));
+        assert!(view.reserve_met);
+        assert!(view.to_string().contains("in 1m30s (unix_timestamp 1000)"));
+        assert!(view.summary().starts_with("high bid 1.5 by "));
+        assert!(view.summary().ends_with(", 1m30s left"));
+
+        let json = serde_json::to_value(&view).unwrap();
+        assert_eq!(json["kind"], "english");
+        assert_eq!(json["status"], "live");
+        assert_eq!(json["address"], address.to_string());
+
+        let sealed = auction(AuctionKind::Sealed {
+            reveal_end: 2_000,
+            second_price: true,
+            second_highest: 0,
+            penalty_bps: 0,
+            unrevealed: 0,
+        });
+        let view = AuctionView::new(&address, &sealed, &clock(1_500));
+        assert_eq!(view.status, AuctionStatus::Revealing);
+        assert_eq!(view.remaining, 500);
+        assert_eq!(view.min_bid, None);
+        let view = AuctionView::new(&address, &sealed, &clock(2_000));
+        assert_eq!(view.status, AuctionStatus::Ended);
+        assert_eq!(view.remaining, 0);
+        assert!(view.summary().ends_with(", ended"));
+
+        let cancelled = Auction {
+            cancelled: true,
+            ..auction(AuctionKind::English)
+        };
+        let view = AuctionView::new(&address, &cancelled, &clock(910));
+        assert_eq!(view.status, AuctionStatus::Cancelled);
+        assert_eq!(view.min_bid, None);
+        assert!(view.summary().ends_with(", cancelled"));
+    }
+
+    #[test]
+    fn test_bid_view() {
+        let state = auction(AuctionKind::English);
+        let mut bid = BidState {
+            bidder: state.bidder.unwrap(),
+            auction: Pubkey::new_unique(),
+            bump: 255,
+            price: 1_000_000,
+            commitment: None,
+            quantity: 0,
+            next: None,
+            max_price: Some(2_000_000),
+        };
+        // the leading proxy stands at the auction's price
+        let view = BidView::new(&Pubkey::new_unique(), &bid, Some(&state), &clock(910));
+        assert!(view.highest);
+        assert_eq!(view.price.as_deref(), Some("1.5"));
+        assert!(view.to_string().contains("max price:      2"));
+
+        bid.bidder = Pubkey::new_unique();
+        let view = BidView::new(&Pubkey::new_unique(), &bid, Some(&state), &clock(910));
+        assert!(!view.highest);
+        assert_eq!(view.price.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_stats_view() {
+        let (mint, other_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let stats = UserStats {
+            wallet: Pubkey::new_unique(),
+            auction_house: Pubkey::new_unique(),
+            bump: 255,
+            auctions_created: 3,
+            auctions_completed: 1,
+            auctions_cancelled: 1,
+            bids_placed: 4,
+            auctions_won: 2,
+            volume: vec![
+                MintVolume {
+                    mint,
+                    amount: 2_500_000,
+                },
+                MintVolume {
+                    mint: other_mint,
+                    amount: 7,
+                },
+            ],
+        };
+        let view = StatsView::new(&Pubkey::new_unique(), &stats, &HashMap::from([(mint, 6)]));
+        let text = view.to_string();
+        assert!(text.contains("3 created, 1 completed, 1 cancelled"));
+        assert!(text.contains("4 placed, 2 won"));
+        assert!(text.contains(&format!("2.5 of {}", mint)));
+        assert!(text.contains(&format!("7 of {}", other_mint)));
+
+        let json = serde_json::to_value(&view).unwrap();
+        assert_eq!(json["volume"][0]["amount"], "2.5");
+        assert_eq!(json["auctions_won"], 2);
+    }
+
+    #[test]
+    fn test_gate_view() {
+        let mint = Pubkey::new_unique();
+        let gated = Auction {
+            gate: Some(Gate::Token {
+                mint,
+                min_amount: 5,
+            }),
+            ..auction(AuctionKind::English)
+        };
+        let view = AuctionView::new(&Pubkey::default(), &gated, &clock(0));
+        assert!(view
+            .to_string()
+            .contains(&format!("holders of 5 base units of {}", mint)));
+        let json = serde_json::to_value(&view).unwrap();
+        assert_eq!(json["gate"]["type"], "token");
+        assert_eq!(json["gate"]["min_amount"], "5");
+
+        let view = AuctionView::new(
+            &Pubkey::default(),
+            &auction(AuctionKind::English),
+            &clock(0),
+        );
+        assert!(!view.to_string().contains("bidders"));
+        assert_eq!(
+            serde_json::to_value(&view).unwrap()["gate"],
+            serde_json::Value::Null
+        );
+    }
+
+    #[test]
+    fn test_dutch_price() {
+        let dutch = Auction {
+            end: 1_100,
+            ..auction(AuctionKind::Dutch {
+                start_price: 2_000_000,
+                floor_price: 1_000_000,
+                start: 100,
+                step: 100,
+            })
+        };
+        let current_price =
+            |now| match AuctionView::new(&Pubkey::default(), &dutch, &clock(now)).kind {
+                KindView::Dutch { current_price, .. } => current_price,
+                _ => unreachable!(),
+            };
+        assert_eq!(current_price(100).as_deref(), Some("2"));
+        assert_eq!(current_price(650).as_deref(), Some("1.5"));
+        assert_eq!(current_price(1_099).as_deref(), Some("1.1"));
+        assert_eq!(current_price(1_100), None);
+    }
+}
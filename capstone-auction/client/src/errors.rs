@@ -0,0 +1,128 @@
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+use std::fmt;
+
+// anchor numbers the variants of `#[error_code]` from 6000 in declaration
+// order, so they have to stay in the order of the program's `AuctionError`
+macro_rules! auction_errors {
+    ($($error:ident => $msg:literal,)*) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum AuctionError {
+            $($error,)*
+        }
+
+        const ERRORS: &[AuctionError] = &[$(AuctionError::$error,)*];
+
+        impl AuctionError {
+            pub fn message(&self) -> &'static str {
+                match self {
+                    $(AuctionError::$error => $msg,)*
+                }
+            }
+        }
+    };
+}
+
+auction_errors! {
+    NameTooLong => "The given name is too long",
+    ArithematicOverflow => "ArithematicOverflow",
+    PriceTooLow => "PriceTooLow",
+    NotEligibleToWithdraw => "NotEligibleToWithdraw",
+    AuctionEnded => "AuctionEnded",
+    InvalidEnd => "InvalidEnd",
+    InvalidReserve => "InvalidReserve",
+    WrongAuctionKind => "WrongAuctionKind",
+    InvalidSchedule => "InvalidSchedule",
+    NotInRevealPhase => "NotInRevealPhase",
+    InvalidCommitment => "InvalidCommitment",
+    InsufficientCollateral => "InsufficientCollateral",
+    InvalidPenalty => "InvalidPenalty",
+    MissingPenaltyAccounts => "MissingPenaltyAccounts",
+    NotHouseAdmin => "NotHouseAdmin",
+    InvalidFee => "InvalidFee",
+    HousePaused => "HousePaused",
+    InvalidBidOrder => "InvalidBidOrder",
+    InvalidQuantity => "InvalidQuantity",
+    AuctionNotEnded => "AuctionNotEnded",
+    WrongSettlementPhase => "WrongSettlementPhase",
+    MissingTokenAccount => "MissingTokenAccount",
+    InsufficientHouseLamports => "InsufficientHouseLamports",
+    NonTransferableMint => "NonTransferableMint",
+    PermanentDelegateMint => "PermanentDelegateMint",
+    ProgrammableNftUnsupported => "ProgrammableNftUnsupported",
+    MissingProgrammableAccounts => "MissingProgrammableAccounts",
+    InvalidCreatorAccount => "InvalidCreatorAccount",
+    NotAllowedToBid => "NotAllowedToBid",
+    MaxPriceTooLow => "MaxPriceTooLow",
+    AuctionCancelled => "AuctionCancelled",
+    UnrevealedBids => "UnrevealedBids",
+    RoyaltiesUnsupported => "RoyaltiesUnsupported",
+    AuctionNotCancelled => "AuctionNotCancelled",
+    BidsOutstanding => "BidsOutstanding",
+}
+
+const ERROR_CODE_OFFSET: u32 = 6000;
+
+impl AuctionError {
+    pub fn code(&self) -> u32 {
+        ERROR_CODE_OFFSET + *self as u32
+    }
+
+    pub fn from_code(code: u32) -> Option<Self> {
+        let index = code.checked_sub(ERROR_CODE_OFFSET)?;
+        ERRORS.get(index as usize).copied()
+    }
+
+    // `None` unless an instruction failed with one of our custom errors
+    pub fn from_transaction_error(err: &TransactionError) -> Option<Self> {
+        match err {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+                Self::from_code(*code)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AuctionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.message(), self.code())
+    }
+}
+
+impl std::error::Error for AuctionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_transaction_error() {
+        assert_eq!(AuctionError::NameTooLong.code(), 6000);
+        assert_eq!(AuctionError::MaxPriceTooLow.code(), 6029);
+        assert_eq!(AuctionError::AuctionCancelled.code(), 6030);
+        assert_eq!(AuctionError::UnrevealedBids.code(), 6031);
+        assert_eq!(AuctionError::RoyaltiesUnsupported.code(), 6032);
+        assert_eq!(AuctionError::AuctionNotCancelled.code(), 6033);
+        assert_eq!(AuctionError::BidsOutstanding.code(), 6034);
+
+        let err = TransactionError::InstructionError(1, InstructionError::Custom(6017));
+        assert_eq!(
+            AuctionError::from_transaction_error(&err),
+            Some(AuctionError::InvalidBidOrder)
+        );
+        assert_eq!(
+            AuctionError::InvalidBidOrder.to_string(),
+            "InvalidBidOrder (6017)"
+        );
+
+        // anchor's own errors and other programs' errors are not ours
+        for code in [2003, 5999, 6035] {
+            let err = TransactionError::InstructionError(0, InstructionError::Custom(code));
+            assert_eq!(AuctionError::from_transaction_error(&err), None);
+        }
+        assert_eq!(
+            AuctionError::from_transaction_error(&TransactionError::AccountNotFound),
+            None
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use borsh::BorshDeserialize;
+use solana_sdk::{hash::hash, pubkey::Pubkey};
+
+use crate::state::{AuctionKind, EndCondition, KeeperReward};
+
+// Mirrors the `#[event]` structs in the auction program.
+
+#[derive(BorshDeserialize, Debug)]
+pub struct HouseCreated {
+    pub auction_house: Pubkey,
+    pub admin: Pubkey,
+    pub fee: u16,
+    pub name: String,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct HouseFeeUpdated {
+    pub auction_house: Pubkey,
+    pub old_fee: u16,
+    pub new_fee: u16,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct AdminProposed {
+    pub auction_house: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct AdminAccepted {
+    pub auction_house: Pubkey,
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct HousePaused {
+    pub auction_house: Pubkey,
+    pub paused: bool,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct KeeperRewardUpdated {
+    pub auction_house: Pubkey,
+    pub keeper_reward: KeeperReward,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct FeesSwept {
+    pub auction_house: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct KeeperPoolFunded {
+    pub auction_house: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct KeeperPoolWithdrawn {
+    pub auction_house: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct AuctionCreated {
+    pub auction_house: Pubkey,
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub seed: u64,
+    pub amount: u64,
+    pub kind: AuctionKind,
+    pub end_condition: EndCondition,
+    pub end: u64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct BidPlaced {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub price: u64,
+    pub previous_bidder: Option<Pubkey>,
+    pub previous_price: u64,
+    pub end: u64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct ProxyBidRaised {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub challenger: Pubkey,
+    pub price: u64,
+    pub end: u64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct BidWithdrawn {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct AuctionFinalized {
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+    pub winner: Option<Pubkey>,
+    pub price: u64,
+    pub total: u64,
+    pub house_fee: u64,
+    pub royalties: u64,
+    pub seller_proceeds: u64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct BatchBidFilled {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub quantity: u64,
+    pub price: u64,
+    pub total: u64,
+    pub house_fee: u64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct BatchSettled {
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+    pub clearing_price: u64,
+    pub sold: u64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct AuctionCancelled {
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+    pub early: bool,
+    pub penalty: u64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct CancelledAuctionClosed {
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct KeeperPaid {
+    pub auction: Pubkey,
+    pub keeper: Pubkey,
+    pub lamports: u64,
+    pub tokens: u64,
+}
+
+#[derive(Debug)]
+pub enum AuctionEvent {
+    HouseCreated(HouseCreated),
+    HouseFeeUpdated(HouseFeeUpdated),
+    AdminProposed(AdminProposed),
+    AdminAccepted(AdminAccepted),
+    HousePaused(HousePaused),
+    KeeperRewardUpdated(KeeperRewardUpdated),
+    FeesSwept(FeesSwept),
+    KeeperPoolFunded(KeeperPoolFunded),
+    KeeperPoolWithdrawn(KeeperPoolWithdrawn),
+    AuctionCreated(AuctionCreated),
+    BidPlaced(BidPlaced),
+    ProxyBidRaised(ProxyBidRaised),
+    BidWithdrawn(BidWithdrawn),
+    AuctionFinalized(AuctionFinalized),
+    AuctionCancelled(AuctionCancelled),
+    CancelledAuctionClosed(CancelledAuctionClosed),
+    BatchBidFilled(BatchBidFilled),
+    BatchSettled(BatchSettled),
+    KeeperPaid(KeeperPaid),
+}
+
+// anchor prefixes every event with the first 8 bytes of sha256("event:<Name>")
+fn discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0; 8];
+    discriminator.copy_from_slice(&hash(format!("event:{name}").as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+// Decodes the data of a `Program data: <base64>` log line, `None` if it is not
+// one of our events.
+pub fn decode_event(data: &[u8]) -> Option<AuctionEvent> {
+    if data.len() < 8 {
+        return None;
+    }
+    let (prefix, mut data) = data.split_at(8);
+
+    macro_rules! decode {
+        ($($event:ident),*) => {
+            $(
+                if prefix == discriminator(stringify!($event)) {
+                    return $event::deserialize(&mut data).ok().map(AuctionEvent::$event);
+                }
+            )*
+        };
+    }
+    decode!(
+        HouseCreated,
+        HouseFeeUpdated,
+        AdminProposed,
+        AdminAccepted,
+        HousePaused,
+        KeeperRewardUpdated,
+        FeesSwept,
+        KeeperPoolFunded,
+        KeeperPoolWithdrawn,
+        AuctionCreated,
+        BidPlaced,
+        ProxyBidRaised,
+        BidWithdrawn,
+        AuctionFinalized,
+        AuctionCancelled,
+        CancelledAuctionClosed,
+        BatchBidFilled,
+        BatchSettled,
+        KeeperPaid
+    );
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_event() {
+        let auction_house = Pubkey::new_unique();
+        let mut data = discriminator("HouseFeeUpdated").to_vec();
+        data.extend_from_slice(auction_house.as_ref());
+        data.extend_from_slice(&100u16.to_le_bytes());
+        data.extend_from_slice(&250u16.to_le_bytes());
+
+        match decode_event(&data) {
+            Some(AuctionEvent::HouseFeeUpdated(event)) => {
+                assert_eq!(event.auction_house, auction_house);
+                assert_eq!(event.old_fee, 100);
+                assert_eq!(event.new_fee, 250);
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+
+        data[0] ^= 1;
+        assert!(decode_event(&data).is_none());
+        assert!(decode_event(&[1, 2, 3]).is_none());
+    }
+}
@@ -0,0 +1,1018 @@
+use borsh::BorshSerialize;
+use solana_sdk::{
+    hash::{hash, hashv},
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::pda::{derive_house, derive_keeper_pool, AuctionKeys, BidderKeys, GateKeys, NftKeys};
+use crate::state::{EndCondition, Gate, KeeperReward};
+use crate::ID;
+
+// Builders for every instruction of the auction program. Optional accounts
+// that are left out are passed as the program id, which anchor reads as
+// `None`.
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct InitHouseArgs {
+    pub fee: u16,
+    pub cancel_penalty_bps: u16,
+    pub name: String,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct UpdateHouseFeeArgs {
+    pub fee: u16,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct ProposeAdminArgs {
+    pub pending_admin: Pubkey,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct PauseHouseArgs {
+    pub paused: bool,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct SetKeeperRewardArgs {
+    pub keeper_reward: KeeperReward,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct SweepLamportsArgs {
+    pub amount: u64,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct KeeperPoolArgs {
+    pub amount: u64,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct InitAuctionArgs {
+    pub starting_price: u64,
+    pub buy_now_price: u64,
+    pub end_condition: EndCondition,
+    pub end: u64,
+    pub amount: u64,
+    pub decimal: u8,
+    pub extension_window: u64,
+    pub extension: u64,
+    pub max_extension: u64,
+    pub min_increment: u64,
+    pub min_increment_bps: u16,
+    pub reserve_price: u64,
+    pub reserve_hash: [u8; 32],
+    pub gate: Option<Gate>,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct RevealReserveArgs {
+    pub reserve_price: u64,
+    pub salt: [u8; 32],
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct InitDutchAuctionArgs {
+    pub start_price: u64,
+    pub floor_price: u64,
+    pub step: u64,
+    pub end_condition: EndCondition,
+    pub end: u64,
+    pub amount: u64,
+    pub decimal: u8,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct InitSealedAuctionArgs {
+    pub starting_price: u64,
+    pub end_condition: EndCondition,
+    pub end: u64,
+    pub reveal_end: u64,
+    pub amount: u64,
+    pub decimal: u8,
+    pub second_price: bool,
+    pub penalty_bps: u16,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct InitBatchAuctionArgs {
+    pub min_price: u64,
+    pub end_condition: EndCondition,
+    pub end: u64,
+    pub amount: u64,
+    pub decimal: u8,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct BuyNowArgs {
+    pub max_price: u64,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct BidArgs {
+    pub price: u64,
+    // the bidder's allowlist proof, see `allowlist_proof`
+    pub proof: Vec<[u8; 32]>,
+    // makes it a proxy bid, the program outbids rivals up to it
+    pub max_price: Option<u64>,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct CommitBidArgs {
+    pub commitment: [u8; 32],
+    pub deposit: u64,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct BatchBidArgs {
+    pub quantity: u64,
+    pub price: u64,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct RevealBidArgs {
+    pub price: u64,
+    pub salt: [u8; 32],
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct RaiseBidArgs {
+    pub price: u64,
+    // replaces the bid's proxy ceiling, `None` ends it
+    pub max_price: Option<u64>,
+}
+
+pub fn secret_salt(secret: &str) -> [u8; 32] {
+    hash(secret.as_bytes()).to_bytes()
+}
+
+// must match `bid_commitment` in the auction program.
+pub fn sealed_bid_commitment(price: u64, secret: &str, bidder: &Pubkey) -> [u8; 32] {
+    hashv(&[&price.to_le_bytes(), &secret_salt(secret), bidder.as_ref()]).to_bytes()
+}
+
+// must match `reserve_hash` in the auction program.
+pub fn hidden_reserve_hash(reserve_price: u64, secret: &str) -> [u8; 32] {
+    hashv(&[&reserve_price.to_le_bytes(), &secret_salt(secret)]).to_bytes()
+}
+
+// must match `allowlist_leaf` in the auction program.
+pub fn allowlist_leaf(bidder: &Pubkey) -> [u8; 32] {
+    hashv(&[&[0], bidder.as_ref()]).to_bytes()
+}
+
+// must match `allowlist_node` in the auction program.
+pub fn allowlist_node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[&[1], left, right]).to_bytes()
+}
+
+// every level of the merkle tree over `members`, leaves first. A node
+// without a sibling moves up a level as it is.
+fn allowlist_levels(members: &[Pubkey]) -> Vec<Vec<[u8; 32]>> {
+    let mut leaves: Vec<_> = members.iter().map(allowlist_leaf).collect();
+    leaves.sort_unstable();
+    leaves.dedup();
+    let mut levels = vec![leaves];
+    while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+        let next = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => allowlist_node(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+// the root a gated auction stores, nobody gets past an empty allowlist
+pub fn allowlist_root(members: &[Pubkey]) -> [u8; 32] {
+    allowlist_levels(members)
+        .last()
+        .and_then(|level| level.first())
+        .copied()
+        .unwrap_or_default()
+}
+
+// `None` if `bidder` is not a member
+pub fn allowlist_proof(members: &[Pubkey], bidder: &Pubkey) -> Option<Vec<[u8; 32]>> {
+    let levels = allowlist_levels(members);
+    let leaf = allowlist_leaf(bidder);
+    let mut index = levels.first()?.binary_search(&leaf).ok()?;
+    let mut proof = vec![];
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+    Some(proof)
+}
+
+fn instruction<T: BorshSerialize>(name: &str, args: &T, accounts: Vec<AccountMeta>) -> Instruction {
+    // anchor dispatches on the first 8 bytes of sha256("global:<name>")
+    let mut data = hash(format!("global:{name}").as_bytes()).to_bytes()[..8].to_vec();
+    args.serialize(&mut data)
+        .expect("args serialize into a vec");
+    Instruction {
+        program_id: ID,
+        accounts,
+        data,
+    }
+}
+
+fn optional(account: Option<&Pubkey>) -> Pubkey {
+    account.copied().unwrap_or(ID)
+}
+
+// bids in native SOL are paid from the bidder's lamports, which the program
+// wraps itself
+fn bidder_payment_account(auction: &AuctionKeys, bidder: &BidderKeys) -> Pubkey {
+    if auction.purchase_mint == spl_token::native_mint::ID {
+        ID
+    } else {
+        bidder.bidder_purchase_mint_ata
+    }
+}
+
+// native SOL auctions pay the seller and the house in lamports, their
+// purchase mint token accounts are left out
+fn seller_payment_accounts(auction: &AuctionKeys) -> (Pubkey, Pubkey) {
+    if auction.purchase_mint == spl_token::native_mint::ID {
+        (ID, ID)
+    } else {
+        (auction.seller_purchase_mint_ata, auction.treasury)
+    }
+}
+
+pub fn init_house(admin: &Pubkey, args: &InitHouseArgs) -> Instruction {
+    instruction(
+        "init_house",
+        args,
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(derive_house(&args.name), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+fn manage_house<T: BorshSerialize>(
+    name: &str,
+    admin: &Pubkey,
+    auction_house: &Pubkey,
+    args: &T,
+) -> Instruction {
+    instruction(
+        name,
+        args,
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*auction_house, false),
+        ],
+    )
+}
+
+pub fn update_house_fee(
+    admin: &Pubkey,
+    auction_house: &Pubkey,
+    args: &UpdateHouseFeeArgs,
+) -> Instruction {
+    manage_house("update_house_fee", admin, auction_house, args)
+}
+
+pub fn propose_admin(
+    admin: &Pubkey,
+    auction_house: &Pubkey,
+    args: &ProposeAdminArgs,
+) -> Instruction {
+    manage_house("propose_admin", admin, auction_house, args)
+}
+
+pub fn accept_admin(pending_admin: &Pubkey, auction_house: &Pubkey) -> Instruction {
+    manage_house("accept_admin", pending_admin, auction_house, &())
+}
+
+pub fn pause_house(admin: &Pubkey, auction_house: &Pubkey, args: &PauseHouseArgs) -> Instruction {
+    manage_house("pause_house", admin, auction_house, args)
+}
+
+pub fn set_keeper_reward(
+    admin: &Pubkey,
+    auction_house: &Pubkey,
+    args: &SetKeeperRewardArgs,
+) -> Instruction {
+    manage_house("set_keeper_reward", admin, auction_house, args)
+}
+
+pub fn sweep_fees(
+    admin: &Pubkey,
+    auction_house: &Pubkey,
+    mint: &Pubkey,
+    treasury: &Pubkey,
+    destination: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    instruction(
+        "sweep_fees",
+        &(),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(*auction_house, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(*treasury, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+    )
+}
+
+pub fn sweep_lamports(
+    admin: &Pubkey,
+    auction_house: &Pubkey,
+    destination: &Pubkey,
+    args: &SweepLamportsArgs,
+) -> Instruction {
+    instruction(
+        "sweep_lamports",
+        args,
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*auction_house, false),
+            AccountMeta::new(*destination, false),
+        ],
+    )
+}
+
+pub fn fund_keeper_pool(
+    admin: &Pubkey,
+    auction_house: &Pubkey,
+    args: &KeeperPoolArgs,
+) -> Instruction {
+    manage_keeper_pool("fund_keeper_pool", admin, auction_house, args)
+}
+
+// the lamports go back to the admin
+pub fn withdraw_keeper_pool(
+    admin: &Pubkey,
+    auction_house: &Pubkey,
+    args: &KeeperPoolArgs,
+) -> Instruction {
+    manage_keeper_pool("withdraw_keeper_pool", admin, auction_house, args)
+}
+
+fn manage_keeper_pool(
+    name: &str,
+    admin: &Pubkey,
+    auction_house: &Pubkey,
+    args: &KeeperPoolArgs,
+) -> Instruction {
+    instruction(
+        name,
+        args,
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(*auction_house, false),
+            AccountMeta::new(derive_keeper_pool(auction_house), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+// every kind of auction is created from the same accounts
+fn init<T: BorshSerialize>(
+    name: &str,
+    auction: &AuctionKeys,
+    nft: &NftKeys,
+    args: &T,
+) -> Instruction {
+    // the auction's seed comes first, the program derives the auction with it
+    instruction(
+        name,
+        &(auction.seed, args),
+        vec![
+            AccountMeta::new(auction.seller, true),
+            AccountMeta::new_readonly(auction.auction_house, false),
+            AccountMeta::new(auction.auction, false),
+            AccountMeta::new_readonly(auction.listing_mint, false),
+            AccountMeta::new_readonly(auction.purchase_mint, false),
+            AccountMeta::new(auction.seller_listing_mint_ata, false),
+            AccountMeta::new(auction.vault, false),
+            AccountMeta::new(auction.seller_stats, false),
+            AccountMeta::new(nft.metadata, false),
+            AccountMeta::new_readonly(nft.edition, false),
+            AccountMeta::new(nft.from_token_record, false),
+            AccountMeta::new(nft.to_token_record, false),
+            AccountMeta::new_readonly(nft.authorization_rules, false),
+            AccountMeta::new_readonly(nft.authorization_rules_program, false),
+            AccountMeta::new_readonly(nft.sysvar_instructions, false),
+            AccountMeta::new_readonly(nft.token_metadata_program, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(auction.token_program, false),
+        ],
+    )
+}
+
+// `nft` moves the listing from the seller to the vault
+pub fn init_auction(auction: &AuctionKeys, nft: &NftKeys, args: &InitAuctionArgs) -> Instruction {
+    init("init_auction", auction, nft, args)
+}
+
+pub fn init_dutch_auction(
+    auction: &AuctionKeys,
+    nft: &NftKeys,
+    args: &InitDutchAuctionArgs,
+) -> Instruction {
+    init("init_dutch_auction", auction, nft, args)
+}
+
+pub fn init_sealed_auction(
+    auction: &AuctionKeys,
+    nft: &NftKeys,
+    args: &InitSealedAuctionArgs,
+) -> Instruction {
+    init("init_sealed_auction", auction, nft, args)
+}
+
+pub fn init_batch_auction(
+    auction: &AuctionKeys,
+    nft: &NftKeys,
+    args: &InitBatchAuctionArgs,
+) -> Instruction {
+    init("init_batch_auction", auction, nft, args)
+}
+
+pub fn reveal_reserve(auction: &AuctionKeys, args: &RevealReserveArgs) -> Instruction {
+    instruction(
+        "reveal_reserve",
+        args,
+        vec![
+            AccountMeta::new_readonly(auction.seller, true),
+            AccountMeta::new_readonly(auction.auction_house, false),
+            AccountMeta::new(auction.auction, false),
+        ],
+    )
+}
+
+pub fn buy_now(auction: &AuctionKeys, buyer: &BidderKeys, args: &BuyNowArgs) -> Instruction {
+    let (seller_purchase_mint_ata, treasury) = seller_payment_accounts(auction);
+    instruction(
+        "buy_now",
+        args,
+        vec![
+            AccountMeta::new(buyer.bidder, true),
+            AccountMeta::new(auction.seller, false),
+            AccountMeta::new(auction.listing_mint, false),
+            AccountMeta::new_readonly(auction.purchase_mint, false),
+            AccountMeta::new(auction.auction_house, false),
+            AccountMeta::new(auction.auction, false),
+            AccountMeta::new(auction.vault, false),
+            AccountMeta::new(buyer.stats, false),
+            AccountMeta::new(auction.seller_stats, false),
+            AccountMeta::new(bidder_payment_account(auction, buyer), false),
+            AccountMeta::new(buyer.bidder_listing_mint_ata, false),
+            AccountMeta::new(seller_purchase_mint_ata, false),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(auction.token_program, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+// `bid` and `commit_bid` share their accounts
+fn place_bid<T: BorshSerialize>(
+    name: &str,
+    auction: &AuctionKeys,
+    bidder: &BidderKeys,
+    gate: Option<&GateKeys>,
+    args: &T,
+) -> Instruction {
+    instruction(
+        name,
+        args,
+        vec![
+            AccountMeta::new(bidder.bidder, true),
+            AccountMeta::new_readonly(auction.listing_mint, false),
+            AccountMeta::new_readonly(auction.purchase_mint, false),
+            AccountMeta::new_readonly(auction.auction_house, false),
+            AccountMeta::new(auction.auction, false),
+            AccountMeta::new(bidder_payment_account(auction, bidder), false),
+            AccountMeta::new(bidder.bid_state, false),
+            AccountMeta::new(bidder.bid_escrow, false),
+            AccountMeta::new(auction.vault, false),
+            AccountMeta::new(bidder.stats, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(auction.token_program, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(optional(gate.map(|gate| &gate.token_account)), false),
+            AccountMeta::new_readonly(
+                optional(gate.and_then(|gate| gate.metadata.as_ref())),
+                false,
+            ),
+        ],
+    )
+}
+
+// `gate` holds the accounts token- and collection-gated auctions check
+pub fn bid(
+    auction: &AuctionKeys,
+    bidder: &BidderKeys,
+    gate: Option<&GateKeys>,
+    args: &BidArgs,
+) -> Instruction {
+    place_bid("bid", auction, bidder, gate, args)
+}
+
+// sealed auctions aren't gated
+pub fn commit_bid(auction: &AuctionKeys, bidder: &BidderKeys, args: &CommitBidArgs) -> Instruction {
+    place_bid("commit_bid", auction, bidder, None, args)
+}
+
+// `prev` and `next` are the bid states the new bid is linked between, see
+// `state::batch_bid_order`
+pub fn batch_bid(
+    auction: &AuctionKeys,
+    bidder: &BidderKeys,
+    prev: Option<&Pubkey>,
+    next: Option<&Pubkey>,
+    args: &BatchBidArgs,
+) -> Instruction {
+    instruction(
+        "batch_bid",
+        args,
+        vec![
+            AccountMeta::new(bidder.bidder, true),
+            AccountMeta::new_readonly(auction.listing_mint, false),
+            AccountMeta::new_readonly(auction.purchase_mint, false),
+            AccountMeta::new_readonly(auction.auction_house, false),
+            AccountMeta::new(auction.auction, false),
+            AccountMeta::new(bidder_payment_account(auction, bidder), false),
+            AccountMeta::new(bidder.bid_state, false),
+            AccountMeta::new(bidder.bid_escrow, false),
+            AccountMeta::new(optional(prev), false),
+            AccountMeta::new_readonly(optional(next), false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(auction.token_program, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+// walks the bids in price order, at most 4 per transaction fit
+pub fn tally_batch(auction: &AuctionKeys, bid_states: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(auction.auction_house, false),
+        AccountMeta::new(auction.auction, false),
+    ];
+    accounts.extend(
+        bid_states
+            .iter()
+            .map(|bid_state| AccountMeta::new_readonly(*bid_state, false)),
+    );
+    instruction("tally_batch", &(), accounts)
+}
+
+// fills `bidders` in price order once the batch auction is tallied
+// `payer_purchase_mint_ata` receives the keeper's share of the house fees
+pub fn settle_batch(
+    payer: &Pubkey,
+    payer_purchase_mint_ata: Option<&Pubkey>,
+    auction: &AuctionKeys,
+    bidders: &[BidderKeys],
+) -> Instruction {
+    let (seller_purchase_mint_ata, treasury) = seller_payment_accounts(auction);
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(auction.seller, false),
+        AccountMeta::new(auction.listing_mint, false),
+        AccountMeta::new_readonly(auction.purchase_mint, false),
+        AccountMeta::new(auction.auction_house, false),
+        AccountMeta::new(auction.keeper_pool, false),
+        AccountMeta::new(auction.auction, false),
+        AccountMeta::new(auction.vault, false),
+        AccountMeta::new(auction.seller_listing_mint_ata, false),
+        AccountMeta::new(seller_purchase_mint_ata, false),
+        AccountMeta::new(treasury, false),
+        AccountMeta::new(optional(payer_purchase_mint_ata), false),
+        AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        AccountMeta::new_readonly(auction.token_program, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    // native SOL winners get the rest of their escrow back right away
+    for bidder in bidders {
+        accounts.extend([
+            AccountMeta::new(bidder.bid_state, false),
+            AccountMeta::new(bidder.bid_escrow, false),
+            AccountMeta::new(bidder.bidder, false),
+            AccountMeta::new(bidder.bidder_listing_mint_ata, false),
+        ]);
+    }
+    instruction("settle_batch", &(), accounts)
+}
+
+pub fn reveal_bid(auction: &AuctionKeys, bidder: &BidderKeys, args: &RevealBidArgs) -> Instruction {
+    instruction(
+        "reveal_bid",
+        args,
+        vec![
+            AccountMeta::new_readonly(bidder.bidder, true),
+            AccountMeta::new_readonly(auction.listing_mint, false),
+            AccountMeta::new_readonly(auction.purchase_mint, false),
+            AccountMeta::new_readonly(auction.auction_house, false),
+            AccountMeta::new(auction.auction, false),
+            AccountMeta::new(bidder.bid_state, false),
+            AccountMeta::new_readonly(bidder.bid_escrow, false),
+            AccountMeta::new_readonly(auction.vault, false),
+        ],
+    )
+}
+
+// anyone can pay for it, the penalty goes to the seller. Native SOL bids are
+// refunded to the bidder right away.
+pub fn penalize_bid(payer: &Pubkey, auction: &AuctionKeys, bidder: &BidderKeys) -> Instruction {
+    let (seller_purchase_mint_ata, _) = seller_payment_accounts(auction);
+    instruction(
+        "penalize_bid",
+        &(),
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(auction.seller, false),
+            AccountMeta::new(bidder.bidder, false),
+            AccountMeta::new_readonly(auction.purchase_mint, false),
+            AccountMeta::new_readonly(auction.auction_house, false),
+            AccountMeta::new(auction.auction, false),
+            AccountMeta::new(bidder.bid_state, false),
+            AccountMeta::new(bidder.bid_escrow, false),
+            AccountMeta::new(seller_purchase_mint_ata, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(auction.token_program, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+pub fn raise_bid(auction: &AuctionKeys, bidder: &BidderKeys, args: &RaiseBidArgs) -> Instruction {
+    instruction(
+        "raise_bid",
+        args,
+        vec![
+            AccountMeta::new(bidder.bidder, true),
+            AccountMeta::new_readonly(auction.listing_mint, false),
+            AccountMeta::new_readonly(auction.purchase_mint, false),
+            AccountMeta::new_readonly(auction.auction_house, false),
+            AccountMeta::new(auction.auction, false),
+            AccountMeta::new(bidder_payment_account(auction, bidder), false),
+            AccountMeta::new(bidder.bid_state, false),
+            AccountMeta::new(bidder.bid_escrow, false),
+            AccountMeta::new_readonly(auction.vault, false),
+            AccountMeta::new_readonly(auction.token_program, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+pub fn withdraw(auction: &AuctionKeys, bidder: &BidderKeys) -> Instruction {
+    instruction(
+        "withdraw",
+        &(),
+        vec![
+            AccountMeta::new(bidder.bidder, true),
+            AccountMeta::new(auction.purchase_mint, false),
+            AccountMeta::new_readonly(auction.auction_house, false),
+            AccountMeta::new(auction.auction, false),
+            AccountMeta::new(bidder_payment_account(auction, bidder), false),
+            AccountMeta::new(bidder.bid_escrow, false),
+            AccountMeta::new(bidder.bid_state, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(auction.token_program, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+// `payer_purchase_mint_ata` receives the keeper's share of the house fee.
+// `nft` moves the listing out of the vault, to the winner or back to the
+// seller when the reserve isn't met, and lists the creators paid royalties.
+pub fn finalize(
+    payer: &Pubkey,
+    payer_purchase_mint_ata: Option<&Pubkey>,
+    auction: &AuctionKeys,
+    bidder: &BidderKeys,
+    nft: &NftKeys,
+) -> Instruction {
+    let (_, treasury) = seller_payment_accounts(auction);
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(auction.seller, false),
+        AccountMeta::new(bidder.bidder, false),
+        AccountMeta::new(auction.listing_mint, false),
+        AccountMeta::new(auction.purchase_mint, false),
+        AccountMeta::new(auction.auction_house, false),
+        AccountMeta::new(auction.keeper_pool, false),
+        AccountMeta::new(auction.auction, false),
+        AccountMeta::new(bidder.bid_state, false),
+        AccountMeta::new(bidder.bid_escrow, false),
+        AccountMeta::new(auction.vault, false),
+        AccountMeta::new(bidder.stats, false),
+        AccountMeta::new(auction.seller_stats, false),
+        AccountMeta::new(bidder.bidder_listing_mint_ata, false),
+        AccountMeta::new(auction.seller_purchase_mint_ata, false),
+        AccountMeta::new(treasury, false),
+        AccountMeta::new(optional(payer_purchase_mint_ata), false),
+        AccountMeta::new(auction.seller_listing_mint_ata, false),
+        AccountMeta::new(bidder.bidder_purchase_mint_ata, false),
+        AccountMeta::new(nft.metadata, false),
+        AccountMeta::new_readonly(nft.edition, false),
+        AccountMeta::new(nft.from_token_record, false),
+        AccountMeta::new(nft.to_token_record, false),
+        AccountMeta::new_readonly(nft.authorization_rules, false),
+        AccountMeta::new_readonly(nft.authorization_rules_program, false),
+        AccountMeta::new_readonly(nft.sysvar_instructions, false),
+        AccountMeta::new_readonly(nft.token_metadata_program, false),
+        AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        AccountMeta::new_readonly(auction.token_program, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    // creators take their royalties in lamports from native SOL auctions
+    for creator in &nft.creators {
+        accounts.push(AccountMeta::new(*creator, false));
+        if auction.purchase_mint != spl_token::native_mint::ID {
+            let creator_purchase_mint_ata =
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    creator,
+                    &auction.purchase_mint,
+                    &auction.token_program,
+                );
+            accounts.push(AccountMeta::new(creator_purchase_mint_ata, false));
+        }
+    }
+    instruction("finalize", &(), accounts)
+}
+
+// `nft` moves the listing from the vault back to the seller
+pub fn cancel(auction: &AuctionKeys, nft: &NftKeys) -> Instruction {
+    instruction(
+        "cancel",
+        &(),
+        vec![
+            AccountMeta::new(auction.seller, true),
+            AccountMeta::new_readonly(auction.auction_house, false),
+            AccountMeta::new(auction.auction, false),
+            AccountMeta::new(auction.listing_mint, false),
+            AccountMeta::new_readonly(auction.purchase_mint, false),
+            AccountMeta::new(auction.seller_listing_mint_ata, false),
+            AccountMeta::new(auction.vault, false),
+            AccountMeta::new(auction.seller_stats, false),
+            AccountMeta::new(nft.metadata, false),
+            AccountMeta::new_readonly(nft.edition, false),
+            AccountMeta::new(nft.from_token_record, false),
+            AccountMeta::new(nft.to_token_record, false),
+            AccountMeta::new_readonly(nft.authorization_rules, false),
+            AccountMeta::new_readonly(nft.authorization_rules_program, false),
+            AccountMeta::new_readonly(nft.sysvar_instructions, false),
+            AccountMeta::new_readonly(nft.token_metadata_program, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(auction.token_program, false),
+        ],
+    )
+}
+
+// `nft` moves the listing from the vault back to the seller
+pub fn cancel_expired(payer: &Pubkey, auction: &AuctionKeys, nft: &NftKeys) -> Instruction {
+    instruction(
+        "cancel_expired",
+        &(),
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(auction.seller, false),
+            AccountMeta::new_readonly(auction.auction_house, false),
+            AccountMeta::new(auction.keeper_pool, false),
+            AccountMeta::new(auction.auction, false),
+            AccountMeta::new(auction.listing_mint, false),
+            AccountMeta::new_readonly(auction.purchase_mint, false),
+            AccountMeta::new(auction.seller_listing_mint_ata, false),
+            AccountMeta::new(auction.vault, false),
+            AccountMeta::new(auction.seller_stats, false),
+            AccountMeta::new(nft.metadata, false),
+            AccountMeta::new_readonly(nft.edition, false),
+            AccountMeta::new(nft.from_token_record, false),
+            AccountMeta::new(nft.to_token_record, false),
+            AccountMeta::new_readonly(nft.authorization_rules, false),
+            AccountMeta::new_readonly(nft.authorization_rules_program, false),
+            AccountMeta::new_readonly(nft.sysvar_instructions, false),
+            AccountMeta::new_readonly(nft.token_metadata_program, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(auction.token_program, false),
+        ],
+    )
+}
+
+// the highest bidder receives the house's cancellation penalty, there is
+// none to pay without bids. `nft` moves the listing from the vault back to the
+// seller
+pub fn cancel_early(
+    auction: &AuctionKeys,
+    nft: &NftKeys,
+    highest_bidder: Option<&BidderKeys>,
+) -> Instruction {
+    let (bid_state, bid_escrow) =
+        highest_bidder.map_or((ID, ID), |bidder| (bidder.bid_state, bidder.bid_escrow));
+    // a native SOL penalty is paid from the seller's lamports
+    let seller_purchase_mint_ata = match highest_bidder {
+        Some(_) if auction.purchase_mint != spl_token::native_mint::ID => {
+            auction.seller_purchase_mint_ata
+        }
+        _ => ID,
+    };
+    instruction(
+        "cancel_early",
+        &(),
+        vec![
+            AccountMeta::new(auction.seller, true),
+            AccountMeta::new_readonly(auction.auction_house, false),
+            AccountMeta::new(auction.auction, false),
+            AccountMeta::new(auction.listing_mint, false),
+            AccountMeta::new_readonly(auction.purchase_mint, false),
+            AccountMeta::new(auction.seller_listing_mint_ata, false),
+            AccountMeta::new(auction.vault, false),
+            AccountMeta::new(auction.seller_stats, false),
+            AccountMeta::new(nft.metadata, false),
+            AccountMeta::new_readonly(nft.edition, false),
+            AccountMeta::new(nft.from_token_record, false),
+            AccountMeta::new(nft.to_token_record, false),
+            AccountMeta::new_readonly(nft.authorization_rules, false),
+            AccountMeta::new_readonly(nft.authorization_rules_program, false),
+            AccountMeta::new_readonly(nft.sysvar_instructions, false),
+            AccountMeta::new_readonly(nft.token_metadata_program, false),
+            AccountMeta::new(seller_purchase_mint_ata, false),
+            AccountMeta::new_readonly(bid_state, false),
+            AccountMeta::new(bid_escrow, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(auction.token_program, false),
+        ],
+    )
+}
+
+pub fn close_cancelled(auction: &AuctionKeys) -> Instruction {
+    instruction(
+        "close_cancelled",
+        &(),
+        vec![
+            AccountMeta::new(auction.seller, true),
+            AccountMeta::new_readonly(auction.auction_house, false),
+            AccountMeta::new(auction.auction, false),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pda::{derive_auction_keys, derive_bidder_keys, derive_nft_keys};
+    use crate::TOKEN_2022_PROGRAM_ID;
+
+    #[test]
+    fn test_instruction_data() {
+        let ix = update_house_fee(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &UpdateHouseFeeArgs { fee: 250 },
+        );
+        assert_eq!(
+            ix.data[..8],
+            hash(b"global:update_house_fee").to_bytes()[..8]
+        );
+        assert_eq!(ix.data[8..], 250u16.to_le_bytes());
+        assert!(ix.accounts[0].is_signer);
+
+        // init instructions lead with the auction's seed
+        let auction = derive_auction_keys(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            7,
+            &spl_token::ID,
+        );
+        let nft = derive_nft_keys(&auction.listing_mint, None, &auction.seller, &auction.vault);
+        let ix = init_batch_auction(
+            &auction,
+            &nft,
+            &InitBatchAuctionArgs {
+                min_price: 1,
+                end_condition: EndCondition::Slot,
+                end: 100,
+                amount: 10,
+                decimal: 6,
+            },
+        );
+        assert_eq!(ix.data[8..16], 7u64.to_le_bytes());
+        assert_eq!(ix.data[16..24], 1u64.to_le_bytes());
+        assert_eq!(ix.accounts[2].pubkey, auction.auction);
+        assert_eq!(ix.accounts[7].pubkey, auction.seller_stats);
+    }
+
+    #[test]
+    fn test_native_sol_bids() {
+        let auction = derive_auction_keys(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &spl_token::native_mint::ID,
+            &Pubkey::new_unique(),
+            0,
+            &spl_token::ID,
+        );
+        let bidder = derive_bidder_keys(&Pubkey::new_unique(), &auction);
+
+        // the bidder pays from their lamports, not a token account
+        let ix = bid(
+            &auction,
+            &bidder,
+            None,
+            &BidArgs {
+                price: 1,
+                proof: vec![],
+                max_price: None,
+            },
+        );
+        assert_eq!(ix.accounts[5].pubkey, ID);
+        let ix = withdraw(&auction, &bidder);
+        assert_eq!(ix.accounts[4].pubkey, ID);
+
+        // and the seller and the house are paid in lamports
+        let ix = buy_now(&auction, &bidder, &BuyNowArgs { max_price: 1 });
+        assert_eq!(ix.accounts[11].pubkey, ID);
+        assert_eq!(ix.accounts[12].pubkey, ID);
+        let ix = penalize_bid(&Pubkey::new_unique(), &auction, &bidder);
+        assert_eq!(ix.accounts[8].pubkey, ID);
+    }
+
+    #[test]
+    fn test_token_2022_accounts() {
+        let auction = derive_auction_keys(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            0,
+            &TOKEN_2022_PROGRAM_ID,
+        );
+        let bidder = derive_bidder_keys(&Pubkey::new_unique(), &auction);
+        assert_eq!(
+            bidder.bid_escrow,
+            spl_associated_token_account::get_associated_token_address_with_program_id(
+                &bidder.bid_state,
+                &auction.purchase_mint,
+                &TOKEN_2022_PROGRAM_ID,
+            )
+        );
+        assert_ne!(
+            auction.vault,
+            spl_associated_token_account::get_associated_token_address(
+                &auction.auction,
+                &auction.listing_mint,
+            )
+        );
+
+        let ix = withdraw(&auction, &bidder);
+        assert_eq!(ix.accounts[5].pubkey, bidder.bid_escrow);
+        assert_eq!(ix.accounts[8].pubkey, TOKEN_2022_PROGRAM_ID);
+    }
+    #[test]
+    fn test_allowlist_proofs() {
+        let members: Vec<_> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let root = allowlist_root(&members);
+        // `verify_allowlist` in the auction program
+        let verify = |bidder: &Pubkey, proof: &[[u8; 32]]| {
+            proof.iter().fold(allowlist_leaf(bidder), |node, sibling| {
+                allowlist_node(&node, sibling)
+            }) == root
+        };
+        for member in &members {
+            let proof = allowlist_proof(&members, member).unwrap();
+            assert!(verify(member, &proof));
+        }
+        let outsider = Pubkey::new_unique();
+        assert_eq!(allowlist_proof(&members, &outsider), None);
+        assert!(!verify(
+            &outsider,
+            &allowlist_proof(&members, &members[0]).unwrap()
+        ));
+
+        assert_eq!(allowlist_root(&members[..1]), allowlist_leaf(&members[0]));
+        assert_eq!(allowlist_proof(&members[..1], &members[0]), Some(vec![]));
+        assert_eq!(allowlist_root(&[]), [0; 32]);
+    }
+}
@@ -0,0 +1,22 @@
+// Client side of the auction program: addresses, instructions, accounts,
+// events and errors. Everything here mirrors the program by hand, the same
+// way the IDL does, and has to be kept in sync with it.
+
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+pub mod errors;
+pub mod events;
+pub mod instructions;
+pub mod pda;
+pub mod state;
+
+pub const ID: Pubkey = pubkey!("6k28s3kLLzHwdDbuw6BVKSxK6oH1FUJQiWPeoFomnGrF");
+
+// Metaplex token metadata and token auth rules programs
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+pub const TOKEN_AUTH_RULES_PROGRAM_ID: Pubkey =
+    pubkey!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
+
+// the auction program takes either token program
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
@@ -0,0 +1,226 @@
+use solana_sdk::{pubkey::Pubkey, sysvar};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::state::{Gate, Metadata};
+use crate::{ID, TOKEN_AUTH_RULES_PROGRAM_ID, TOKEN_METADATA_PROGRAM_ID};
+
+pub fn derive_house(name: &str) -> Pubkey {
+    Pubkey::find_program_address(&[b"house", name.as_bytes()], &ID).0
+}
+
+pub fn derive_treasury(auction_house: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"treasury", auction_house.as_ref(), mint.as_ref()], &ID).0
+}
+
+// system account the house's lamport keeper tips are paid from
+pub fn derive_keeper_pool(auction_house: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"keeper_pool", auction_house.as_ref()], &ID).0
+}
+
+// a wallet's history in a house, see `state::UserStats`
+pub fn derive_user_stats(auction_house: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"stats", auction_house.as_ref(), wallet.as_ref()], &ID).0
+}
+
+// an auction is identified by its house, seller, both mints and the seed the
+// seller picked for it
+pub fn derive_auction(
+    auction_house: &Pubkey,
+    listing_mint: &Pubkey,
+    purchase_mint: &Pubkey,
+    seller: &Pubkey,
+    seed: u64,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"auction",
+            auction_house.as_ref(),
+            seller.as_ref(),
+            listing_mint.as_ref(),
+            purchase_mint.as_ref(),
+            &seed.to_le_bytes(),
+        ],
+        &ID,
+    )
+    .0
+}
+
+// `token_program` owns both mints, spl-token or Token-2022
+#[derive(Clone, Copy, Debug)]
+pub struct AuctionKeys {
+    pub auction_house: Pubkey,
+    pub seller: Pubkey,
+    pub listing_mint: Pubkey,
+    pub purchase_mint: Pubkey,
+    pub seed: u64,
+    pub token_program: Pubkey,
+    pub auction: Pubkey,
+    pub vault: Pubkey,
+    pub seller_listing_mint_ata: Pubkey,
+    pub seller_purchase_mint_ata: Pubkey,
+    pub treasury: Pubkey,
+    pub keeper_pool: Pubkey,
+    pub seller_stats: Pubkey,
+}
+
+pub fn derive_auction_keys(
+    auction_house: &Pubkey,
+    listing_mint: &Pubkey,
+    purchase_mint: &Pubkey,
+    seller: &Pubkey,
+    seed: u64,
+    token_program: &Pubkey,
+) -> AuctionKeys {
+    let auction = derive_auction(auction_house, listing_mint, purchase_mint, seller, seed);
+    let ata = |wallet: &Pubkey, mint: &Pubkey| {
+        get_associated_token_address_with_program_id(wallet, mint, token_program)
+    };
+
+    AuctionKeys {
+        auction_house: *auction_house,
+        seller: *seller,
+        listing_mint: *listing_mint,
+        purchase_mint: *purchase_mint,
+        seed,
+        token_program: *token_program,
+        auction,
+        vault: ata(&auction, listing_mint),
+        seller_listing_mint_ata: ata(seller, listing_mint),
+        seller_purchase_mint_ata: ata(seller, purchase_mint),
+        treasury: derive_treasury(auction_house, purchase_mint),
+        keeper_pool: derive_keeper_pool(auction_house),
+        seller_stats: derive_user_stats(auction_house, seller),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BidderKeys {
+    pub bidder: Pubkey,
+    pub bidder_purchase_mint_ata: Pubkey,
+    pub bidder_listing_mint_ata: Pubkey,
+    pub bid_state: Pubkey,
+    pub bid_escrow: Pubkey,
+    pub stats: Pubkey,
+}
+
+pub fn derive_bidder_keys(bidder: &Pubkey, auction: &AuctionKeys) -> BidderKeys {
+    let (bid_state, _bid_state_bump) =
+        Pubkey::find_program_address(&[b"bid", auction.auction.as_ref(), bidder.as_ref()], &ID);
+    let ata = |wallet: &Pubkey, mint: &Pubkey| {
+        get_associated_token_address_with_program_id(wallet, mint, &auction.token_program)
+    };
+
+    BidderKeys {
+        bidder: *bidder,
+        bidder_purchase_mint_ata: ata(bidder, &auction.purchase_mint),
+        bidder_listing_mint_ata: ata(bidder, &auction.listing_mint),
+        bid_state,
+        bid_escrow: ata(&bid_state, &auction.purchase_mint),
+        stats: derive_user_stats(&auction.auction_house, bidder),
+    }
+}
+
+// what a token- or collection-gated auction checks a bidder's holding with:
+// their account of the gate mint or of the NFT, and the NFT's metadata
+#[derive(Clone, Copy, Debug)]
+pub struct GateKeys {
+    pub token_account: Pubkey,
+    pub metadata: Option<Pubkey>,
+}
+
+// `nft` is the bidder's NFT of a collection gate's collection and
+// `token_program` owns the gate mint or the NFT. Allowlists need no accounts,
+// and neither does a collection gate without an NFT.
+pub fn derive_gate_keys(
+    bidder: &Pubkey,
+    gate: &Gate,
+    nft: Option<&Pubkey>,
+    token_program: &Pubkey,
+) -> Option<GateKeys> {
+    match (gate, nft) {
+        (Gate::Token { mint, .. }, _) => Some(GateKeys {
+            token_account: get_associated_token_address_with_program_id(
+                bidder,
+                mint,
+                token_program,
+            ),
+            metadata: None,
+        }),
+        (Gate::Collection { .. }, Some(nft)) => Some(GateKeys {
+            token_account: get_associated_token_address_with_program_id(
+                bidder,
+                nft,
+                token_program,
+            ),
+            metadata: Some(derive_metadata(nft)),
+        }),
+        _ => None,
+    }
+}
+
+fn metadata_pda(mint: &Pubkey, seeds: &[&[u8]]) -> Pubkey {
+    let seeds = [
+        &[
+            b"metadata".as_ref(),
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            mint.as_ref(),
+        ],
+        seeds,
+    ]
+    .concat();
+    Pubkey::find_program_address(&seeds, &TOKEN_METADATA_PROGRAM_ID).0
+}
+
+pub fn derive_metadata(mint: &Pubkey) -> Pubkey {
+    metadata_pda(mint, &[])
+}
+
+// the Metaplex accounts of a listing mint. Only programmable NFTs need more
+// than the metadata, the program id stands in for the rest otherwise.
+#[derive(Clone, Debug)]
+pub struct NftKeys {
+    pub metadata: Pubkey,
+    pub edition: Pubkey,
+    pub from_token_record: Pubkey,
+    pub to_token_record: Pubkey,
+    pub authorization_rules: Pubkey,
+    pub authorization_rules_program: Pubkey,
+    pub sysvar_instructions: Pubkey,
+    pub token_metadata_program: Pubkey,
+    pub creators: Vec<Pubkey>,
+}
+
+// `metadata` is the listing mint's metadata account if it has one, `from`
+// and `to` are the token accounts a programmable NFT moves between
+pub fn derive_nft_keys(
+    listing_mint: &Pubkey,
+    metadata: Option<&Metadata>,
+    from: &Pubkey,
+    to: &Pubkey,
+) -> NftKeys {
+    let mut keys = NftKeys {
+        metadata: derive_metadata(listing_mint),
+        edition: ID,
+        from_token_record: ID,
+        to_token_record: ID,
+        authorization_rules: ID,
+        authorization_rules_program: ID,
+        sysvar_instructions: ID,
+        token_metadata_program: ID,
+        creators: metadata.map(Metadata::royalty_creators).unwrap_or_default(),
+    };
+    let Some(metadata) = metadata.filter(|metadata| metadata.is_programmable()) else {
+        return keys;
+    };
+
+    keys.edition = metadata_pda(listing_mint, &[b"edition"]);
+    keys.from_token_record = metadata_pda(listing_mint, &[b"token_record", from.as_ref()]);
+    keys.to_token_record = metadata_pda(listing_mint, &[b"token_record", to.as_ref()]);
+    if let Some(rule_set) = metadata.rule_set {
+        keys.authorization_rules = rule_set;
+        keys.authorization_rules_program = TOKEN_AUTH_RULES_PROGRAM_ID;
+    }
+    keys.sysvar_instructions = sysvar::instructions::ID;
+    keys.token_metadata_program = TOKEN_METADATA_PROGRAM_ID;
+    keys
+}
@@ -0,0 +1,344 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::{clock::Clock, hash::hash, pubkey::Pubkey};
+use std::collections::HashMap;
+
+// Mirrors the accounts of the auction program.
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EndCondition {
+    Slot,
+    UnixTimestamp,
+}
+
+impl EndCondition {
+    // must match `reserve_reveal_window` in the auction program.
+    pub fn reserve_reveal_window(&self) -> u64 {
+        match self {
+            EndCondition::Slot => 9_000,
+            EndCondition::UnixTimestamp => 3_600,
+        }
+    }
+}
+
+#[derive(BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuctionKind {
+    English,
+    Dutch {
+        start_price: u64,
+        floor_price: u64,
+        start: u64,
+        step: u64,
+    },
+    Sealed {
+        reveal_end: u64,
+        second_price: bool,
+        second_highest: u64,
+        penalty_bps: u16,
+        unrevealed: u32,
+    },
+    Batch {
+        supply: u64,
+        head: Option<Pubkey>,
+        cursor: Option<Pubkey>,
+        unfilled: u64,
+        clearing_price: u64,
+        filling: bool,
+    },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeeperReward {
+    None,
+    FeeShare { bps: u16 },
+    Lamports { amount: u64 },
+}
+
+// who may bid, see `instructions::allowlist_root` for allowlists
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gate {
+    Allowlist { root: [u8; 32] },
+    Token { mint: Pubkey, min_amount: u64 },
+    Collection { collection: Pubkey },
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct AuctionHouse {
+    pub admin: Pubkey,
+    pub fee: u16,
+    pub cancel_penalty_bps: u16,
+    pub bump: u8,
+    pub pending_admin: Option<Pubkey>,
+    pub paused: bool,
+    pub keeper_reward: KeeperReward,
+    pub name: String,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct Auction {
+    pub seller: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub seed: u64,
+    pub bump: u8,
+    pub end: u64,
+    pub highest_price: u64,
+    pub decimal: u8,
+    pub bidder: Option<Pubkey>,
+    pub end_condition: EndCondition,
+    pub extension_window: u64,
+    pub extension: u64,
+    pub max_end: u64,
+    pub min_increment: u64,
+    pub min_increment_bps: u16,
+    pub reserve_price: u64,
+    pub reserve_hash: Option<[u8; 32]>,
+    pub kind: AuctionKind,
+    pub buy_now_price: Option<u64>,
+    pub gate: Option<Gate>,
+    // the highest bidder's proxy ceiling
+    pub proxy_max: Option<u64>,
+    pub cancelled: bool,
+    // bid states not closed yet, see `close_cancelled`
+    pub open_bids: u32,
+}
+
+impl Auction {
+    pub fn now(&self, clock: &Clock) -> u64 {
+        match self.end_condition {
+            EndCondition::Slot => clock.slot,
+            EndCondition::UnixTimestamp => clock.unix_timestamp as u64,
+        }
+    }
+
+    // must match `reserve_met` in the auction program.
+    pub fn reserve_met(&self, now: u64) -> bool {
+        match self.reserve_hash {
+            Some(_) => now >= self.reserve_deadline(),
+            None => self.highest_price >= self.reserve_price,
+        }
+    }
+
+    // must match `reserve_deadline` in the auction program.
+    pub fn reserve_deadline(&self) -> u64 {
+        self.settles_at()
+            .saturating_add(self.end_condition.reserve_reveal_window())
+    }
+
+    // must match `finalizes_at` in the auction program.
+    pub fn finalizes_at(&self) -> u64 {
+        match self.reserve_hash {
+            Some(_) => self.reserve_deadline(),
+            None => self.settles_at(),
+        }
+    }
+
+    // sealed auctions settle once the reveal phase is over
+    pub fn settles_at(&self) -> u64 {
+        match self.kind {
+            AuctionKind::Sealed { reveal_end, .. } => reveal_end,
+            _ => self.end,
+        }
+    }
+
+    // must match `min_bid` in the auction program.
+    pub fn min_bid(&self) -> Option<u64> {
+        if self.bidder.is_none() {
+            return self.highest_price.checked_add(1);
+        }
+        self.outbid(self.highest_price)
+    }
+
+    // must match `outbid` in the auction program.
+    pub fn outbid(&self, price: u64) -> Option<u64> {
+        let increment_bps = u128::from(price)
+            .checked_mul(u128::from(self.min_increment_bps))?
+            .checked_div(10_000)?;
+        let increment = u64::try_from(increment_bps)
+            .ok()?
+            .max(self.min_increment)
+            .max(1);
+        price.checked_add(increment)
+    }
+
+    // must match `dutch_price` in the auction program.
+    pub fn dutch_price(&self, now: u64) -> Option<u64> {
+        let AuctionKind::Dutch {
+            start_price,
+            floor_price,
+            start,
+            step,
+        } = self.kind
+        else {
+            return None;
+        };
+
+        let duration = self.end.checked_sub(start)?;
+        let elapsed = now.saturating_sub(start).min(duration);
+        let elapsed = elapsed - elapsed % step.max(1);
+        let decay = u128::from(start_price.checked_sub(floor_price)?)
+            .checked_mul(u128::from(elapsed))?
+            .checked_div(u128::from(duration))?;
+        start_price.checked_sub(u64::try_from(decay).ok()?)
+    }
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct BidState {
+    pub bidder: Pubkey,
+    pub auction: Pubkey,
+    pub bump: u8,
+    pub price: u64,
+    pub commitment: Option<[u8; 32]>,
+    pub quantity: u64,
+    pub next: Option<Pubkey>,
+    pub max_price: Option<u64>,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct UserStats {
+    pub wallet: Pubkey,
+    pub auction_house: Pubkey,
+    pub bump: u8,
+    pub auctions_created: u64,
+    pub auctions_completed: u64,
+    pub auctions_cancelled: u64,
+    pub bids_placed: u64,
+    pub auctions_won: u64,
+    pub volume: Vec<MintVolume>,
+}
+
+#[derive(BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MintVolume {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+// the bids of a batch auction in price order, starting at `from`
+pub fn batch_bid_order(bids: &HashMap<Pubkey, BidState>, from: Option<Pubkey>) -> Vec<Pubkey> {
+    let mut order = vec![];
+    let mut next = from;
+    while let Some(bid_state) = next {
+        order.push(bid_state);
+        next = bids.get(&bid_state).and_then(|bid| bid.next);
+    }
+    order
+}
+
+// The start of a Metaplex metadata account, which is all the auction reads.
+#[derive(BorshDeserialize, Debug)]
+pub struct Metadata {
+    pub key: u8,
+    pub update_authority: Pubkey,
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<Creator>>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<u8>,
+    // from the programmable config further down, see `deserialize_metadata`
+    #[borsh_skip]
+    pub rule_set: Option<Pubkey>,
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct Creator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+// what follows the token standard, older metadata accounts may end before it
+#[derive(BorshDeserialize)]
+struct MetadataConfig {
+    _collection: Option<(bool, Pubkey)>,
+    _uses: Option<(u8, u64, u64)>,
+    _collection_details: Option<(u8, u64)>,
+    programmable_config: Option<(u8, Option<Pubkey>)>,
+}
+
+const METADATA_V1: u8 = 4;
+const PROGRAMMABLE_NON_FUNGIBLE: u8 = 4;
+const PROGRAMMABLE_NON_FUNGIBLE_EDITION: u8 = 5;
+
+impl Metadata {
+    pub fn is_programmable(&self) -> bool {
+        matches!(
+            self.token_standard,
+            Some(PROGRAMMABLE_NON_FUNGIBLE | PROGRAMMABLE_NON_FUNGIBLE_EDITION)
+        )
+    }
+
+    // the creators the auction program pays royalties to, in metadata order
+    pub fn royalty_creators(&self) -> Vec<Pubkey> {
+        self.creators
+            .iter()
+            .flatten()
+            .filter(|creator| creator.share > 0)
+            .map(|creator| creator.address)
+            .collect()
+    }
+}
+
+pub fn deserialize_metadata(data: &[u8]) -> Option<Metadata> {
+    let data = &mut &data[..];
+    let mut metadata = Metadata::deserialize(data).ok()?;
+    if metadata.key != METADATA_V1 {
+        return None;
+    }
+    metadata.rule_set = MetadataConfig::deserialize(data)
+        .ok()
+        .and_then(|config| config.programmable_config)
+        .and_then(|(_version, rule_set)| rule_set);
+    Some(metadata)
+}
+
+// offsets of the fields accounts are looked up by, the discriminator first
+pub const AUCTION_SELLER_OFFSET: usize = 8;
+pub const AUCTION_MINT_A_OFFSET: usize = 8 + 32;
+pub const AUCTION_MINT_B_OFFSET: usize = 8 + 32 + 32;
+pub const BID_STATE_BIDDER_OFFSET: usize = 8;
+pub const BID_STATE_AUCTION_OFFSET: usize = 8 + 32;
+
+// anchor prefixes every account with the first 8 bytes of
+// sha256("account:<Name>")
+pub fn account_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0; 8];
+    discriminator.copy_from_slice(&hash(format!("account:{name}").as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+pub trait AuctionAccount: BorshDeserialize {
+    const NAME: &'static str;
+
+    fn discriminator() -> [u8; 8] {
+        account_discriminator(Self::NAME)
+    }
+
+    // accounts may be padded at the end
+    fn from_account_data(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            return None;
+        }
+        Self::deserialize(&mut &data[8..]).ok()
+    }
+}
+
+impl AuctionAccount for AuctionHouse {
+    const NAME: &'static str = "AuctionHouse";
+}
+
+impl AuctionAccount for Auction {
+    const NAME: &'static str = "Auction";
+}
+
+impl AuctionAccount for BidState {
+    const NAME: &'static str = "BidState";
+}
+
+impl AuctionAccount for UserStats {
+    const NAME: &'static str = "UserStats";
+}
@@ -0,0 +1,31 @@
+#!/bin/bash
+# Proxy bidding against a running solana-test-validator, with the program
+# deployed and setup.sh run: bidder1 follows the auction and outbids everyone
+# up to 5, bidder2 bids by hand until priced out.
+set -e
+
+export HOUSE="auction_house2"
+CLI="auction-cli -u localhost -a $HOUSE"
+MINT_A=$(solana-keygen pubkey mintA.json)
+MINT_B=$(solana-keygen pubkey mintB.json)
+SELLER=$(solana-keygen pubkey auctioneer.json)
+# a fresh lot every run
+LOT=$(date +%s)
+AUCTION="--listing-mint $MINT_A --purchase-mint $MINT_B --seller $SELLER --seed $LOT"
+
+$CLI init-house 100 || echo "house $HOUSE already exists"
+$CLI -k auctioneer.json init-auction --listing-mint $MINT_A --purchase-mint $MINT_B --seed $LOT \
+    --starting-price 4 --amount 100 --min-increment 0.1 --end-slot $(( $(solana -ul slot) + 300 ))
+
+$CLI -k bidder1.json follow $AUCTION --max-price 5 --increment 0.05 --interval 5 &
+FOLLOW=$!
+trap "kill $FOLLOW" EXIT
+
+sleep 10
+$CLI -k bidder2.json bid $AUCTION --price 4.3
+for price in 4.7 5.1; do
+    sleep 10
+    $CLI -k bidder2.json raise-bid $AUCTION --price $price
+done
+sleep 10
+$CLI show-auction $AUCTION
@@ -10,4 +10,66 @@ pub enum AuctionError {
     PriceTooLow,
     #[msg("NotEligibleToWithdraw")]
     NotEligibleToWithdraw,
+    #[msg("AuctionEnded")]
+    AuctionEnded,
+    #[msg("InvalidEnd")]
+    InvalidEnd,
+    #[msg("InvalidReserve")]
+    InvalidReserve,
+    #[msg("WrongAuctionKind")]
+    WrongAuctionKind,
+    #[msg("InvalidSchedule")]
+    InvalidSchedule,
+    #[msg("NotInRevealPhase")]
+    NotInRevealPhase,
+    #[msg("InvalidCommitment")]
+    InvalidCommitment,
+    #[msg("InsufficientCollateral")]
+    InsufficientCollateral,
+    #[msg("InvalidPenalty")]
+    InvalidPenalty,
+    #[msg("MissingPenaltyAccounts")]
+    MissingPenaltyAccounts,
+    #[msg("NotHouseAdmin")]
+    NotHouseAdmin,
+    #[msg("InvalidFee")]
+    InvalidFee,
+    #[msg("HousePaused")]
+    HousePaused,
+    #[msg("InvalidBidOrder")]
+    InvalidBidOrder,
+    #[msg("InvalidQuantity")]
+    InvalidQuantity,
+    #[msg("AuctionNotEnded")]
+    AuctionNotEnded,
+    #[msg("WrongSettlementPhase")]
+    WrongSettlementPhase,
+    #[msg("MissingTokenAccount")]
+    MissingTokenAccount,
+    #[msg("InsufficientHouseLamports")]
+    InsufficientHouseLamports,
+    #[msg("NonTransferableMint")]
+    NonTransferableMint,
+    #[msg("PermanentDelegateMint")]
+    PermanentDelegateMint,
+    #[msg("ProgrammableNftUnsupported")]
+    ProgrammableNftUnsupported,
+    #[msg("MissingProgrammableAccounts")]
+    MissingProgrammableAccounts,
+    #[msg("InvalidCreatorAccount")]
+    InvalidCreatorAccount,
+    #[msg("NotAllowedToBid")]
+    NotAllowedToBid,
+    #[msg("MaxPriceTooLow")]
+    MaxPriceTooLow,
+    #[msg("AuctionCancelled")]
+    AuctionCancelled,
+    #[msg("UnrevealedBids")]
+    UnrevealedBids,
+    #[msg("RoyaltiesUnsupported")]
+    RoyaltiesUnsupported,
+    #[msg("AuctionNotCancelled")]
+    AuctionNotCancelled,
+    #[msg("BidsOutstanding")]
+    BidsOutstanding,
 }
@@ -0,0 +1,162 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{AuctionKind, EndCondition, KeeperReward};
+
+#[event]
+pub struct HouseFeeUpdated {
+    pub auction_house: Pubkey,
+    pub old_fee: u16,
+    pub new_fee: u16,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub auction_house: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminAccepted {
+    pub auction_house: Pubkey,
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+pub struct HousePaused {
+    pub auction_house: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct KeeperRewardUpdated {
+    pub auction_house: Pubkey,
+    pub keeper_reward: KeeperReward,
+}
+
+#[event]
+pub struct FeesSwept {
+    pub auction_house: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct KeeperPoolFunded {
+    pub auction_house: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct KeeperPoolWithdrawn {
+    pub auction_house: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct HouseCreated {
+    pub auction_house: Pubkey,
+    pub admin: Pubkey,
+    pub fee: u16,
+    pub name: String,
+}
+
+#[event]
+pub struct AuctionCreated {
+    pub auction_house: Pubkey,
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub seed: u64,
+    pub amount: u64,
+    pub kind: AuctionKind,
+    pub end_condition: EndCondition,
+    pub end: u64,
+}
+
+#[event]
+pub struct BidPlaced {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub price: u64,
+    pub previous_bidder: Option<Pubkey>,
+    pub previous_price: u64,
+    // late bids can push the end out
+    pub end: u64,
+}
+
+// a proxy bid held the lead against `challenger`, its price went up to `price`
+#[event]
+pub struct ProxyBidRaised {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub challenger: Pubkey,
+    pub price: u64,
+    pub end: u64,
+}
+
+#[event]
+pub struct BidWithdrawn {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct AuctionFinalized {
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+    // `None` when the reserve was not met and the lot went back to the seller
+    pub winner: Option<Pubkey>,
+    pub price: u64,
+    pub total: u64,
+    pub house_fee: u64,
+    // paid to the creators when the lot is a Metaplex NFT
+    pub royalties: u64,
+    pub seller_proceeds: u64,
+}
+
+#[event]
+pub struct AuctionCancelled {
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+    pub early: bool,
+    // paid by the seller to the highest bidder
+    pub penalty: u64,
+}
+
+#[event]
+pub struct CancelledAuctionClosed {
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+}
+
+#[event]
+pub struct BatchBidFilled {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub quantity: u64,
+    pub price: u64,
+    pub total: u64,
+    pub house_fee: u64,
+}
+
+#[event]
+pub struct BatchSettled {
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+    pub clearing_price: u64,
+    pub sold: u64,
+}
+
+// the house paid `keeper` for settling `auction`, `tokens` are in the purchase mint
+#[event]
+pub struct KeeperPaid {
+    pub auction: Pubkey,
+    pub keeper: Pubkey,
+    pub lamports: u64,
+    pub tokens: u64,
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::errors::AuctionError;
+use crate::nft::read_metadata;
+use crate::state::{verify_allowlist, Gate};
+
+// Gated auctions only take bids from an allowlist or from holders of a token
+// or of an NFT from a collection. Bidders prove it when they place their bid,
+// later raises don't check again.
+
+// `token_account` is the bidder's account of the gate mint or of their NFT,
+// `metadata` the NFT's Metaplex metadata.
+pub(crate) fn check_gate(
+    gate: &Gate,
+    bidder: &Pubkey,
+    proof: &[[u8; 32]],
+    token_account: Option<&InterfaceAccount<TokenAccount>>,
+    metadata: Option<&AccountInfo>,
+) -> Result<()> {
+    let allowed = match gate {
+        Gate::Allowlist { root } => verify_allowlist(root, bidder, proof),
+        Gate::Token { mint, min_amount } => token_account.is_some_and(|account| {
+            account.owner == *bidder && account.mint == *mint && account.amount >= *min_amount
+        }),
+        Gate::Collection { collection } => match (token_account, metadata) {
+            (Some(account), Some(metadata)) if account.owner == *bidder && account.amount > 0 => {
+                // only the metadata program writes metadata accounts, so one
+                // naming the NFT's mint is that mint's metadata
+                read_metadata(metadata)?.is_some_and(|metadata| {
+                    metadata.mint == account.mint
+                        && metadata
+                            .collection
+                            .is_some_and(|parent| parent.verified && parent.key == *collection)
+                })
+            }
+            _ => false,
+        },
+    };
+    require!(allowed, AuctionError::NotAllowedToBid);
+    Ok(())
+}
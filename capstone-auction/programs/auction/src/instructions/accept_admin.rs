@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AuctionError;
+use crate::events::AdminAccepted;
+use crate::state::AuctionHouse;
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+    #[account(
+        mut,
+        constraint = auction_house.pending_admin == Some(pending_admin.key()) @ AuctionError::NotHouseAdmin,
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+}
+
+impl<'info> AcceptAdmin<'info> {
+    pub fn accept_admin(&mut self) -> Result<()> {
+        let old_admin = self.auction_house.admin;
+        self.auction_house.admin = self.pending_admin.key();
+        self.auction_house.pending_admin = None;
+
+        emit!(AdminAccepted {
+            auction_house: self.auction_house.key(),
+            old_admin,
+            new_admin: self.auction_house.admin,
+        });
+        Ok(())
+    }
+}
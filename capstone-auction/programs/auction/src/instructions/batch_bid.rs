@@ -0,0 +1,175 @@
+use crate::errors::AuctionError;
+use crate::events::BidPlaced;
+use crate::instructions::{calculate_amount_b, deposit_into_escrow};
+use crate::state::{Auction, AuctionHouse, AuctionKind, BidState};
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+// bidder asks for `quantity` units of a batch auction at a unit price. The new
+// bid is linked into the price-ordered list right after `prev_bid_state`, or at
+// the head when there is none; `next_bid_state` is the bid currently following
+// that spot. Both are found off-chain and only checked here.
+#[derive(Accounts)]
+pub struct BatchBid<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        constraint = !auction_house.paused @ AuctionError::HousePaused,
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    #[account(
+        mut,
+        seeds = [b"auction", auction_house.key().as_ref(), auction.seller.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = !auction.cancelled @ AuctionError::AuctionCancelled,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+    // left out to bid native SOL straight from the bidder's wallet
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = bidder,
+    )]
+    pub bidder_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        init,
+        payer = bidder,
+        space = 8 + BidState::INIT_SPACE,
+        seeds = [b"bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump,
+    )]
+    pub bid_state: Box<Account<'info, BidState>>,
+    #[account(
+        init,
+        payer = bidder,
+        associated_token::mint = mint_b,
+        associated_token::authority = bid_state,
+    )]
+    pub bidder_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = prev_bid_state.auction == auction.key() @ AuctionError::InvalidBidOrder,
+    )]
+    pub prev_bid_state: Option<Box<Account<'info, BidState>>>,
+    #[account(
+        constraint = next_bid_state.auction == auction.key() @ AuctionError::InvalidBidOrder,
+    )]
+    pub next_bid_state: Option<Box<Account<'info, BidState>>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> BatchBid<'info> {
+    pub fn batch_bid(&mut self, quantity: u64, price: u64, bumps: &BatchBidBumps) -> Result<()> {
+        let AuctionKind::Batch { head, supply, .. } = self.auction.kind else {
+            return err!(AuctionError::WrongAuctionKind);
+        };
+        require!(
+            self.auction.now()? < self.auction.end,
+            AuctionError::AuctionEnded
+        );
+        // `highest_price` holds the minimum unit price - 1
+        require!(
+            price > self.auction.highest_price,
+            AuctionError::PriceTooLow
+        );
+        require!(
+            quantity > 0 && quantity <= supply,
+            AuctionError::InvalidQuantity
+        );
+
+        let next = self.link(price, head)?;
+        self.auction.open_bid()?;
+        self.bid_state.set_inner(BidState {
+            bidder: self.bidder.key(),
+            auction: self.auction.key(),
+            bump: bumps.bid_state,
+            price,
+            commitment: None,
+            quantity,
+            next,
+            max_price: None,
+        });
+
+        // the bid ranked just above takes the place of the previous high bidder
+        let (previous_bidder, previous_price) = match &self.prev_bid_state {
+            Some(prev) => (Some(prev.bidder), prev.price),
+            None => (None, 0),
+        };
+        emit!(BidPlaced {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            price,
+            previous_bidder,
+            previous_price,
+            end: self.auction.end,
+        });
+
+        let amount = calculate_amount_b(
+            quantity,
+            price,
+            self.mint_a.decimals,
+            self.mint_b.decimals,
+            self.auction.decimal,
+        )
+        .ok_or(AuctionError::ArithematicOverflow)?;
+        self.deposit_bid(amount)
+    }
+
+    // checks the new bid fits between `prev_bid_state` and `next_bid_state`,
+    // points `prev_bid_state` (or the head) at it and returns its successor.
+    fn link(&mut self, price: u64, head: Option<Pubkey>) -> Result<Option<Pubkey>> {
+        let next = self.next_bid_state.as_ref().map(|next| next.key());
+        // strictly lower than the next bid keeps equal prices in arrival order
+        if let Some(next_bid_state) = &self.next_bid_state {
+            require!(price > next_bid_state.price, AuctionError::InvalidBidOrder);
+        }
+
+        let bid_state = self.bid_state.key();
+        match &mut self.prev_bid_state {
+            Some(prev) => {
+                require!(
+                    prev.price >= price && prev.next == next,
+                    AuctionError::InvalidBidOrder
+                );
+                prev.next = Some(bid_state);
+            }
+            None => {
+                require!(head == next, AuctionError::InvalidBidOrder);
+                // bidding closes before settlement starts, so the cursor
+                // just follows the head until then
+                if let AuctionKind::Batch {
+                    ref mut head,
+                    ref mut cursor,
+                    ..
+                } = self.auction.kind
+                {
+                    *head = Some(bid_state);
+                    *cursor = Some(bid_state);
+                }
+            }
+        }
+        Ok(next)
+    }
+
+    fn deposit_bid(&mut self, amount: u64) -> Result<()> {
+        deposit_into_escrow(
+            &self.bidder,
+            self.bidder_mint_b_ata.as_deref(),
+            &self.bidder_escrow,
+            &self.mint_b,
+            amount,
+            &self.token_program,
+            &self.system_program,
+        )
+    }
+}
@@ -1,9 +1,15 @@
 use crate::errors::AuctionError;
-use crate::state::{Auction, AuctionHouse, BidState};
+use crate::events::{BidPlaced, ProxyBidRaised};
+use crate::gate::check_gate;
+use crate::state::{Auction, AuctionHouse, AuctionKind, BidState, UserStats};
+use crate::stats::open_stats;
+use crate::token_extensions::amount_with_fee;
 
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
 use anchor_spl::{
     associated_token::AssociatedToken,
+    token::{spl_token, sync_native, SyncNative},
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
@@ -14,22 +20,25 @@ pub struct Bid<'info> {
     pub mint_a: Box<InterfaceAccount<'info, Mint>>,
     pub mint_b: Box<InterfaceAccount<'info, Mint>>,
     #[account(
+        constraint = !auction_house.paused @ AuctionError::HousePaused,
         seeds = [b"house", auction_house.name.as_bytes()],
         bump = auction_house.bump,
     )]
     pub auction_house: Box<Account<'info, AuctionHouse>>,
     #[account(
         mut,
-        seeds = [b"auction", auction_house.key().as_ref(), auction.seller.key().as_ref(),mint_a.key().as_ref(), mint_b.key().as_ref()],
+        seeds = [b"auction", auction_house.key().as_ref(), auction.seller.key().as_ref(),mint_a.key().as_ref(), mint_b.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
         bump = auction.bump,
+        constraint = !auction.cancelled @ AuctionError::AuctionCancelled,
     )]
     pub auction: Box<Account<'info, Auction>>,
+    // left out to bid native SOL straight from the bidder's wallet
     #[account(
         mut,
         associated_token::mint = mint_b,
         associated_token::authority = bidder,
     )]
-    pub bidder_mint_b_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub bidder_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     #[account(
         init,
         payer = bidder,
@@ -51,74 +60,238 @@ pub struct Bid<'info> {
         associated_token::authority = auction,
     )]
     pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        space = 8 + UserStats::INIT_SPACE,
+        seeds = [b"stats", auction_house.key().as_ref(), bidder.key().as_ref()],
+        bump,
+    )]
+    pub bidder_stats: Box<Account<'info, UserStats>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
+    // token- and collection-gated auctions: the bidder's account of the gate
+    // mint or of their NFT from the collection
+    pub gate_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// CHECK: Metaplex metadata of the NFT in `gate_token_account`, checked in `check_gate`.
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
 }
 
 impl<'info> Bid<'info> {
-    pub fn bid(&mut self, price: u64, bump: &BidBumps) -> Result<()> {
-        require!(
-            price > self.auction.highest_price,
-            AuctionError::PriceTooLow
+    // `proof` is the bidder's merkle proof in allowlist-gated auctions. A bid
+    // below a leading proxy's ceiling is placed but outbid right away.
+    pub fn bid(
+        &mut self,
+        price: u64,
+        max_price: Option<u64>,
+        proof: &[[u8; 32]],
+        bump: &BidBumps,
+    ) -> Result<()> {
+        if let Some(gate) = &self.auction.gate {
+            check_gate(
+                gate,
+                self.bidder.key,
+                proof,
+                self.gate_token_account.as_deref(),
+                self.gate_metadata.as_deref(),
+            )?;
+        }
+
+        let now = self.auction.now()?;
+        let (previous_bidder, previous_price) = (self.auction.bidder, self.auction.highest_price);
+        let leads = self
+            .auction
+            .place_bid(self.bidder.key(), price, max_price, now)?;
+        self.create_bid_state(price, None, bump)?;
+        self.bid_state.max_price = max_price;
+        self.record_bid(bump)?;
+
+        emit_bid(
+            &self.auction,
+            self.bidder.key(),
+            price,
+            leads,
+            previous_bidder,
+            previous_price,
         );
-        self.update_auction(price);
-        self.create_bid_state(bump);
-        self.deposit_bid()?;
+
+        let amount = calculate_amount_b(
+            self.vault.amount,
+            self.bid_state.covered_price(),
+            self.mint_a.decimals,
+            self.mint_b.decimals,
+            self.auction.decimal,
+        )
+        .ok_or(AuctionError::ArithematicOverflow)?;
+        self.deposit_bid(amount)?;
         Ok(())
     }
 
-    fn update_auction(&mut self, price: u64) {
-        self.auction.bidder = Some(self.bidder.key());
-        self.auction.highest_price = price;
+    // sealed auctions: the price stays hidden behind `commitment` until it is
+    // revealed, `deposit` must be enough to cover it.
+    pub fn commit_bid(
+        &mut self,
+        commitment: [u8; 32],
+        deposit: u64,
+        bump: &BidBumps,
+    ) -> Result<()> {
+        require!(
+            matches!(self.auction.kind, AuctionKind::Sealed { .. }),
+            AuctionError::WrongAuctionKind
+        );
+        require!(
+            self.auction.now()? < self.auction.end,
+            AuctionError::AuctionEnded
+        );
+
+        self.auction.commit_bid()?;
+        self.create_bid_state(0, Some(commitment), bump)?;
+        self.record_bid(bump)?;
+        self.deposit_bid(deposit)
+    }
+
+    // raises aren't counted, the bid stays the same
+    fn record_bid(&mut self, bump: &BidBumps) -> Result<()> {
+        open_stats(
+            &mut self.bidder_stats,
+            self.bidder.key,
+            &self.auction_house.key(),
+            bump.bidder_stats,
+            &self.mint_b.key(),
+            &self.bidder,
+            &self.system_program,
+        )?;
+        self.bidder_stats.record_bid();
+        Ok(())
     }
 
-    fn create_bid_state(&mut self, bump: &BidBumps) {
+    fn create_bid_state(
+        &mut self,
+        price: u64,
+        commitment: Option<[u8; 32]>,
+        bump: &BidBumps,
+    ) -> Result<()> {
+        self.auction.open_bid()?;
         self.bid_state.set_inner(BidState {
             bidder: self.bidder.key(),
             bump: bump.bid_state,
             auction: self.auction.key(),
+            price,
+            commitment,
+            quantity: 0,
+            next: None,
+            max_price: None,
         });
+        Ok(())
     }
 
-    fn deposit_bid(&mut self) -> Result<()> {
-        let amount = calculate_amount_b(
-            self.vault.amount,
-            self.auction.highest_price,
-            self.mint_a.decimals,
-            self.mint_b.decimals,
-            self.auction.decimal,
+    fn deposit_bid(&mut self, amount: u64) -> Result<()> {
+        //example bid_price = 2B/A, amount = 50, 2*50 = 100B
+        deposit_into_escrow(
+            &self.bidder,
+            self.bidder_mint_b_ata.as_deref(),
+            &self.bidder_escrow,
+            &self.mint_b,
+            amount,
+            &self.token_program,
+            &self.system_program,
         )
-        .ok_or(AuctionError::ArithematicOverflow)?;
+    }
+}
 
-        let cpi_program = self.token_program.to_account_info();
-        //example bid_price = 2B/A, amount = 50, 2*50 = 100B
-        let transfer_accounts = TransferChecked {
-            from: self.bidder_mint_b_ata.to_account_info(),
-            to: self.bidder_escrow.to_account_info(),
-            mint: self.mint_b.to_account_info(),
-            authority: self.bidder.to_account_info(),
+// shared by `bid` and `raise_bid`, a bid that didn't take the lead was
+// outbid by the leader's proxy
+pub(crate) fn emit_bid(
+    auction: &Account<Auction>,
+    bidder: Pubkey,
+    price: u64,
+    leads: bool,
+    previous_bidder: Option<Pubkey>,
+    previous_price: u64,
+) {
+    emit!(BidPlaced {
+        auction: auction.key(),
+        bidder,
+        price: if leads { auction.highest_price } else { price },
+        previous_bidder,
+        previous_price,
+        end: auction.end,
+    });
+    if let (false, Some(leader)) = (leads, auction.bidder) {
+        emit!(ProxyBidRaised {
+            auction: auction.key(),
+            bidder: leader,
+            challenger: bidder,
+            price: auction.highest_price,
+            end: auction.end,
+        });
+    }
+}
+
+pub(crate) fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::ID
+}
+
+// moves `amount` from the bidder into the escrow. Without a token account the
+// auction has to be in native SOL, the lamports are wrapped in the escrow. The
+// bidder pays any transfer fee on top, so the escrow always covers the bid.
+pub(crate) fn deposit_into_escrow<'info>(
+    bidder: &Signer<'info>,
+    bidder_mint_b_ata: Option<&InterfaceAccount<'info, TokenAccount>>,
+    escrow: &InterfaceAccount<'info, TokenAccount>,
+    mint_b: &InterfaceAccount<'info, Mint>,
+    amount: u64,
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let Some(bidder_mint_b_ata) = bidder_mint_b_ata else {
+        require!(
+            is_native_mint(&mint_b.key()),
+            AuctionError::MissingTokenAccount
+        );
+
+        let transfer_accounts = Transfer {
+            from: bidder.to_account_info(),
+            to: escrow.to_account_info(),
         };
+        transfer(
+            CpiContext::new(system_program.to_account_info(), transfer_accounts),
+            amount,
+        )?;
 
-        let cpi_ctx = CpiContext::new(cpi_program, transfer_accounts);
+        let sync_accounts = SyncNative {
+            account: escrow.to_account_info(),
+        };
+        return sync_native(CpiContext::new(
+            token_program.to_account_info(),
+            sync_accounts,
+        ));
+    };
 
-        transfer_checked(cpi_ctx, amount, self.mint_b.decimals)?;
+    let transfer_accounts = TransferChecked {
+        from: bidder_mint_b_ata.to_account_info(),
+        to: escrow.to_account_info(),
+        mint: mint_b.to_account_info(),
+        authority: bidder.to_account_info(),
+    };
 
-        Ok(())
-    }
+    let cpi_ctx = CpiContext::new(token_program.to_account_info(), transfer_accounts);
+
+    transfer_checked(cpi_ctx, amount_with_fee(mint_b, amount)?, mint_b.decimals)
 }
 
-fn calculate_amount_b(
+pub(crate) fn calculate_amount_b(
     amount_a: u64,
     price: u64,
     decimals_a: u8,
     decimals_b: u8,
     price_decimals: u8,
 ) -> Option<u64> {
-    // Scale factors
-    let scale_a = 10u128.pow(decimals_a as u32);
-    let scale_b = 10u128.pow(decimals_b as u32);
-    let scale_price = 10u128.pow(price_decimals as u32);
+    // Scale factors, too many decimals overflow instead of panicking
+    let scale_a = 10u128.checked_pow(decimals_a as u32)?;
+    let scale_b = 10u128.checked_pow(decimals_b as u32)?;
+    let scale_price = 10u128.checked_pow(price_decimals as u32)?;
 
     // Convert to u128 to prevent overflow
     let amount_a_128 = amount_a as u128;
@@ -134,3 +307,98 @@ fn calculate_amount_b(
     // Convert back to u64 if safe
     amount_b.try_into().ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::calculate_amount_b;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn rounds_down_to_what_the_price_covers(
+            amount_a: u64,
+            price: u64,
+            decimals_a in 0u8..=9,
+            decimals_b in 0u8..=9,
+            price_decimals in 0u8..=9,
+        ) {
+            let numerator = u128::from(amount_a) * u128::from(price);
+            let scale_b = 10u128.pow(decimals_b.into());
+            let denominator = 10u128.pow(u32::from(decimals_a) + u32::from(price_decimals));
+
+            match calculate_amount_b(amount_a, price, decimals_a, decimals_b, price_decimals) {
+                Some(amount_b) => {
+                    // floor(numerator * scale_b / denominator)
+                    let amount_b = u128::from(amount_b);
+                    let exact = numerator.checked_mul(scale_b).unwrap();
+                    prop_assert!(amount_b * denominator <= exact);
+                    prop_assert!(exact < (amount_b + 1) * denominator);
+                }
+                // only when the result or the scaled product doesn't fit
+                None => prop_assert!(
+                    numerator
+                        .checked_mul(scale_b)
+                        .is_none_or(|exact| exact / denominator > u128::from(u64::MAX))
+                ),
+            }
+        }
+
+        #[test]
+        fn never_decreases_with_the_price(
+            amount_a in 0u64..=u64::from(u32::MAX),
+            price in 0u64..=u64::from(u32::MAX),
+            raise in 0u64..=u64::from(u32::MAX),
+            decimals_a in 0u8..=9,
+            decimals_b in 0u8..=9,
+            price_decimals in 0u8..=9,
+        ) {
+            let low = calculate_amount_b(amount_a, price, decimals_a, decimals_b, price_decimals);
+            let high =
+                calculate_amount_b(amount_a, price + raise, decimals_a, decimals_b, price_decimals);
+            if let (Some(low), Some(high)) = (low, high) {
+                prop_assert!(low <= high);
+            }
+        }
+
+        // splitting a purchase saves at most the rounding of one part
+        #[test]
+        fn splitting_saves_at_most_one_unit(
+            first in 0u64..=u64::from(u32::MAX),
+            second in 0u64..=u64::from(u32::MAX),
+            price in 0u64..=u64::from(u32::MAX),
+            decimals_a in 0u8..=9,
+            decimals_b in 0u8..=9,
+            price_decimals in 0u8..=9,
+        ) {
+            let amount_b = |amount_a| {
+                calculate_amount_b(amount_a, price, decimals_a, decimals_b, price_decimals)
+            };
+            if let (Some(whole), Some(first), Some(second)) =
+                (amount_b(first + second), amount_b(first), amount_b(second))
+            {
+                prop_assert!(whole >= first + second);
+                prop_assert!(whole - (first + second) <= 1);
+            }
+        }
+
+        #[test]
+        fn unit_price_keeps_the_amount(amount_a: u64, decimals in 0u8..=9, price_decimals in 0u8..=9) {
+            let price = 10u64.pow(price_decimals.into());
+            prop_assert_eq!(
+                calculate_amount_b(amount_a, price, decimals, decimals, price_decimals),
+                Some(amount_a)
+            );
+        }
+
+        #[test]
+        fn any_decimals_fail_without_panicking(
+            amount_a: u64,
+            price: u64,
+            decimals_a: u8,
+            decimals_b: u8,
+            price_decimals: u8,
+        ) {
+            let _ = calculate_amount_b(amount_a, price, decimals_a, decimals_b, price_decimals);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use crate::errors::AuctionError;
+use crate::events::AuctionFinalized;
+use crate::instructions::{
+    calculate_amount_b, house_fee, is_native_mint, transfer_vault_and_close,
+};
+use crate::state::{Auction, AuctionHouse, UserStats};
+use crate::stats::open_stats;
+use crate::token_extensions::amount_with_fee;
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+// the first buyer of a dutch auction pays the current price and settles it
+#[derive(Accounts)]
+pub struct BuyNow<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    /// CHECK: Just need pubkey...
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+    // mutable to collect withheld transfer fees before closing accounts
+    #[account(mut)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    // mutable to take native SOL fees in lamports
+    #[account(
+        mut,
+        constraint = !auction_house.paused @ AuctionError::HousePaused,
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"auction", auction_house.key().as_ref(), seller.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = !auction.cancelled @ AuctionError::AuctionCancelled,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = auction,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    // written back before seller_stats, see `record_sale`
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + UserStats::INIT_SPACE,
+        seeds = [b"stats", auction_house.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub buyer_stats: Box<Account<'info, UserStats>>,
+    #[account(
+        mut,
+        seeds = [b"stats", auction_house.key().as_ref(), seller.key().as_ref()],
+        bump = seller_stats.bump,
+    )]
+    pub seller_stats: Box<Account<'info, UserStats>>,
+    // left out to pay native SOL straight from the buyer's wallet
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint_a,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_mint_a_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    // this and the treasury are left out in native SOL auctions, which pay
+    // the seller and the house in lamports
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint_b,
+        associated_token::authority = seller,
+    )]
+    pub seller_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // fees are kept by the house, not the admin, so changing admins doesn't
+    // redirect them
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        seeds = [b"treasury", auction_house.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+        token::mint = mint_b,
+        token::authority = auction_house,
+        token::token_program = token_program,
+    )]
+    pub treasury: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> BuyNow<'info> {
+    // `max_price` protects the buyer from paying more than they saw
+    pub fn buy_now(&mut self, max_price: u64, bumps: &BuyNowBumps) -> Result<()> {
+        let now = self.auction.now()?;
+        require!(now < self.auction.end, AuctionError::AuctionEnded);
+
+        let price = self
+            .auction
+            .dutch_price(now)
+            .ok_or(AuctionError::WrongAuctionKind)?;
+        require!(price <= max_price, AuctionError::PriceTooLow);
+
+        self.auction.bidder = Some(self.buyer.key());
+        self.auction.highest_price = price;
+
+        let (total, house_fee) = self.pay_seller_and_house(price)?;
+        self.withdraw_and_close_vault()?;
+        self.record_sale(total, bumps)?;
+
+        emit!(AuctionFinalized {
+            auction: self.auction.key(),
+            seller: self.seller.key(),
+            winner: Some(self.buyer.key()),
+            price,
+            total,
+            house_fee,
+            royalties: 0,
+            seller_proceeds: total - house_fee,
+        });
+        Ok(())
+    }
+
+    // like `Finalize::record_sale`, a seller buying their own lot is counted
+    // on seller_stats alone
+    fn record_sale(&mut self, amount: u64, bumps: &BuyNowBumps) -> Result<()> {
+        let mint = self.mint_b.key();
+        self.seller_stats.record_completed();
+        self.seller_stats.add_volume(&mint, amount);
+        let buyer_stats = if self.buyer.key() == self.seller.key() {
+            &mut self.seller_stats
+        } else {
+            open_stats(
+                &mut self.buyer_stats,
+                self.buyer.key,
+                &self.auction_house.key(),
+                bumps.buyer_stats,
+                &mint,
+                &self.buyer,
+                &self.system_program,
+            )?;
+            &mut self.buyer_stats
+        };
+        buyer_stats.record_won();
+        buyer_stats.add_volume(&mint, amount);
+        Ok(())
+    }
+
+    // returns the total paid and the part of it taken as the house fee
+    fn pay_seller_and_house(&mut self, price: u64) -> Result<(u64, u64)> {
+        let amount = calculate_amount_b(
+            self.vault.amount,
+            price,
+            self.mint_a.decimals,
+            self.mint_b.decimals,
+            self.auction.decimal,
+        )
+        .ok_or(AuctionError::ArithematicOverflow)?;
+        let house_fee =
+            house_fee(amount, self.auction_house.fee).ok_or(AuctionError::ArithematicOverflow)?;
+
+        // native SOL is paid in lamports, like `Finalize::pay_out_lamports`
+        let (seller, house) = match (
+            &self.buyer_mint_b_ata,
+            &self.seller_mint_b_ata,
+            &self.treasury,
+        ) {
+            (Some(_), Some(seller_mint_b_ata), Some(treasury)) => (
+                seller_mint_b_ata.to_account_info(),
+                treasury.to_account_info(),
+            ),
+            (Some(_), _, _) => return err!(AuctionError::MissingTokenAccount),
+            (None, _, _) => (
+                self.seller.to_account_info(),
+                self.auction_house.to_account_info(),
+            ),
+        };
+        self.transfer_from_buyer(seller, amount - house_fee)?;
+        self.transfer_from_buyer(house, house_fee)?;
+        Ok((amount, house_fee))
+    }
+
+    fn transfer_from_buyer(&mut self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let Some(buyer_mint_b_ata) = &self.buyer_mint_b_ata else {
+            require!(
+                is_native_mint(&self.mint_b.key()),
+                AuctionError::MissingTokenAccount
+            );
+            let transfer_accounts = Transfer {
+                from: self.buyer.to_account_info(),
+                to,
+            };
+            return transfer(
+                CpiContext::new(self.system_program.to_account_info(), transfer_accounts),
+                amount,
+            );
+        };
+
+        let transfer_accounts = TransferChecked {
+            from: buyer_mint_b_ata.to_account_info(),
+            to,
+            mint: self.mint_b.to_account_info(),
+            authority: self.buyer.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), transfer_accounts);
+
+        // the buyer pays any transfer fee, the seller and house get `amount`
+        transfer_checked(
+            cpi_ctx,
+            amount_with_fee(&self.mint_b, amount)?,
+            self.mint_b.decimals,
+        )
+    }
+
+    fn withdraw_and_close_vault(&mut self) -> Result<()> {
+        let seeds = &[
+            b"auction",
+            self.auction_house.to_account_info().key.as_ref(),
+            self.seller.to_account_info().key.as_ref(),
+            self.mint_a.to_account_info().key.as_ref(),
+            self.mint_b.to_account_info().key.as_ref(),
+            &self.auction.seed.to_le_bytes(),
+            &[self.auction.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        transfer_vault_and_close(
+            &self.vault,
+            &self.mint_a,
+            self.buyer_mint_a_ata.to_account_info(),
+            self.auction.to_account_info(),
+            self.seller.to_account_info(),
+            self.token_program.to_account_info(),
+            signer_seeds,
+        )
+    }
+}
@@ -1,12 +1,20 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{close_account, CloseAccount},
-    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+    metadata::{mpl_token_metadata, Metadata},
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
 };
 
 use crate::errors::AuctionError;
-use crate::state::{Auction, AuctionHouse};
+use crate::events::AuctionCancelled;
+use crate::instructions::close_vault;
+use crate::nft::{is_programmable, read_metadata, required, ProgrammableTransfer};
+use crate::state::{Auction, AuctionHouse, UserStats};
+use crate::token_extensions::harvest_withheld;
 
 #[derive(Accounts)]
 pub struct Cancel<'info> {
@@ -22,10 +30,13 @@ pub struct Cancel<'info> {
     #[account(
         mut,
         close = seller,
-        seeds = [b"auction", auction_house.key().as_ref(), seller.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref()],
+        seeds = [b"auction", auction_house.key().as_ref(), seller.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
         bump = auction.bump,
+        constraint = !auction.cancelled @ AuctionError::AuctionCancelled,
     )]
     pub auction: Box<Account<'info, Auction>>,
+    // mutable to collect withheld transfer fees before closing accounts
+    #[account(mut)]
     pub mint_a: Box<InterfaceAccount<'info, Mint>>,
     pub mint_b: Box<InterfaceAccount<'info, Mint>>,
     #[account(
@@ -40,6 +51,38 @@ pub struct Cancel<'info> {
         associated_token::authority = auction,
     )]
     pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"stats", auction_house.key().as_ref(), seller.key().as_ref()],
+        bump = seller_stats.bump,
+    )]
+    pub seller_stats: Box<Account<'info, UserStats>>,
+    /// CHECK: Metaplex metadata of mint_a, empty for plain tokens.
+    #[account(
+        mut,
+        seeds = [b"metadata", mpl_token_metadata::ID.as_ref(), mint_a.key().as_ref()],
+        seeds::program = mpl_token_metadata::ID,
+        bump,
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    // the accounts below are only needed when mint_a is a programmable NFT.
+    // The token metadata program checks them.
+    /// CHECK: Master edition of mint_a.
+    pub edition: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of the vault.
+    #[account(mut)]
+    pub vault_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of seller_mint_a_ata.
+    #[account(mut)]
+    pub seller_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Rule set of the NFT, if it has one.
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token auth rules program, if the NFT has a rule set.
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Instructions sysvar.
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
@@ -47,14 +90,23 @@ pub struct Cancel<'info> {
 
 impl<'info> Cancel<'info> {
     pub fn cancel(&mut self) -> Result<()> {
-        let current_slot = Clock::get()?.slot;
+        let now = self.auction.now()?;
 
         require!(
-            current_slot >= self.auction.end && self.auction.bidder.is_none(),
+            now >= self.auction.settles_at() && !self.auction.has_bids(),
             AuctionError::NotEligibleToWithdraw
         );
 
-        self.withdraw_and_close_vault()
+        self.withdraw_and_close_vault()?;
+
+        self.seller_stats.record_cancelled();
+        emit!(AuctionCancelled {
+            auction: self.auction.key(),
+            seller: self.seller.key(),
+            early: false,
+            penalty: 0,
+        });
+        Ok(())
     }
 
     fn withdraw_and_close_vault(&mut self) -> Result<()> {
@@ -64,10 +116,30 @@ impl<'info> Cancel<'info> {
             self.seller.to_account_info().key.as_ref(),
             self.mint_a.to_account_info().key.as_ref(),
             self.mint_b.to_account_info().key.as_ref(),
+            &self.auction.seed.to_le_bytes(),
             &[self.auction.bump],
         ];
         let signer_seeds = &[&seeds[..]];
 
+        if is_programmable(read_metadata(&self.metadata)?.as_ref()) {
+            self.programmable_transfer()?.transfer(
+                &self.vault.to_account_info(),
+                &self.auction.to_account_info(),
+                &self.seller_mint_a_ata.to_account_info(),
+                &self.seller,
+                self.vault.amount,
+                signer_seeds,
+            )?;
+            return close_vault(
+                &self.vault,
+                &self.mint_a,
+                self.auction.to_account_info(),
+                self.seller.to_account_info(),
+                self.token_program.to_account_info(),
+                signer_seeds,
+            );
+        }
+
         let cpi_program = self.token_program.to_account_info();
         let transfer_accounts = TransferChecked {
             from: self.vault.to_account_info(),
@@ -79,6 +151,12 @@ impl<'info> Cancel<'info> {
 
         transfer_checked(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
 
+        harvest_withheld(
+            self.token_program.to_account_info(),
+            self.mint_a.to_account_info(),
+            self.vault.to_account_info(),
+        )?;
+
         let accounts = CloseAccount {
             account: self.vault.to_account_info(),
             destination: self.seller.to_account_info(),
@@ -95,4 +173,21 @@ impl<'info> Cancel<'info> {
 
         Ok(())
     }
+    fn programmable_transfer(&self) -> Result<ProgrammableTransfer<'_, 'info>> {
+        Ok(ProgrammableTransfer {
+            token_metadata_program: required(&self.token_metadata_program)?,
+            mint: self.mint_a.as_ref().as_ref(),
+            metadata: &self.metadata,
+            edition: required(&self.edition)?,
+            token_record: required(&self.vault_token_record)?,
+            destination_token_record: required(&self.seller_token_record)?,
+            authorization_rules_program: self.authorization_rules_program.as_deref(),
+            authorization_rules: self.authorization_rules.as_deref(),
+            sysvar_instructions: required(&self.sysvar_instructions)?,
+            payer: &self.seller,
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+            associated_token_program: &self.associated_token_program,
+        })
+    }
 }
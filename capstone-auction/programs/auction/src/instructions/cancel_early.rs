@@ -0,0 +1,265 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::{mpl_token_metadata, Metadata},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::errors::AuctionError;
+use crate::events::{AuctionCancelled, CancelledAuctionClosed};
+use crate::instructions::{
+    calculate_amount_b, close_vault, deposit_into_escrow, house_fee, transfer_vault_and_close,
+};
+use crate::nft::{is_programmable, read_metadata, required, ProgrammableTransfer};
+use crate::state::{Auction, AuctionHouse, BidState, UserStats};
+
+// seller aborts a live auction. The auction is marked cancelled, which lets
+// every bidder `withdraw` their escrow, including the highest bidder. Without
+// any bids left the auction is closed right away, otherwise the seller closes
+// it with `close_cancelled` once the last bidder withdrew.
+#[derive(Accounts)]
+pub struct CancelEarly<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    #[account(
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    #[account(
+        mut,
+        seeds = [b"auction", auction_house.key().as_ref(), seller.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = !auction.cancelled @ AuctionError::AuctionCancelled,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+    // mutable to collect withheld transfer fees before closing accounts
+    #[account(mut)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = seller,
+    )]
+    pub seller_mint_a_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = auction,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"stats", auction_house.key().as_ref(), seller.key().as_ref()],
+        bump = seller_stats.bump,
+    )]
+    pub seller_stats: Box<Account<'info, UserStats>>,
+    /// CHECK: Metaplex metadata of mint_a, empty for plain tokens.
+    #[account(
+        mut,
+        seeds = [b"metadata", mpl_token_metadata::ID.as_ref(), mint_a.key().as_ref()],
+        seeds::program = mpl_token_metadata::ID,
+        bump,
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    // the accounts below are only needed when mint_a is a programmable NFT.
+    // The token metadata program checks them.
+    /// CHECK: Master edition of mint_a.
+    pub edition: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of the vault.
+    #[account(mut)]
+    pub vault_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of seller_mint_a_ata.
+    #[account(mut)]
+    pub seller_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Rule set of the NFT, if it has one.
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token auth rules program, if the NFT has a rule set.
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Instructions sysvar.
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    // the accounts below are only needed when the house charges a cancellation
+    // penalty and there is a highest bidder to pay it to. `seller_mint_b_ata`
+    // is left out to pay a native SOL penalty in lamports.
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = seller,
+    )]
+    pub seller_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        constraint = highest_bid_state.auction == auction.key(),
+        constraint = auction.bidder == Some(highest_bid_state.bidder),
+    )]
+    pub highest_bid_state: Option<Box<Account<'info, BidState>>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = highest_bid_state,
+    )]
+    pub highest_bidder_escrow: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CancelEarly<'info> {
+    pub fn cancel_early(&mut self) -> Result<()> {
+        // sealed bids are out of the bidders' hands once the reveal starts
+        let now = self.auction.now()?;
+        require!(now < self.auction.end, AuctionError::AuctionEnded);
+
+        let penalty = self.pay_cancellation_penalty()?;
+        self.withdraw_and_close_vault()?;
+        self.auction.cancelled = true;
+
+        self.seller_stats.record_cancelled();
+        emit!(AuctionCancelled {
+            auction: self.auction.key(),
+            seller: self.seller.key(),
+            early: true,
+            penalty,
+        });
+
+        if self.auction.open_bids == 0 {
+            return self.auction.close(self.seller.to_account_info());
+        }
+        Ok(())
+    }
+
+    // the penalty is a share of the highest bid, paid into the highest bidder's
+    // escrow so it is returned together with the bid on `withdraw`.
+    fn pay_cancellation_penalty(&mut self) -> Result<u64> {
+        if self.auction.bidder.is_none() || self.auction_house.cancel_penalty_bps == 0 {
+            return Ok(0);
+        }
+
+        let highest_bid = calculate_amount_b(
+            self.vault.amount,
+            self.auction.highest_price,
+            self.mint_a.decimals,
+            self.mint_b.decimals,
+            self.auction.decimal,
+        )
+        .ok_or(AuctionError::ArithematicOverflow)?;
+        let penalty = house_fee(highest_bid, self.auction_house.cancel_penalty_bps)
+            .ok_or(AuctionError::ArithematicOverflow)?;
+        if penalty == 0 {
+            return Ok(0);
+        }
+
+        let (Some(_), Some(highest_bidder_escrow)) =
+            (&self.highest_bid_state, &self.highest_bidder_escrow)
+        else {
+            return err!(AuctionError::MissingPenaltyAccounts);
+        };
+
+        // the seller pays any transfer fee, the escrow gets the whole penalty
+        deposit_into_escrow(
+            &self.seller,
+            self.seller_mint_b_ata.as_deref(),
+            highest_bidder_escrow,
+            &self.mint_b,
+            penalty,
+            &self.token_program,
+            &self.system_program,
+        )?;
+        Ok(penalty)
+    }
+
+    fn withdraw_and_close_vault(&mut self) -> Result<()> {
+        let seeds = &[
+            b"auction",
+            self.auction_house.to_account_info().key.as_ref(),
+            self.seller.to_account_info().key.as_ref(),
+            self.mint_a.to_account_info().key.as_ref(),
+            self.mint_b.to_account_info().key.as_ref(),
+            &self.auction.seed.to_le_bytes(),
+            &[self.auction.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // pNFTs are frozen in the vault, only the token metadata program moves them
+        if is_programmable(read_metadata(&self.metadata)?.as_ref()) {
+            self.programmable_transfer()?.transfer(
+                &self.vault.to_account_info(),
+                &self.auction.to_account_info(),
+                &self.seller_mint_a_ata.to_account_info(),
+                &self.seller,
+                self.vault.amount,
+                signer_seeds,
+            )?;
+            return close_vault(
+                &self.vault,
+                &self.mint_a,
+                self.auction.to_account_info(),
+                self.seller.to_account_info(),
+                self.token_program.to_account_info(),
+                signer_seeds,
+            );
+        }
+
+        transfer_vault_and_close(
+            &self.vault,
+            &self.mint_a,
+            self.seller_mint_a_ata.to_account_info(),
+            self.auction.to_account_info(),
+            self.seller.to_account_info(),
+            self.token_program.to_account_info(),
+            signer_seeds,
+        )
+    }
+
+    fn programmable_transfer(&self) -> Result<ProgrammableTransfer<'_, 'info>> {
+        Ok(ProgrammableTransfer {
+            token_metadata_program: required(&self.token_metadata_program)?,
+            mint: self.mint_a.as_ref().as_ref(),
+            metadata: &self.metadata,
+            edition: required(&self.edition)?,
+            token_record: required(&self.vault_token_record)?,
+            destination_token_record: required(&self.seller_token_record)?,
+            authorization_rules_program: self.authorization_rules_program.as_deref(),
+            authorization_rules: self.authorization_rules.as_deref(),
+            sysvar_instructions: required(&self.sysvar_instructions)?,
+            payer: &self.seller,
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+            associated_token_program: &self.associated_token_program,
+        })
+    }
+}
+
+// seller takes back the rent of a cancelled auction once every bidder withdrew
+#[derive(Accounts)]
+pub struct CloseCancelled<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    #[account(
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"auction", auction_house.key().as_ref(), seller.key().as_ref(), auction.mint_a.as_ref(), auction.mint_b.as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = auction.cancelled @ AuctionError::AuctionNotCancelled,
+        constraint = auction.open_bids == 0 @ AuctionError::BidsOutstanding,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+}
+
+impl<'info> CloseCancelled<'info> {
+    pub fn close_cancelled(&mut self) -> Result<()> {
+        emit!(CancelledAuctionClosed {
+            auction: self.auction.key(),
+            seller: self.seller.key(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::{mpl_token_metadata, Metadata},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::errors::AuctionError;
+use crate::events::{AuctionCancelled, KeeperPaid};
+use crate::instructions::{close_vault, pay_keeper_tip, transfer_vault_and_close};
+use crate::nft::{is_programmable, read_metadata, required, ProgrammableTransfer};
+use crate::state::{Auction, AuctionHouse, UserStats};
+
+// anyone can close an auction that ended without bids, the lot goes back to
+// the seller. Lets keepers clean up after sellers that never call `cancel`.
+#[derive(Accounts)]
+pub struct CancelExpired<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Checked by the auction seeds.
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+    #[account(
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    // pays lamport keeper tips
+    #[account(
+        mut,
+        seeds = [b"keeper_pool", auction_house.key().as_ref()],
+        bump,
+    )]
+    pub keeper_pool: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"auction", auction_house.key().as_ref(), seller.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = !auction.cancelled @ AuctionError::AuctionCancelled,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+    // mutable to collect withheld transfer fees before closing accounts
+    #[account(mut)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = seller,
+    )]
+    pub seller_mint_a_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = auction,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"stats", auction_house.key().as_ref(), seller.key().as_ref()],
+        bump = seller_stats.bump,
+    )]
+    pub seller_stats: Box<Account<'info, UserStats>>,
+    /// CHECK: Metaplex metadata of mint_a, empty for plain tokens.
+    #[account(
+        mut,
+        seeds = [b"metadata", mpl_token_metadata::ID.as_ref(), mint_a.key().as_ref()],
+        seeds::program = mpl_token_metadata::ID,
+        bump,
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    // the accounts below are only needed when mint_a is a programmable NFT.
+    // The token metadata program checks them.
+    /// CHECK: Master edition of mint_a.
+    pub edition: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of the vault.
+    #[account(mut)]
+    pub vault_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of seller_mint_a_ata.
+    #[account(mut)]
+    pub seller_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Rule set of the NFT, if it has one.
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token auth rules program, if the NFT has a rule set.
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Instructions sysvar.
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CancelExpired<'info> {
+    pub fn cancel_expired(&mut self, bumps: &CancelExpiredBumps) -> Result<()> {
+        let now = self.auction.now()?;
+
+        require!(
+            now >= self.auction.settles_at() && !self.auction.has_bids(),
+            AuctionError::NotEligibleToWithdraw
+        );
+
+        let seeds = &[
+            b"auction",
+            self.auction_house.to_account_info().key.as_ref(),
+            self.seller.to_account_info().key.as_ref(),
+            self.mint_a.to_account_info().key.as_ref(),
+            self.mint_b.to_account_info().key.as_ref(),
+            &self.auction.seed.to_le_bytes(),
+            &[self.auction.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        self.return_lot(signer_seeds)?;
+
+        self.seller_stats.record_cancelled();
+        emit!(AuctionCancelled {
+            auction: self.auction.key(),
+            seller: self.seller.key(),
+            early: false,
+            penalty: 0,
+        });
+
+        let lamports = pay_keeper_tip(
+            &self.auction_house,
+            &self.keeper_pool,
+            bumps.keeper_pool,
+            &self.payer,
+            self.seller.key,
+            &self.system_program,
+        )?;
+        if lamports > 0 {
+            emit!(KeeperPaid {
+                auction: self.auction.key(),
+                keeper: self.payer.key(),
+                lamports,
+                tokens: 0,
+            });
+        }
+        Ok(())
+    }
+
+    // the lot goes back to the seller, the vault's rent too
+    fn return_lot(&self, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        // pNFTs are frozen in the vault, only the token metadata program moves them
+        if is_programmable(read_metadata(&self.metadata)?.as_ref()) {
+            self.programmable_transfer()?.transfer(
+                &self.vault.to_account_info(),
+                &self.auction.to_account_info(),
+                &self.seller_mint_a_ata.to_account_info(),
+                &self.seller,
+                self.vault.amount,
+                signer_seeds,
+            )?;
+            return close_vault(
+                &self.vault,
+                &self.mint_a,
+                self.auction.to_account_info(),
+                self.seller.to_account_info(),
+                self.token_program.to_account_info(),
+                signer_seeds,
+            );
+        }
+
+        transfer_vault_and_close(
+            &self.vault,
+            &self.mint_a,
+            self.seller_mint_a_ata.to_account_info(),
+            self.auction.to_account_info(),
+            self.seller.to_account_info(),
+            self.token_program.to_account_info(),
+            signer_seeds,
+        )
+    }
+
+    fn programmable_transfer(&self) -> Result<ProgrammableTransfer<'_, 'info>> {
+        Ok(ProgrammableTransfer {
+            token_metadata_program: required(&self.token_metadata_program)?,
+            mint: self.mint_a.as_ref().as_ref(),
+            metadata: &self.metadata,
+            edition: required(&self.edition)?,
+            token_record: required(&self.vault_token_record)?,
+            destination_token_record: required(&self.seller_token_record)?,
+            authorization_rules_program: self.authorization_rules_program.as_deref(),
+            authorization_rules: self.authorization_rules.as_deref(),
+            sysvar_instructions: required(&self.sysvar_instructions)?,
+            payer: &self.payer,
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+            associated_token_program: &self.associated_token_program,
+        })
+    }
+}
@@ -1,10 +1,19 @@
 use crate::errors::AuctionError;
-use crate::state::{Auction, AuctionHouse, BidState};
+use crate::events::{AuctionFinalized, KeeperPaid};
+use crate::instructions::{calculate_amount_b, is_native_mint};
+use crate::nft::{is_programmable, read_metadata, required, royalties, ProgrammableTransfer};
+use crate::state::{Auction, AuctionHouse, BidState, KeeperReward, UserStats};
+use crate::token_extensions::harvest_withheld;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program::{transfer, Transfer};
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{close_account, CloseAccount},
-    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    metadata::{mpl_token_metadata, Metadata},
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
 };
 
 #[derive(Accounts)]
@@ -17,21 +26,32 @@ pub struct Finalize<'info> {
     /// CHECK: Just need pubkey...
     #[account(mut)]
     pub bidder: AccountInfo<'info>,
-    /// CHECK: Just need pubkey...
-    pub admin: AccountInfo<'info>,
+    // mutable to collect withheld transfer fees before closing the vault and escrow
+    #[account(mut)]
     pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
     pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    // mutable to take native SOL fees in lamports
     #[account(
+        mut,
         seeds = [b"house", auction_house.name.as_bytes()],
         bump = auction_house.bump,
     )]
     pub auction_house: Box<Account<'info, AuctionHouse>>,
+    // pays lamport keeper tips
+    #[account(
+        mut,
+        seeds = [b"keeper_pool", auction_house.key().as_ref()],
+        bump,
+    )]
+    pub keeper_pool: SystemAccount<'info>,
     #[account(
         mut,
         close = seller,
-        seeds = [b"auction", auction_house.key().as_ref(), seller.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref()],
+        seeds = [b"auction", auction_house.key().as_ref(), seller.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
         bump = auction.bump,
         constraint = auction.bidder == Some(bidder.key()),
+        constraint = !auction.cancelled @ AuctionError::AuctionCancelled,
     )]
     pub auction: Box<Account<'info, Auction>>,
     #[account(
@@ -54,6 +74,19 @@ pub struct Finalize<'info> {
         associated_token::authority = auction,
     )]
     pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    // written back before seller_stats, see `record_sale`
+    #[account(
+        mut,
+        seeds = [b"stats", auction_house.key().as_ref(), bidder.key().as_ref()],
+        bump = bidder_stats.bump,
+    )]
+    pub bidder_stats: Box<Account<'info, UserStats>>,
+    #[account(
+        mut,
+        seeds = [b"stats", auction_house.key().as_ref(), seller.key().as_ref()],
+        bump = seller_stats.bump,
+    )]
+    pub seller_stats: Box<Account<'info, UserStats>>,
 
     /// CHECK: Must be token account (mint_a, bidder).
     #[account(
@@ -79,40 +112,198 @@ pub struct Finalize<'info> {
         bump,
     )]
     pub seller_mint_b_ata: AccountInfo<'info>,
-    /// CHECK: Must be token account (mint_b, admin).
+    // fees are kept by the house, not the admin, so changing admins doesn't
+    // redirect them. Left out in native SOL auctions, which pay the house in
+    // lamports.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"treasury", auction_house.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+        token::mint = mint_b,
+        token::authority = auction_house,
+        token::token_program = token_program,
+    )]
+    pub treasury: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // receives the keeper's share of the house fee, left out the share stays
+    // in the treasury. Native SOL auctions pay the keeper in lamports instead.
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::authority = payer,
+    )]
+    pub payer_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// CHECK: Must be token account (mint_a, seller).
+    #[account(
+        mut,
+        seeds = [
+            seller.key().as_ref(),
+            token_program.key().as_ref(),
+            mint_a.key().as_ref(),
+        ],
+        seeds::program = associated_token_program.key(),
+        bump,
+    )]
+    pub seller_mint_a_ata: AccountInfo<'info>,
+    /// CHECK: Must be token account (mint_b, bidder).
     #[account(
         mut,
         seeds = [
-            admin.key().as_ref(),
+            bidder.key().as_ref(),
             token_program.key().as_ref(),
             mint_b.key().as_ref(),
         ],
         seeds::program = associated_token_program.key(),
         bump,
     )]
-    pub admin_mint_b_ata: AccountInfo<'info>,
+    pub bidder_mint_b_ata: AccountInfo<'info>,
+    /// CHECK: Metaplex metadata of mint_a, empty for plain tokens.
+    #[account(
+        mut,
+        seeds = [b"metadata", mpl_token_metadata::ID.as_ref(), mint_a.key().as_ref()],
+        seeds::program = mpl_token_metadata::ID,
+        bump,
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    // the accounts below are only needed when mint_a is a programmable NFT.
+    // The token metadata program checks them.
+    /// CHECK: Master edition of mint_a.
+    pub edition: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of the vault.
+    #[account(mut)]
+    pub vault_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of the mint_a ATA the NFT goes to, the winner's or
+    /// the seller's when the reserve isn't met.
+    #[account(mut)]
+    pub destination_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Rule set of the NFT, if it has one.
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token auth rules program, if the NFT has a rule set.
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Instructions sysvar.
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-// there is a winner
+// there is a highest bidder.
+// When mint_a is a single Metaplex NFT its creators get their royalties out of
+// the winning bid. The remaining accounts are then each creator with a share,
+// in metadata order, followed by their mint_b ATA unless the auction is in
+// native SOL: [creator, creator_mint_b_ata].
 impl<'info> Finalize<'info> {
-    pub fn finalize(&mut self) -> Result<()> {
-        // msg!("bidder.key(): {:?}", self.bidder.key());
-        // msg!("bid_state.bidder: {:?}", self.bid_state.bidder);
-        // msg!("bid_state = {:?}", self.bid_state);
-        self.winner_withdraw_and_close_vault()?;
-        self.seller_withdraw_and_close_escrow()?; // house fee transfered covered here as well
-        Ok(())
-    }
-
-    fn winner_withdraw_and_close_vault(&mut self) -> Result<()> {
-        let current_slot = Clock::get()?.slot;
+    pub fn finalize(
+        &mut self,
+        creators: &'info [AccountInfo<'info>],
+        bumps: &FinalizeBumps,
+    ) -> Result<()> {
+        let now = self.auction.now()?;
         require!(
-            current_slot >= self.auction.end,
+            now >= self.auction.finalizes_at(),
             AuctionError::NotEligibleToWithdraw
         );
+        // unrevealed bids could otherwise withdraw without their penalty
+        require!(
+            self.auction.unrevealed_bids() == 0,
+            AuctionError::UnrevealedBids
+        );
+
+        let metadata = read_metadata(&self.metadata)?;
+        let programmable = is_programmable(metadata.as_ref());
+
+        let mut keeper_tokens = 0;
+        if self.auction.reserve_met(now) {
+            // escrow can hold more than the winner pays, e.g. sealed or proxy bids
+            let amount_due = calculate_amount_b(
+                self.vault.amount,
+                self.auction.settlement_price(),
+                self.mint_a.decimals,
+                self.mint_b.decimals,
+                self.auction.decimal,
+            )
+            .ok_or(AuctionError::ArithematicOverflow)?;
+            let royalties = match &metadata {
+                Some(metadata) if self.sells_nft() => royalties(metadata, amount_due)?,
+                _ => vec![],
+            };
+            let royalty_total = royalties.iter().map(|(_, amount)| amount).sum();
+
+            self.winner_withdraw_and_close_vault(programmable)?;
+            let payouts = self.creator_payouts(&royalties, creators)?;
+            let (house_fee, keeper_share) =
+                self.seller_withdraw_and_close_escrow(amount_due, royalty_total, payouts)?; // house fee transfered covered here as well
+            keeper_tokens = keeper_share;
+            self.record_sale(amount_due);
+
+            emit!(AuctionFinalized {
+                auction: self.auction.key(),
+                seller: self.seller.key(),
+                winner: Some(self.bidder.key()),
+                price: self.auction.settlement_price(),
+                total: amount_due,
+                house_fee,
+                royalties: royalty_total,
+                seller_proceeds: amount_due - house_fee - royalty_total,
+            });
+        } else {
+            // reserve not met: the lot goes back to the seller and the bid is refunded
+            self.withdraw_and_close_vault(
+                self.seller.to_account_info(),
+                self.seller_mint_a_ata.to_account_info(),
+                programmable,
+            )?;
+            self.bidder_refund_and_close_escrow()?;
+
+            emit!(AuctionFinalized {
+                auction: self.auction.key(),
+                seller: self.seller.key(),
+                winner: None,
+                price: 0,
+                total: 0,
+                house_fee: 0,
+                royalties: 0,
+                seller_proceeds: 0,
+            });
+        }
+
+        let keeper_lamports = pay_keeper_tip(
+            &self.auction_house,
+            &self.keeper_pool,
+            bumps.keeper_pool,
+            &self.payer,
+            self.seller.key,
+            &self.system_program,
+        )?;
+        if keeper_lamports > 0 || keeper_tokens > 0 {
+            emit!(KeeperPaid {
+                auction: self.auction.key(),
+                keeper: self.payer.key(),
+                lamports: keeper_lamports,
+                tokens: keeper_tokens,
+            });
+        }
+        Ok(())
+    }
+
+    // a seller who won their own auction has one stats account under both
+    // names, the updates all go to seller_stats since it is written last
+    fn record_sale(&mut self, amount: u64) {
+        let mint = self.mint_b.key();
+        self.seller_stats.record_completed();
+        self.seller_stats.add_volume(&mint, amount);
+        let winner_stats = if self.bidder.key() == self.seller.key() {
+            &mut self.seller_stats
+        } else {
+            &mut self.bidder_stats
+        };
+        winner_stats.record_won();
+        winner_stats.add_volume(&mint, amount);
+    }
+
+    fn winner_withdraw_and_close_vault(&mut self, programmable: bool) -> Result<()> {
         require!(
             self.bid_state.bidder == self.bidder.key(),
             AuctionError::NotEligibleToWithdraw
@@ -126,10 +317,23 @@ impl<'info> Finalize<'info> {
             AuctionError::NotEligibleToWithdraw
         );
 
-        self.initialize_token_account_if_needed(
-            self.mint_a.to_account_info(),
+        self.withdraw_and_close_vault(
             self.bidder.to_account_info(),
             self.bidder_mint_a_ata.to_account_info(),
+            programmable,
+        )
+    }
+
+    fn withdraw_and_close_vault(
+        &mut self,
+        owner: AccountInfo<'info>,
+        owner_mint_a_ata: AccountInfo<'info>,
+        programmable: bool,
+    ) -> Result<()> {
+        self.initialize_token_account_if_needed(
+            self.mint_a.to_account_info(),
+            owner.clone(),
+            owner_mint_a_ata.clone(),
         )?;
 
         let seeds = &[
@@ -138,49 +342,124 @@ impl<'info> Finalize<'info> {
             self.seller.to_account_info().key.as_ref(),
             self.mint_a.to_account_info().key.as_ref(),
             self.mint_b.to_account_info().key.as_ref(),
+            &self.auction.seed.to_le_bytes(),
             &[self.auction.bump],
         ];
         let signer_seeds = &[&seeds[..]];
 
-        let transfer_accounts = TransferChecked {
-            from: self.vault.to_account_info(),
-            to: self.bidder_mint_a_ata.to_account_info(),
-            mint: self.mint_a.to_account_info(),
-            authority: self.auction.to_account_info(),
-        };
+        if programmable {
+            self.programmable_transfer()?.transfer(
+                &self.vault.to_account_info(),
+                &self.auction.to_account_info(),
+                &owner_mint_a_ata,
+                &owner,
+                self.vault.amount,
+                signer_seeds,
+            )?;
+            return close_vault(
+                &self.vault,
+                &self.mint_a,
+                self.auction.to_account_info(),
+                self.seller.to_account_info(),
+                self.token_program.to_account_info(),
+                signer_seeds,
+            );
+        }
 
-        let cpi_ctx = CpiContext::new_with_signer(
+        transfer_vault_and_close(
+            &self.vault,
+            &self.mint_a,
+            owner_mint_a_ata,
+            self.auction.to_account_info(),
+            self.seller.to_account_info(),
             self.token_program.to_account_info(),
-            transfer_accounts,
             signer_seeds,
-        );
+        )
+    }
 
-        // msg!("transfering to bidder ata a.");
-        transfer_checked(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+    fn programmable_transfer(&self) -> Result<ProgrammableTransfer<'_, 'info>> {
+        Ok(ProgrammableTransfer {
+            token_metadata_program: required(&self.token_metadata_program)?,
+            mint: self.mint_a.as_ref().as_ref(),
+            metadata: &self.metadata,
+            edition: required(&self.edition)?,
+            token_record: required(&self.vault_token_record)?,
+            destination_token_record: required(&self.destination_token_record)?,
+            authorization_rules_program: self.authorization_rules_program.as_deref(),
+            authorization_rules: self.authorization_rules.as_deref(),
+            sysvar_instructions: required(&self.sysvar_instructions)?,
+            payer: &self.payer,
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+            associated_token_program: &self.associated_token_program,
+        })
+    }
 
-        // close vault to refund rent exemption
-        let accounts = CloseAccount {
-            account: self.vault.to_account_info(),
-            destination: self.seller.to_account_info(),
-            authority: self.auction.to_account_info(),
-        };
+    // royalties are only due on the sale of a single NFT
+    fn sells_nft(&self) -> bool {
+        self.vault.amount == 1 && self.mint_a.decimals == 0 && self.mint_a.supply == 1
+    }
 
-        let ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            accounts,
-            signer_seeds,
+    // the accounts to pay each creator's royalty to, checked against the
+    // metadata's creators
+    fn creator_payouts(
+        &mut self,
+        royalties: &[(Pubkey, u64)],
+        creators: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<(AccountInfo<'info>, u64)>> {
+        let chunk = if is_native_mint(&self.mint_b.key()) {
+            1
+        } else {
+            2
+        };
+        require!(
+            creators.len() >= royalties.len() * chunk,
+            AuctionError::InvalidCreatorAccount
         );
 
-        close_account(ctx)?;
-        Ok(())
+        let mut payouts = Vec::with_capacity(royalties.len());
+        for (&(creator, amount), accounts) in royalties.iter().zip(creators.chunks(chunk)) {
+            require_keys_eq!(
+                accounts[0].key(),
+                creator,
+                AuctionError::InvalidCreatorAccount
+            );
+            if amount == 0 {
+                continue;
+            }
+            let Some(creator_mint_b_ata) = accounts.get(1) else {
+                payouts.push((accounts[0].clone(), amount));
+                continue;
+            };
+            require_keys_eq!(
+                creator_mint_b_ata.key(),
+                get_associated_token_address_with_program_id(
+                    &creator,
+                    &self.mint_b.key(),
+                    &self.token_program.key(),
+                ),
+                AuctionError::InvalidCreatorAccount
+            );
+            self.initialize_token_account_if_needed(
+                self.mint_b.to_account_info(),
+                accounts[0].clone(),
+                creator_mint_b_ata.clone(),
+            )?;
+            payouts.push((creator_mint_b_ata.clone(), amount));
+        }
+        Ok(payouts)
     }
 
-    fn seller_withdraw_and_close_escrow(&mut self) -> Result<()> {
-        let current_slot = Clock::get()?.slot;
-        require!(
-            (current_slot >= self.auction.end),
-            AuctionError::NotEligibleToWithdraw
-        );
+    // returns the house fee and the keeper's share of it
+    fn seller_withdraw_and_close_escrow(
+        &mut self,
+        amount_due: u64,
+        royalty_total: u64,
+        royalties: Vec<(AccountInfo<'info>, u64)>,
+    ) -> Result<(u64, u64)> {
+        if is_native_mint(&self.mint_b.key()) {
+            return self.pay_out_lamports(amount_due, royalty_total, royalties);
+        }
 
         self.initialize_token_account_if_needed(
             self.mint_b.to_account_info(),
@@ -188,57 +467,136 @@ impl<'info> Finalize<'info> {
             self.seller_mint_b_ata.to_account_info(),
         )?;
 
-        let seeds = &[
-            b"bid",
-            self.auction.to_account_info().key.as_ref(),
-            self.bidder.to_account_info().key.as_ref(),
-            &[self.bid_state.bump],
-        ];
-        let signer_seeds = &[&seeds[..]];
+        let house_fee = house_fee(amount_due, self.auction_house.fee)
+            .ok_or(AuctionError::ArithematicOverflow)?;
+        let refund = self
+            .bidder_escrow
+            .amount
+            .checked_sub(amount_due)
+            .ok_or(AuctionError::InsufficientCollateral)?;
 
-        // transfer mintB from bidder_escrow to seller
+        // transfer mintB from bidder_escrow to seller, less the creators' royalties
+        let amount = amount_due
+            .checked_sub(
+                house_fee
+                    .checked_add(royalty_total)
+                    .ok_or(AuctionError::ArithematicOverflow)?,
+            )
+            .ok_or(AuctionError::ArithematicOverflow)?;
+        self.transfer_from_escrow(self.seller_mint_b_ata.to_account_info(), amount)?;
+        for (creator_mint_b_ata, royalty) in royalties {
+            self.transfer_from_escrow(creator_mint_b_ata, royalty)?;
+        }
 
-        let transfer_accounts = TransferChecked {
-            from: self.bidder_escrow.to_account_info(),
-            to: self.seller_mint_b_ata.to_account_info(),
-            mint: self.mint_b.to_account_info(),
-            authority: self.bid_state.to_account_info(),
-        };
+        // transfer mintB from bidder_escrow to auction house, less the keeper's share
+        let keeper_share = self.keeper_share(house_fee)?;
+        let treasury = self
+            .treasury
+            .as_ref()
+            .ok_or(AuctionError::MissingTokenAccount)?
+            .to_account_info();
+        self.transfer_from_escrow(treasury, house_fee - keeper_share)?;
+        if let Some(payer_mint_b_ata) = &self.payer_mint_b_ata {
+            if keeper_share > 0 {
+                self.transfer_from_escrow(payer_mint_b_ata.to_account_info(), keeper_share)?;
+            }
+        }
 
-        let cpi_ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            transfer_accounts,
-            signer_seeds,
+        if refund > 0 {
+            self.initialize_token_account_if_needed(
+                self.mint_b.to_account_info(),
+                self.bidder.to_account_info(),
+                self.bidder_mint_b_ata.to_account_info(),
+            )?;
+            self.transfer_from_escrow(self.bidder_mint_b_ata.to_account_info(), refund)?;
+        }
+
+        self.close_escrow(self.bidder.to_account_info())?;
+        Ok((house_fee, keeper_share))
+    }
+
+    // native SOL auctions settle in lamports: the escrow is unwrapped into the
+    // bid state, which pays the seller, the house, the keeper and the creators.
+    // What is left goes back to the bidder when the bid state is closed.
+    // Royalties too small to make a creator's empty wallet rent exempt go to
+    // the seller.
+    fn pay_out_lamports(
+        &mut self,
+        amount_due: u64,
+        royalty_total: u64,
+        royalties: Vec<(AccountInfo<'info>, u64)>,
+    ) -> Result<(u64, u64)> {
+        let house_fee = house_fee(amount_due, self.auction_house.fee)
+            .ok_or(AuctionError::ArithematicOverflow)?;
+        let seller_proceeds = amount_due
+            .checked_sub(
+                house_fee
+                    .checked_add(royalty_total)
+                    .ok_or(AuctionError::ArithematicOverflow)?,
+            )
+            .ok_or(AuctionError::ArithematicOverflow)?;
+        require!(
+            self.bidder_escrow.amount >= amount_due,
+            AuctionError::InsufficientCollateral
         );
+        let keeper_share = self.keeper_share(house_fee)?;
 
-        let house_fee = self
-            .bidder_escrow
-            .amount
-            .checked_mul(u64::from(self.auction_house.fee))
-            .unwrap()
-            .checked_div(10_000)
-            .unwrap();
+        self.close_escrow(self.bid_state.to_account_info())?;
 
-        // msg!(&format!(
-        //     "bidder escrow: {}. house_fee={}. vault={}.",
-        //     self.bidder_escrow.amount, house_fee, self.vault.amount,
-        // ));
-        let amount = self.bidder_escrow.amount - house_fee;
+        let payouts = [
+            (
+                self.auction_house.to_account_info(),
+                house_fee - keeper_share,
+            ),
+            (self.payer.to_account_info(), keeper_share),
+        ];
+        pay_lamports(
+            &self.bid_state.to_account_info(),
+            payouts.into_iter().chain(royalties),
+            (&self.seller.to_account_info(), seller_proceeds),
+        )?;
+        Ok((house_fee, keeper_share))
+    }
 
-        // msg!("transfering to bidder");
-        transfer_checked(cpi_ctx, amount, self.mint_b.decimals)?;
+    fn keeper_share(&self, fee: u64) -> Result<u64> {
+        if self.payer_mint_b_ata.is_none() && !is_native_mint(&self.mint_b.key()) {
+            return Ok(0);
+        }
+        keeper_fee_share(&self.auction_house, fee, self.payer.key, self.seller.key)
+    }
+
+    fn bidder_refund_and_close_escrow(&mut self) -> Result<()> {
+        // closing a native escrow unwraps the bid straight to the bidder
+        if is_native_mint(&self.mint_b.key()) {
+            return self.close_escrow(self.bidder.to_account_info());
+        }
 
         self.initialize_token_account_if_needed(
             self.mint_b.to_account_info(),
-            self.admin.to_account_info(),
-            self.admin_mint_b_ata.to_account_info(),
+            self.bidder.to_account_info(),
+            self.bidder_mint_b_ata.to_account_info(),
         )?;
 
-        // transfer mintB from bidder_escrow to auction house
+        self.transfer_from_escrow(
+            self.bidder_mint_b_ata.to_account_info(),
+            self.bidder_escrow.amount,
+        )?;
+
+        self.close_escrow(self.bidder.to_account_info())
+    }
+
+    fn transfer_from_escrow(&mut self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let seeds = &[
+            b"bid",
+            self.auction.to_account_info().key.as_ref(),
+            self.bidder.to_account_info().key.as_ref(),
+            &[self.bid_state.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
 
         let transfer_accounts = TransferChecked {
             from: self.bidder_escrow.to_account_info(),
-            to: self.admin_mint_b_ata.to_account_info(),
+            to,
             mint: self.mint_b.to_account_info(),
             authority: self.bid_state.to_account_info(),
         };
@@ -249,12 +607,27 @@ impl<'info> Finalize<'info> {
             signer_seeds,
         );
 
-        // msg!("transfering to house");
-        transfer_checked(cpi_ctx, house_fee, self.mint_b.decimals)?;
+        transfer_checked(cpi_ctx, amount, self.mint_b.decimals)
+    }
+
+    fn close_escrow(&mut self, destination: AccountInfo<'info>) -> Result<()> {
+        let seeds = &[
+            b"bid",
+            self.auction.to_account_info().key.as_ref(),
+            self.bidder.to_account_info().key.as_ref(),
+            &[self.bid_state.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        harvest_withheld(
+            self.token_program.to_account_info(),
+            self.mint_b.to_account_info(),
+            self.bidder_escrow.to_account_info(),
+        )?;
 
         let accounts = CloseAccount {
             account: self.bidder_escrow.to_account_info(),
-            destination: self.bidder.to_account_info(),
+            destination,
             authority: self.bid_state.to_account_info(),
         };
 
@@ -264,8 +637,7 @@ impl<'info> Finalize<'info> {
             signer_seeds,
         );
 
-        close_account(ctx)?;
-        Ok(())
+        close_account(ctx)
     }
 
     fn initialize_token_account_if_needed(
@@ -291,3 +663,156 @@ impl<'info> Finalize<'info> {
         }
     }
 }
+
+// the helpers below are shared with `buy_now`, which settles a dutch auction
+// in a single instruction, and with the other settlement instructions.
+
+pub(crate) fn house_fee(amount: u64, fee: u16) -> Option<u64> {
+    amount.checked_mul(u64::from(fee))?.checked_div(10_000)
+}
+
+// moves lamports out of `from`, an account of this program. A payout that
+// would leave its recipient short of rent exemption would fail the whole
+// transaction, it is added to the last payout, `rest`, instead.
+pub(crate) fn pay_lamports<'info>(
+    from: &AccountInfo<'info>,
+    payouts: impl IntoIterator<Item = (AccountInfo<'info>, u64)>,
+    (rest_to, mut rest): (&AccountInfo<'info>, u64),
+) -> Result<()> {
+    let rent = Rent::get()?;
+    for (to, amount) in payouts {
+        let balance = to.lamports().saturating_add(amount);
+        if amount == 0 || !rent.is_exempt(balance, to.data_len()) {
+            rest = rest
+                .checked_add(amount)
+                .ok_or(AuctionError::ArithematicOverflow)?;
+            continue;
+        }
+        **from.try_borrow_mut_lamports()? -= amount;
+        **to.try_borrow_mut_lamports()? += amount;
+    }
+    **from.try_borrow_mut_lamports()? -= rest;
+    **rest_to.try_borrow_mut_lamports()? += rest;
+    Ok(())
+}
+
+// pays the house's fixed lamport tip to `keeper` out of the keeper pool, as
+// far as the pool holds SOL above its rent. An unfunded pool never blocks
+// settlement. Sellers settling their own auctions get nothing, or they could
+// list and cancel lots in a loop to empty the pool.
+pub(crate) fn pay_keeper_tip<'info>(
+    auction_house: &Account<'info, AuctionHouse>,
+    keeper_pool: &SystemAccount<'info>,
+    keeper_pool_bump: u8,
+    keeper: &AccountInfo<'info>,
+    seller: &Pubkey,
+    system_program: &Program<'info, System>,
+) -> Result<u64> {
+    let KeeperReward::Lamports { amount } = auction_house.keeper_reward else {
+        return Ok(0);
+    };
+    if keeper.key == seller {
+        return Ok(0);
+    }
+    let rent = Rent::get()?.minimum_balance(0);
+    let tip = amount.min(keeper_pool.lamports().saturating_sub(rent));
+    if tip == 0 {
+        return Ok(0);
+    }
+
+    let house = auction_house.key();
+    let seeds = &[b"keeper_pool", house.as_ref(), &[keeper_pool_bump]];
+    let transfer_accounts = Transfer {
+        from: keeper_pool.to_account_info(),
+        to: keeper.clone(),
+    };
+    transfer(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            transfer_accounts,
+            &[&seeds[..]],
+        ),
+        tip,
+    )?;
+    Ok(tip)
+}
+
+// the keeper's share of a house fee when the house pays one, nothing for a
+// seller settling their own auction
+pub(crate) fn keeper_fee_share(
+    auction_house: &AuctionHouse,
+    fee: u64,
+    keeper: &Pubkey,
+    seller: &Pubkey,
+) -> Result<u64> {
+    let KeeperReward::FeeShare { bps } = auction_house.keeper_reward else {
+        return Ok(0);
+    };
+    if keeper == seller {
+        return Ok(0);
+    }
+    Ok(house_fee(fee, bps).ok_or(AuctionError::ArithematicOverflow)?)
+}
+
+// move everything in the vault to `to` and close the vault, refunding the rent
+// to `rent_destination`.
+pub(crate) fn transfer_vault_and_close<'info>(
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint_a: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    auction: AccountInfo<'info>,
+    rent_destination: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let transfer_accounts = TransferChecked {
+        from: vault.to_account_info(),
+        to,
+        mint: mint_a.to_account_info(),
+        authority: auction.clone(),
+    };
+
+    let cpi_ctx =
+        CpiContext::new_with_signer(token_program.clone(), transfer_accounts, signer_seeds);
+
+    transfer_checked(cpi_ctx, vault.amount, mint_a.decimals)?;
+
+    close_vault(
+        vault,
+        mint_a,
+        auction,
+        rent_destination,
+        token_program,
+        signer_seeds,
+    )
+}
+
+// close the emptied vault to refund its rent to `rent_destination`. The token
+// metadata program may already have closed it when moving a pNFT out.
+pub(crate) fn close_vault<'info>(
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint_a: &InterfaceAccount<'info, Mint>,
+    auction: AccountInfo<'info>,
+    rent_destination: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if vault.to_account_info().lamports() == 0 {
+        return Ok(());
+    }
+
+    harvest_withheld(
+        token_program.clone(),
+        mint_a.to_account_info(),
+        vault.to_account_info(),
+    )?;
+    let accounts = CloseAccount {
+        account: vault.to_account_info(),
+        destination: rent_destination,
+        authority: auction,
+    };
+
+    let ctx = CpiContext::new_with_signer(token_program, accounts, signer_seeds);
+
+    close_account(ctx)
+}
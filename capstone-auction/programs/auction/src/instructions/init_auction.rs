@@ -1,28 +1,38 @@
-use anchor_lang::{prelude::*, solana_program::clock::Slot};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
 use anchor_spl::{
     associated_token::AssociatedToken,
+    metadata::{mpl_token_metadata, Metadata},
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::state::{Auction, AuctionHouse};
+use crate::errors::AuctionError;
+use crate::events::AuctionCreated;
+use crate::nft::{is_programmable, pays_royalties, read_metadata, required, ProgrammableTransfer};
+use crate::state::{Auction, AuctionHouse, AuctionKind, EndCondition, Gate, UserStats};
+use crate::stats::open_stats;
+use crate::token_extensions::{amount_with_fee, check_mint};
 
+// shared by `init_auction`, `init_dutch_auction`, `init_sealed_auction` and `init_batch_auction`
 #[derive(Accounts)]
-#[instruction(starting_price: u64,end: Slot, amount: u64, decimal: u8)]
+#[instruction(seed: u64)]
 pub struct InitAuction<'info> {
     #[account(mut)]
     pub seller: Signer<'info>,
     #[account(
+        constraint = !auction_house.paused @ AuctionError::HousePaused,
         seeds = [b"house", auction_house.name.as_bytes()],
         bump = auction_house.bump,
     )]
     pub auction_house: Box<Account<'info, AuctionHouse>>,
-    // for the same house, seller can create auctions identified by different mints.
+    // for the same house, seller can create auctions identified by different mints,
+    // and several auctions of the same mints under different seeds.
     // at the same time, seller can create auctions in different house when necessary.
     #[account(
         init,
         payer = seller,
         space = 8 + Auction::INIT_SPACE,
-        seeds = [b"auction", auction_house.key().as_ref(), seller.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref()],
+        seeds = [b"auction", auction_house.key().as_ref(), seller.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref(), seed.to_le_bytes().as_ref()],
         bump,
     )]
     pub auction: Box<Account<'info, Auction>>,
@@ -41,36 +51,301 @@ pub struct InitAuction<'info> {
         associated_token::authority = auction,
     )]
     pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8 + UserStats::INIT_SPACE,
+        seeds = [b"stats", auction_house.key().as_ref(), seller.key().as_ref()],
+        bump,
+    )]
+    pub seller_stats: Box<Account<'info, UserStats>>,
+    /// CHECK: Metaplex metadata of mint_a, empty for plain tokens.
+    #[account(
+        mut,
+        seeds = [b"metadata", mpl_token_metadata::ID.as_ref(), mint_a.key().as_ref()],
+        seeds::program = mpl_token_metadata::ID,
+        bump,
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    // the accounts below are only needed when mint_a is a programmable NFT.
+    // The token metadata program checks them.
+    /// CHECK: Master edition of mint_a.
+    pub edition: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of seller_mint_a_ata.
+    #[account(mut)]
+    pub seller_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token record of the vault.
+    #[account(mut)]
+    pub vault_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: Rule set of the NFT, if it has one.
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token auth rules program, if the NFT has a rule set.
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Instructions sysvar.
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
 }
 
 impl<'info> InitAuction<'info> {
+    #[allow(clippy::too_many_arguments)]
     pub fn init_auction(
         &mut self,
+        seed: u64,
+        starting_price: u64,
+        buy_now_price: u64,
+        end_condition: EndCondition,
+        end: u64,
+        amount: u64,
+        decimal: u8,
+        extension_window: u64,
+        extension: u64,
+        max_extension: u64,
+        min_increment: u64,
+        min_increment_bps: u16,
+        reserve_price: u64,
+        reserve_hash: [u8; 32],
+        gate: Option<Gate>,
+        bumps: &InitAuctionBumps,
+    ) -> Result<()> {
+        require!(end > end_condition.now()?, AuctionError::InvalidEnd);
+        let max_end = end
+            .checked_add(max_extension)
+            .ok_or(AuctionError::ArithematicOverflow)?;
+
+        // an all-zero hash means the reserve (if any) is public.
+        let reserve_hash = (reserve_hash != [0; 32]).then_some(reserve_hash);
+        require!(
+            reserve_hash.is_none() || reserve_price == 0,
+            AuctionError::InvalidReserve
+        );
+        // zero means there is no buy-it-now price.
+        let buy_now_price = (buy_now_price != 0).then_some(buy_now_price);
+        require!(
+            buy_now_price.unwrap_or(starting_price) >= starting_price,
+            AuctionError::PriceTooLow
+        );
+
+        self.auction.set_inner(Auction {
+            seller: self.seller.key(),
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            seed,
+            bump: bumps.auction,
+            end_condition,
+            end,
+            highest_price: starting_price.saturating_sub(1),
+            decimal,
+            bidder: None,
+            extension_window,
+            extension,
+            max_end,
+            min_increment,
+            min_increment_bps,
+            reserve_price,
+            reserve_hash,
+            kind: AuctionKind::English,
+            buy_now_price,
+            gate,
+            proxy_max: None,
+            cancelled: false,
+            open_bids: 0,
+        });
+        self.deposit(amount)?;
+        self.record_created(bumps)?;
+        self.emit_created(amount);
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_dutch_auction(
+        &mut self,
+        seed: u64,
+        start_price: u64,
+        floor_price: u64,
+        step: u64,
+        end_condition: EndCondition,
+        end: u64,
+        amount: u64,
+        decimal: u8,
+        bumps: &InitAuctionBumps,
+    ) -> Result<()> {
+        let start = end_condition.now()?;
+        require!(end > start, AuctionError::InvalidEnd);
+        require!(
+            start_price >= floor_price && step > 0,
+            AuctionError::InvalidSchedule
+        );
+
+        self.auction.set_inner(Auction {
+            seller: self.seller.key(),
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            seed,
+            bump: bumps.auction,
+            end_condition,
+            end,
+            highest_price: start_price,
+            decimal,
+            bidder: None,
+            extension_window: 0,
+            extension: 0,
+            max_end: end,
+            min_increment: 0,
+            min_increment_bps: 0,
+            reserve_price: floor_price,
+            reserve_hash: None,
+            buy_now_price: None,
+            gate: None,
+            proxy_max: None,
+            cancelled: false,
+            open_bids: 0,
+            kind: AuctionKind::Dutch {
+                start_price,
+                floor_price,
+                start,
+                step,
+            },
+        });
+        self.deposit(amount)?;
+        self.record_created(bumps)?;
+        self.emit_created(amount);
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_sealed_auction(
+        &mut self,
+        seed: u64,
         starting_price: u64,
-        end: Slot,
+        end_condition: EndCondition,
+        end: u64,
+        reveal_end: u64,
         amount: u64,
         decimal: u8,
+        second_price: bool,
+        penalty_bps: u16,
         bumps: &InitAuctionBumps,
     ) -> Result<()> {
+        require!(end > end_condition.now()?, AuctionError::InvalidEnd);
+        require!(
+            reveal_end > end && penalty_bps <= 10_000,
+            AuctionError::InvalidSchedule
+        );
+
         self.auction.set_inner(Auction {
             seller: self.seller.key(),
             mint_a: self.mint_a.key(),
             mint_b: self.mint_b.key(),
+            seed,
             bump: bumps.auction,
+            end_condition,
             end,
             highest_price: starting_price.saturating_sub(1),
             decimal,
             bidder: None,
+            extension_window: 0,
+            extension: 0,
+            max_end: end,
+            min_increment: 0,
+            min_increment_bps: 0,
+            reserve_price: 0,
+            reserve_hash: None,
+            buy_now_price: None,
+            gate: None,
+            proxy_max: None,
+            cancelled: false,
+            open_bids: 0,
+            kind: AuctionKind::Sealed {
+                reveal_end,
+                second_price,
+                second_highest: starting_price,
+                penalty_bps,
+                unrevealed: 0,
+            },
+        });
+        self.deposit(amount)?;
+        self.record_created(bumps)?;
+        self.emit_created(amount);
+
+        Ok(())
+    }
+
+    // bids below `min_price` per unit are rejected
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_batch_auction(
+        &mut self,
+        seed: u64,
+        min_price: u64,
+        end_condition: EndCondition,
+        end: u64,
+        amount: u64,
+        decimal: u8,
+        bumps: &InitAuctionBumps,
+    ) -> Result<()> {
+        require!(end > end_condition.now()?, AuctionError::InvalidEnd);
+
+        self.auction.set_inner(Auction {
+            seller: self.seller.key(),
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            seed,
+            bump: bumps.auction,
+            end_condition,
+            end,
+            highest_price: min_price.saturating_sub(1),
+            decimal,
+            bidder: None,
+            extension_window: 0,
+            extension: 0,
+            max_end: end,
+            min_increment: 0,
+            min_increment_bps: 0,
+            reserve_price: 0,
+            reserve_hash: None,
+            buy_now_price: None,
+            gate: None,
+            proxy_max: None,
+            cancelled: false,
+            open_bids: 0,
+            kind: AuctionKind::Batch {
+                supply: amount,
+                head: None,
+                cursor: None,
+                unfilled: amount,
+                clearing_price: 0,
+                filling: false,
+            },
         });
         self.deposit(amount)?;
+        self.record_created(bumps)?;
+        self.emit_created(amount);
 
         Ok(())
     }
 
     fn deposit(&mut self, amount: u64) -> Result<()> {
+        check_mint(&self.mint_a)?;
+        check_mint(&self.mint_b)?;
+
+        let metadata = read_metadata(&self.metadata)?;
+        if is_programmable(metadata.as_ref()) {
+            return self.deposit_programmable(amount);
+        }
+        // only `finalize` pays creators, dutch and batch sales settle without it
+        require!(
+            matches!(
+                self.auction.kind,
+                AuctionKind::English | AuctionKind::Sealed { .. }
+            ) || !metadata.as_ref().is_some_and(pays_royalties),
+            AuctionError::RoyaltiesUnsupported
+        );
+
         let cpi_program = self.token_program.to_account_info();
         let transfer_accounts = TransferChecked {
             from: self.seller_mint_a_ata.to_account_info(),
@@ -80,8 +355,78 @@ impl<'info> InitAuction<'info> {
         };
         let cpi_ctx = CpiContext::new(cpi_program, transfer_accounts);
 
-        transfer_checked(cpi_ctx, amount, self.mint_a.decimals)?;
+        // the seller pays any transfer fee, the vault holds exactly `amount`
+        transfer_checked(
+            cpi_ctx,
+            amount_with_fee(&self.mint_a, amount)?,
+            self.mint_a.decimals,
+        )?;
+
+        Ok(())
+    }
+
+    // pNFTs can only leave the vault through `finalize` and the cancel
+    // instructions, so auctions that settle any other way can't hold them
+    fn deposit_programmable(&mut self, amount: u64) -> Result<()> {
+        require!(
+            matches!(
+                self.auction.kind,
+                AuctionKind::English | AuctionKind::Sealed { .. }
+            ) && self.auction.buy_now_price.is_none(),
+            AuctionError::ProgrammableNftUnsupported
+        );
 
+        ProgrammableTransfer {
+            token_metadata_program: required(&self.token_metadata_program)?,
+            mint: self.mint_a.as_ref().as_ref(),
+            metadata: &self.metadata,
+            edition: required(&self.edition)?,
+            token_record: required(&self.seller_token_record)?,
+            destination_token_record: required(&self.vault_token_record)?,
+            authorization_rules_program: self.authorization_rules_program.as_deref(),
+            authorization_rules: self.authorization_rules.as_deref(),
+            sysvar_instructions: required(&self.sysvar_instructions)?,
+            payer: &self.seller,
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+            associated_token_program: &self.associated_token_program,
+        }
+        .transfer(
+            &self.seller_mint_a_ata.to_account_info(),
+            &self.seller,
+            &self.vault.to_account_info(),
+            &self.auction.to_account_info(),
+            amount,
+            &[],
+        )
+    }
+
+    fn record_created(&mut self, bumps: &InitAuctionBumps) -> Result<()> {
+        open_stats(
+            &mut self.seller_stats,
+            self.seller.key,
+            &self.auction_house.key(),
+            bumps.seller_stats,
+            &self.mint_b.key(),
+            &self.seller,
+            &self.system_program,
+        )?;
+        self.seller_stats.record_created();
         Ok(())
     }
+
+    fn emit_created(&self, amount: u64) {
+        emit!(AuctionCreated {
+            auction_house: self.auction_house.key(),
+            auction: self.auction.key(),
+            seller: self.seller.key(),
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            seed: self.auction.seed,
+            amount,
+            kind: self.auction.kind,
+            end_condition: self.auction.end_condition,
+            end: self.auction.end,
+        });
+    }
 }
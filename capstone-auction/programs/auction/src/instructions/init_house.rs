@@ -1,10 +1,11 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::AuctionError;
-use crate::state::AuctionHouse;
+use crate::events::HouseCreated;
+use crate::state::{AuctionHouse, KeeperReward};
 
 #[derive(Accounts)]
-#[instruction(_fee: u16, name: String)]
+#[instruction(_fee: u16, _cancel_penalty_bps: u16, name: String)]
 pub struct InitHouse<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
@@ -20,18 +21,37 @@ pub struct InitHouse<'info> {
 }
 
 impl<'info> InitHouse<'info> {
-    pub fn init_house(&mut self, fee: u16, bumps: &InitHouseBumps, name: String) -> Result<()> {
+    pub fn init_house(
+        &mut self,
+        fee: u16,
+        cancel_penalty_bps: u16,
+        bumps: &InitHouseBumps,
+        name: String,
+    ) -> Result<()> {
         require!(
             !name.is_empty() && name.len() < 32,
             AuctionError::NameTooLong
         );
+        require!(fee <= 10_000, AuctionError::InvalidFee);
+        require!(cancel_penalty_bps <= 10_000, AuctionError::InvalidPenalty);
 
         self.auction_house.set_inner(AuctionHouse {
             admin: self.admin.key(),
             fee,
+            cancel_penalty_bps,
             bump: bumps.auction_house,
+            pending_admin: None,
+            paused: false,
+            keeper_reward: KeeperReward::None,
             name,
         });
+
+        emit!(HouseCreated {
+            auction_house: self.auction_house.key(),
+            admin: self.admin.key(),
+            fee,
+            name: self.auction_house.name.clone(),
+        });
         Ok(())
     }
 }
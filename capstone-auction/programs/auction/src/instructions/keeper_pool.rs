@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::errors::AuctionError;
+use crate::events::{KeeperPoolFunded, KeeperPoolWithdrawn};
+use crate::state::AuctionHouse;
+
+// admin moves SOL in and out of the pool lamport keeper tips are paid from.
+// The pool is kept apart from the house account so tips never come out of the
+// native SOL fees collected there.
+#[derive(Accounts)]
+pub struct ManageKeeperPool<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin @ AuctionError::NotHouseAdmin,
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    #[account(
+        mut,
+        seeds = [b"keeper_pool", auction_house.key().as_ref()],
+        bump,
+    )]
+    pub keeper_pool: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ManageKeeperPool<'info> {
+    pub fn fund_keeper_pool(&mut self, amount: u64) -> Result<()> {
+        let transfer_accounts = Transfer {
+            from: self.admin.to_account_info(),
+            to: self.keeper_pool.to_account_info(),
+        };
+        transfer(
+            CpiContext::new(self.system_program.to_account_info(), transfer_accounts),
+            amount,
+        )?;
+
+        emit!(KeeperPoolFunded {
+            auction_house: self.auction_house.key(),
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn withdraw_keeper_pool(
+        &mut self,
+        amount: u64,
+        bumps: &ManageKeeperPoolBumps,
+    ) -> Result<()> {
+        require!(
+            self.keeper_pool.lamports() >= amount,
+            AuctionError::InsufficientHouseLamports
+        );
+
+        let house = self.auction_house.key();
+        let seeds = &[b"keeper_pool", house.as_ref(), &[bumps.keeper_pool]];
+        let transfer_accounts = Transfer {
+            from: self.keeper_pool.to_account_info(),
+            to: self.admin.to_account_info(),
+        };
+        transfer(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                transfer_accounts,
+                &[&seeds[..]],
+            ),
+            amount,
+        )?;
+
+        emit!(KeeperPoolWithdrawn {
+            auction_house: house,
+            amount,
+        });
+        Ok(())
+    }
+}
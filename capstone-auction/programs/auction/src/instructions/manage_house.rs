@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AuctionError;
+use crate::events::{AdminProposed, HouseFeeUpdated, HousePaused, KeeperRewardUpdated};
+use crate::state::{AuctionHouse, KeeperReward};
+
+// shared by `update_house_fee`, `propose_admin`, `pause_house` and
+// `set_keeper_reward`
+#[derive(Accounts)]
+pub struct ManageHouse<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin @ AuctionError::NotHouseAdmin,
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+}
+
+impl<'info> ManageHouse<'info> {
+    pub fn update_house_fee(&mut self, fee: u16) -> Result<()> {
+        require!(fee <= 10_000, AuctionError::InvalidFee);
+
+        let old_fee = self.auction_house.fee;
+        self.auction_house.fee = fee;
+
+        emit!(HouseFeeUpdated {
+            auction_house: self.auction_house.key(),
+            old_fee,
+            new_fee: fee,
+        });
+        Ok(())
+    }
+
+    // the new admin has to accept before the house changes hands, so a typo
+    // can't lock the house. Proposing again replaces the pending admin.
+    pub fn propose_admin(&mut self, pending_admin: Pubkey) -> Result<()> {
+        self.auction_house.pending_admin = Some(pending_admin);
+
+        emit!(AdminProposed {
+            auction_house: self.auction_house.key(),
+            admin: self.admin.key(),
+            pending_admin,
+        });
+        Ok(())
+    }
+
+    pub fn pause_house(&mut self, paused: bool) -> Result<()> {
+        self.auction_house.paused = paused;
+
+        emit!(HousePaused {
+            auction_house: self.auction_house.key(),
+            paused,
+        });
+        Ok(())
+    }
+
+    pub fn set_keeper_reward(&mut self, keeper_reward: KeeperReward) -> Result<()> {
+        if let KeeperReward::FeeShare { bps } = keeper_reward {
+            require!(bps <= 10_000, AuctionError::InvalidFee);
+        }
+        self.auction_house.keeper_reward = keeper_reward;
+
+        emit!(KeeperRewardUpdated {
+            auction_house: self.auction_house.key(),
+            keeper_reward,
+        });
+        Ok(())
+    }
+}
@@ -1,12 +1,48 @@
 pub mod init_house;
 pub use init_house::*;
 
+pub mod manage_house;
+pub use manage_house::*;
+
+pub mod accept_admin;
+pub use accept_admin::*;
+
+pub mod sweep_fees;
+pub use sweep_fees::*;
+
+pub mod keeper_pool;
+pub use keeper_pool::*;
+
 pub mod init_auction;
 pub use init_auction::*;
 
+pub mod reveal_reserve;
+pub use reveal_reserve::*;
+
 pub mod bid;
 pub use bid::*;
 
+pub mod batch_bid;
+pub use batch_bid::*;
+
+pub mod tally_batch;
+pub use tally_batch::*;
+
+pub mod settle_batch;
+pub use settle_batch::*;
+
+pub mod reveal_bid;
+pub use reveal_bid::*;
+
+pub mod penalize_bid;
+pub use penalize_bid::*;
+
+pub mod raise_bid;
+pub use raise_bid::*;
+
+pub mod buy_now;
+pub use buy_now::*;
+
 pub mod withdraw;
 pub use withdraw::*;
 
@@ -15,3 +51,9 @@ pub use finalize::*;
 
 pub mod cancel;
 pub use cancel::*;
+
+pub mod cancel_expired;
+pub use cancel_expired::*;
+
+pub mod cancel_early;
+pub use cancel_early::*;
@@ -0,0 +1,158 @@
+use crate::errors::AuctionError;
+use crate::instructions::{is_native_mint, pay_lamports};
+use crate::state::{Auction, AuctionHouse, AuctionKind, BidState};
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+// the seller gets the penalty from a sealed bid that was never revealed.
+// Anyone can call it, the auction doesn't settle before every such bid is.
+// Native SOL bids are settled right away: the seller is paid in lamports and
+// the rest of the deposit goes straight back to the bidder.
+#[derive(Accounts)]
+pub struct PenalizeBid<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Checked by the auction seeds.
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+    /// CHECK: Checked by the bid state seeds.
+    #[account(mut)]
+    pub bidder: AccountInfo<'info>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    #[account(
+        mut,
+        seeds = [b"auction", auction_house.key().as_ref(), seller.key().as_ref(), auction.mint_a.as_ref(), mint_b.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = !auction.cancelled @ AuctionError::AuctionCancelled,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+    #[account(
+        mut,
+        seeds = [b"bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = bid_state.bump,
+        constraint = bid_state.auction == auction.key(),
+    )]
+    pub bid_state: Box<Account<'info, BidState>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = bid_state,
+    )]
+    pub bidder_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+    // left out in native SOL auctions
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = seller,
+    )]
+    pub seller_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PenalizeBid<'info> {
+    pub fn penalize_bid(&mut self) -> Result<()> {
+        let AuctionKind::Sealed {
+            reveal_end,
+            penalty_bps,
+            ..
+        } = self.auction.kind
+        else {
+            return err!(AuctionError::WrongAuctionKind);
+        };
+        require!(
+            self.auction.now()? >= reveal_end && self.bid_state.commitment.is_some(),
+            AuctionError::NotEligibleToWithdraw
+        );
+
+        // the rest of the deposit can be withdrawn as usual afterwards
+        self.bid_state.commitment = None;
+        self.auction.uncommit_bid();
+
+        let penalty = self
+            .bidder_escrow
+            .amount
+            .checked_mul(u64::from(penalty_bps))
+            .ok_or(AuctionError::ArithematicOverflow)?
+            / 10_000;
+        if is_native_mint(&self.mint_b.key()) {
+            return self.pay_out_lamports(penalty);
+        }
+        if penalty == 0 {
+            return Ok(());
+        }
+
+        let seeds = &[
+            b"bid",
+            self.auction.to_account_info().key.as_ref(),
+            self.bidder.to_account_info().key.as_ref(),
+            &[self.bid_state.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let seller_mint_b_ata = self
+            .seller_mint_b_ata
+            .as_ref()
+            .ok_or(AuctionError::MissingTokenAccount)?;
+        let transfer_accounts = TransferChecked {
+            from: self.bidder_escrow.to_account_info(),
+            to: seller_mint_b_ata.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            authority: self.bid_state.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, penalty, self.mint_b.decimals)
+    }
+
+    // like `Finalize::pay_out_lamports`: the escrow is unwrapped into the bid
+    // state, which pays the seller and is then closed to the bidder. A penalty
+    // too small to land in the seller's wallet stays with the bidder.
+    fn pay_out_lamports(&mut self, penalty: u64) -> Result<()> {
+        let seeds = &[
+            b"bid",
+            self.auction.to_account_info().key.as_ref(),
+            self.bidder.to_account_info().key.as_ref(),
+            &[self.bid_state.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let accounts = CloseAccount {
+            account: self.bidder_escrow.to_account_info(),
+            destination: self.bid_state.to_account_info(),
+            authority: self.bid_state.to_account_info(),
+        };
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            signer_seeds,
+        ))?;
+
+        pay_lamports(
+            &self.bid_state.to_account_info(),
+            [(self.seller.to_account_info(), penalty)],
+            (&self.bidder.to_account_info(), 0),
+        )?;
+        self.auction.close_bid();
+        self.bid_state.close(self.bidder.to_account_info())
+    }
+}
@@ -0,0 +1,108 @@
+use crate::errors::AuctionError;
+use crate::instructions::{calculate_amount_b, deposit_into_escrow, emit_bid};
+use crate::state::{Auction, AuctionHouse, BidState};
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+// bidder raises the price of the bid they already placed in this auction
+#[derive(Accounts)]
+pub struct RaiseBid<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        constraint = !auction_house.paused @ AuctionError::HousePaused,
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    #[account(
+        mut,
+        seeds = [b"auction", auction_house.key().as_ref(), auction.seller.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = !auction.cancelled @ AuctionError::AuctionCancelled,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+    // left out to bid native SOL straight from the bidder's wallet
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = bidder,
+    )]
+    pub bidder_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = bid_state.bump,
+        constraint = bid_state.auction == auction.key(),
+    )]
+    pub bid_state: Box<Account<'info, BidState>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = bid_state,
+    )]
+    pub bidder_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        associated_token::mint = auction.mint_a,
+        associated_token::authority = auction,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RaiseBid<'info> {
+    // the new `max_price` replaces the old one, left out the bid is no
+    // longer a proxy bid. A leader raising their max keeps their price.
+    pub fn raise_bid(&mut self, price: u64, max_price: Option<u64>) -> Result<()> {
+        let now = self.auction.now()?;
+        let (previous_bidder, previous_price) = (self.auction.bidder, self.auction.highest_price);
+        let leads = self
+            .auction
+            .place_bid(self.bidder.key(), price, max_price, now)?;
+        if previous_bidder != Some(self.bidder.key()) || max_price.is_none() {
+            self.bid_state.price = price;
+        }
+        self.bid_state.max_price = max_price;
+
+        emit_bid(
+            &self.auction,
+            self.bidder.key(),
+            price,
+            leads,
+            previous_bidder,
+            previous_price,
+        );
+        self.deposit_difference()
+    }
+
+    // only the part of the new price not already held in escrow is deposited
+    fn deposit_difference(&mut self) -> Result<()> {
+        let amount = calculate_amount_b(
+            self.vault.amount,
+            self.bid_state.covered_price(),
+            self.mint_a.decimals,
+            self.mint_b.decimals,
+            self.auction.decimal,
+        )
+        .ok_or(AuctionError::ArithematicOverflow)?
+        .saturating_sub(self.bidder_escrow.amount);
+
+        if amount == 0 {
+            return Ok(());
+        }
+
+        deposit_into_escrow(
+            &self.bidder,
+            self.bidder_mint_b_ata.as_deref(),
+            &self.bidder_escrow,
+            &self.mint_b,
+            amount,
+            &self.token_program,
+            &self.system_program,
+        )
+    }
+}
@@ -0,0 +1,83 @@
+use crate::errors::AuctionError;
+use crate::events::BidPlaced;
+use crate::instructions::calculate_amount_b;
+use crate::state::{bid_commitment, Auction, AuctionHouse, BidState};
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+// bidder opens their sealed bid during the reveal phase
+#[derive(Accounts)]
+pub struct RevealBid<'info> {
+    pub bidder: Signer<'info>,
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    #[account(
+        mut,
+        seeds = [b"auction", auction_house.key().as_ref(), auction.seller.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = !auction.cancelled @ AuctionError::AuctionCancelled,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+    #[account(
+        mut,
+        seeds = [b"bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = bid_state.bump,
+        constraint = bid_state.auction == auction.key(),
+    )]
+    pub bid_state: Box<Account<'info, BidState>>,
+    #[account(
+        associated_token::mint = mint_b,
+        associated_token::authority = bid_state,
+    )]
+    pub bidder_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        associated_token::mint = mint_a,
+        associated_token::authority = auction,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+impl<'info> RevealBid<'info> {
+    pub fn reveal_bid(&mut self, price: u64, salt: [u8; 32]) -> Result<()> {
+        require!(
+            self.bid_state.commitment == Some(bid_commitment(price, &salt, &self.bidder.key())),
+            AuctionError::InvalidCommitment
+        );
+
+        let amount = calculate_amount_b(
+            self.vault.amount,
+            price,
+            self.mint_a.decimals,
+            self.mint_b.decimals,
+            self.auction.decimal,
+        )
+        .ok_or(AuctionError::ArithematicOverflow)?;
+        require!(
+            self.bidder_escrow.amount >= amount,
+            AuctionError::InsufficientCollateral
+        );
+
+        let now = self.auction.now()?;
+        let (previous_bidder, previous_price) = (self.auction.bidder, self.auction.highest_price);
+        self.auction.reveal_bid(self.bidder.key(), price, now)?;
+
+        self.bid_state.price = price;
+        self.bid_state.commitment = None;
+
+        emit!(BidPlaced {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            price,
+            previous_bidder,
+            previous_price,
+            end: self.auction.end,
+        });
+        Ok(())
+    }
+}
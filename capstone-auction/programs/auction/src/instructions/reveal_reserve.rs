@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AuctionError;
+use crate::state::{reserve_hash, Auction, AuctionHouse};
+
+#[derive(Accounts)]
+pub struct RevealReserve<'info> {
+    pub seller: Signer<'info>,
+    #[account(
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    #[account(
+        mut,
+        seeds = [b"auction", auction_house.key().as_ref(), seller.key().as_ref(), auction.mint_a.as_ref(), auction.mint_b.as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = !auction.cancelled @ AuctionError::AuctionCancelled,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+}
+
+impl<'info> RevealReserve<'info> {
+    pub fn reveal_reserve(&mut self, reserve_price: u64, salt: [u8; 32]) -> Result<()> {
+        require!(
+            self.auction.now()? < self.auction.reserve_deadline(),
+            AuctionError::NotInRevealPhase
+        );
+        require!(
+            self.auction.reserve_hash == Some(reserve_hash(reserve_price, &salt)),
+            AuctionError::InvalidReserve
+        );
+
+        self.auction.reserve_price = reserve_price;
+        self.auction.reserve_hash = None;
+        Ok(())
+    }
+}
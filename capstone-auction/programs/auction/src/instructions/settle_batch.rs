@@ -0,0 +1,412 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::errors::AuctionError;
+use crate::events::{BatchBidFilled, BatchSettled, KeeperPaid};
+use crate::instructions::{
+    calculate_amount_b, house_fee, is_native_mint, keeper_fee_share, pay_keeper_tip, pay_lamports,
+    transfer_vault_and_close,
+};
+use crate::state::{Auction, AuctionHouse, AuctionKind, BidState};
+
+// second settlement pass of a batch auction, after `tally_batch`. Walks the
+// winning bids again, in chunks of four remaining accounts per bid:
+// [bid_state, bidder_escrow, bidder, bidder_mint_a_ata]. Every winner gets
+// their units and pays the clearing price out of escrow; whatever is left in
+// escrow is theirs to `withdraw`. The last chunk returns unsold units to the
+// seller and closes the auction, which releases all other bids. The keeper is
+// rewarded like in `finalize`: a share of each fill's house fee, and the
+// lamport tip once the auction closes. Native SOL winners are settled in
+// lamports and get the rest of their escrow back right away.
+#[derive(Accounts)]
+pub struct SettleBatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Checked by the auction seeds.
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+    // mutable to collect withheld transfer fees before closing accounts
+    #[account(mut)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    // mutable to take native SOL fees in lamports
+    #[account(
+        mut,
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    // pays lamport keeper tips
+    #[account(
+        mut,
+        seeds = [b"keeper_pool", auction_house.key().as_ref()],
+        bump,
+    )]
+    pub keeper_pool: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"auction", auction_house.key().as_ref(), seller.key().as_ref(), mint_a.key().as_ref(), mint_b.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = !auction.cancelled @ AuctionError::AuctionCancelled,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = auction,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = seller,
+    )]
+    pub seller_mint_a_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    // this and the treasury are left out in native SOL auctions
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = seller,
+    )]
+    pub seller_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"treasury", auction_house.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+        token::mint = mint_b,
+        token::authority = auction_house,
+        token::token_program = token_program,
+    )]
+    pub treasury: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // receives the keeper's share of the house fees, left out the share stays
+    // in the treasury
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::authority = payer,
+    )]
+    pub payer_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SettleBatch<'info> {
+    pub fn settle_batch(
+        &mut self,
+        bids: &'info [AccountInfo<'info>],
+        bumps: &SettleBatchBumps,
+    ) -> Result<()> {
+        let AuctionKind::Batch {
+            clearing_price,
+            filling: true,
+            ..
+        } = self.auction.kind
+        else {
+            return err!(AuctionError::WrongSettlementPhase);
+        };
+
+        let mut keeper_tokens = 0;
+        for accounts in bids.chunks(4) {
+            let [bid_state, bidder_escrow, bidder, bidder_mint_a_ata] = accounts else {
+                return err!(AuctionError::InvalidBidOrder);
+            };
+            let bid = Account::<BidState>::try_from(bid_state)?;
+            require!(
+                bid.auction == self.auction.key() && bid.bidder == bidder.key(),
+                AuctionError::InvalidBidOrder
+            );
+
+            let quantity = self.auction.fill_batch_bid(bid_state.key(), &bid)?;
+            keeper_tokens += self.fill(
+                &bid,
+                bidder_escrow,
+                bidder,
+                bidder_mint_a_ata,
+                quantity,
+                clearing_price,
+            )?;
+        }
+
+        let mut keeper_lamports = 0;
+        if let AuctionKind::Batch {
+            cursor: None,
+            supply,
+            unfilled,
+            ..
+        } = self.auction.kind
+        {
+            self.return_unsold_and_close()?;
+
+            emit!(BatchSettled {
+                auction: self.auction.key(),
+                seller: self.seller.key(),
+                clearing_price,
+                sold: supply - unfilled,
+            });
+            keeper_lamports = pay_keeper_tip(
+                &self.auction_house,
+                &self.keeper_pool,
+                bumps.keeper_pool,
+                &self.payer,
+                self.seller.key,
+                &self.system_program,
+            )?;
+        }
+
+        if keeper_lamports > 0 || keeper_tokens > 0 {
+            emit!(KeeperPaid {
+                auction: self.auction.key(),
+                keeper: self.payer.key(),
+                lamports: keeper_lamports,
+                tokens: keeper_tokens,
+            });
+        }
+        Ok(())
+    }
+
+    // returns the keeper's share of the house fee
+    fn fill(
+        &mut self,
+        bid: &Account<'info, BidState>,
+        bidder_escrow: &'info AccountInfo<'info>,
+        bidder: &'info AccountInfo<'info>,
+        bidder_mint_a_ata: &'info AccountInfo<'info>,
+        quantity: u64,
+        price: u64,
+    ) -> Result<u64> {
+        let token_program = self.token_program.key();
+        require_keys_eq!(
+            bidder_escrow.key(),
+            get_associated_token_address_with_program_id(
+                &bid.key(),
+                &self.mint_b.key(),
+                &token_program
+            ),
+            AuctionError::InvalidBidOrder
+        );
+        require_keys_eq!(
+            bidder_mint_a_ata.key(),
+            get_associated_token_address_with_program_id(
+                &bidder.key(),
+                &self.mint_a.key(),
+                &token_program
+            ),
+            AuctionError::InvalidBidOrder
+        );
+
+        if bidder_mint_a_ata.data_is_empty() {
+            anchor_spl::associated_token::create(CpiContext::new(
+                self.associated_token_program.to_account_info(),
+                anchor_spl::associated_token::Create {
+                    payer: self.payer.to_account_info(),
+                    associated_token: bidder_mint_a_ata.clone(),
+                    authority: bidder.clone(),
+                    mint: self.mint_a.to_account_info(),
+                    system_program: self.system_program.to_account_info(),
+                    token_program: self.token_program.to_account_info(),
+                },
+            ))?;
+        }
+
+        let auction_seeds = &[
+            b"auction",
+            self.auction_house.to_account_info().key.as_ref(),
+            self.seller.to_account_info().key.as_ref(),
+            self.mint_a.to_account_info().key.as_ref(),
+            self.mint_b.to_account_info().key.as_ref(),
+            &self.auction.seed.to_le_bytes(),
+            &[self.auction.bump],
+        ];
+        let transfer_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            to: bidder_mint_a_ata.clone(),
+            mint: self.mint_a.to_account_info(),
+            authority: self.auction.to_account_info(),
+        };
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                transfer_accounts,
+                &[&auction_seeds[..]],
+            ),
+            quantity,
+            self.mint_a.decimals,
+        )?;
+
+        let total = calculate_amount_b(
+            quantity,
+            price,
+            self.mint_a.decimals,
+            self.mint_b.decimals,
+            self.auction.decimal,
+        )
+        .ok_or(AuctionError::ArithematicOverflow)?;
+        let house_fee =
+            house_fee(total, self.auction_house.fee).ok_or(AuctionError::ArithematicOverflow)?;
+        let keeper_share = if is_native_mint(&self.mint_b.key()) {
+            self.pay_out_lamports(bid, bidder_escrow, bidder, total, house_fee)?
+        } else {
+            self.pay_out_tokens(bid, bidder_escrow, bidder, total, house_fee)?
+        };
+
+        emit!(BatchBidFilled {
+            auction: self.auction.key(),
+            bidder: bidder.key(),
+            quantity,
+            price,
+            total,
+            house_fee,
+        });
+        Ok(keeper_share)
+    }
+
+    // returns the keeper's share of the house fee, the rest of the escrow is
+    // the bidder's to `withdraw`
+    fn pay_out_tokens(
+        &self,
+        bid: &Account<'info, BidState>,
+        bidder_escrow: &'info AccountInfo<'info>,
+        bidder: &'info AccountInfo<'info>,
+        total: u64,
+        house_fee: u64,
+    ) -> Result<u64> {
+        let (Some(seller_mint_b_ata), Some(treasury)) = (&self.seller_mint_b_ata, &self.treasury)
+        else {
+            return err!(AuctionError::MissingTokenAccount);
+        };
+        let (keeper, keeper_share) = match &self.payer_mint_b_ata {
+            Some(payer_mint_b_ata) => (
+                payer_mint_b_ata.to_account_info(),
+                keeper_fee_share(
+                    &self.auction_house,
+                    house_fee,
+                    self.payer.key,
+                    self.seller.key,
+                )?,
+            ),
+            None => (treasury.to_account_info(), 0),
+        };
+
+        let bid_seeds = &[
+            b"bid",
+            self.auction.to_account_info().key.as_ref(),
+            bidder.key.as_ref(),
+            &[bid.bump],
+        ];
+        for (to, amount) in [
+            (seller_mint_b_ata.to_account_info(), total - house_fee),
+            (treasury.to_account_info(), house_fee - keeper_share),
+            (keeper, keeper_share),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            let transfer_accounts = TransferChecked {
+                from: bidder_escrow.clone(),
+                to,
+                mint: self.mint_b.to_account_info(),
+                authority: bid.to_account_info(),
+            };
+            transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    transfer_accounts,
+                    &[&bid_seeds[..]],
+                ),
+                amount,
+                self.mint_b.decimals,
+            )?;
+        }
+        Ok(keeper_share)
+    }
+
+    // like `Finalize::pay_out_lamports`: the escrow is unwrapped into the bid
+    // state, which pays the seller, the house and the keeper and is then
+    // closed to the bidder. Returns the keeper's share of the house fee.
+    fn pay_out_lamports(
+        &mut self,
+        bid: &Account<'info, BidState>,
+        bidder_escrow: &'info AccountInfo<'info>,
+        bidder: &'info AccountInfo<'info>,
+        total: u64,
+        house_fee: u64,
+    ) -> Result<u64> {
+        let keeper_share = keeper_fee_share(
+            &self.auction_house,
+            house_fee,
+            self.payer.key,
+            self.seller.key,
+        )?;
+
+        let bid_seeds = &[
+            b"bid",
+            self.auction.to_account_info().key.as_ref(),
+            bidder.key.as_ref(),
+            &[bid.bump],
+        ];
+        let accounts = CloseAccount {
+            account: bidder_escrow.clone(),
+            destination: bid.to_account_info(),
+            authority: bid.to_account_info(),
+        };
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            accounts,
+            &[&bid_seeds[..]],
+        ))?;
+
+        let payouts = [
+            (
+                self.auction_house.to_account_info(),
+                house_fee - keeper_share,
+            ),
+            (self.payer.to_account_info(), keeper_share),
+        ];
+        pay_lamports(
+            &bid.to_account_info(),
+            payouts,
+            (&self.seller, total - house_fee),
+        )?;
+        self.auction.close_bid();
+        bid.close(bidder.clone())?;
+        Ok(keeper_share)
+    }
+
+    fn return_unsold_and_close(&mut self) -> Result<()> {
+        // the fills above moved tokens out of the vault
+        self.vault.reload()?;
+
+        let seeds = &[
+            b"auction",
+            self.auction_house.to_account_info().key.as_ref(),
+            self.seller.to_account_info().key.as_ref(),
+            self.mint_a.to_account_info().key.as_ref(),
+            self.mint_b.to_account_info().key.as_ref(),
+            &self.auction.seed.to_le_bytes(),
+            &[self.auction.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        transfer_vault_and_close(
+            &self.vault,
+            &self.mint_a,
+            self.seller_mint_a_ata.to_account_info(),
+            self.auction.to_account_info(),
+            self.seller.to_account_info(),
+            self.token_program.to_account_info(),
+            signer_seeds,
+        )?;
+
+        self.auction.close(self.seller.to_account_info())
+    }
+}
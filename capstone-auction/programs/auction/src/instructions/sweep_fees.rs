@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::errors::AuctionError;
+use crate::events::FeesSwept;
+use crate::state::AuctionHouse;
+
+// admin moves the fees collected in one mint out of the house treasury
+#[derive(Accounts)]
+pub struct SweepFees<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin @ AuctionError::NotHouseAdmin,
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"treasury", auction_house.key().as_ref(), mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = auction_house,
+        token::token_program = token_program,
+    )]
+    pub treasury: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> SweepFees<'info> {
+    pub fn sweep_fees(&mut self) -> Result<()> {
+        let amount = self.treasury.amount;
+
+        let seeds = &[
+            b"house",
+            self.auction_house.name.as_bytes(),
+            &[self.auction_house.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_accounts = TransferChecked {
+            from: self.treasury.to_account_info(),
+            to: self.destination.to_account_info(),
+            mint: self.mint.to_account_info(),
+            authority: self.auction_house.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            transfer_accounts,
+            signer_seeds,
+        );
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)?;
+
+        emit!(FeesSwept {
+            auction_house: self.auction_house.key(),
+            mint: self.mint.key(),
+            destination: self.destination.key(),
+            amount,
+        });
+        Ok(())
+    }
+}
+
+// admin moves SOL out of the house account, where native SOL auctions pay
+// their fees.
+#[derive(Accounts)]
+pub struct SweepLamports<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin @ AuctionError::NotHouseAdmin,
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    #[account(mut)]
+    pub destination: SystemAccount<'info>,
+}
+
+impl<'info> SweepLamports<'info> {
+    pub fn sweep_lamports(&mut self, amount: u64) -> Result<()> {
+        let house = self.auction_house.to_account_info();
+        let rent = Rent::get()?.minimum_balance(house.data_len());
+        require!(
+            house.lamports().saturating_sub(rent) >= amount,
+            AuctionError::InsufficientHouseLamports
+        );
+
+        **house.try_borrow_mut_lamports()? -= amount;
+        **self.destination.try_borrow_mut_lamports()? += amount;
+
+        emit!(FeesSwept {
+            auction_house: self.auction_house.key(),
+            mint: spl_token::native_mint::ID,
+            destination: self.destination.key(),
+            amount,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::AuctionError;
+use crate::state::{Auction, AuctionHouse, AuctionKind, BidState};
+
+// first settlement pass of a batch auction: anyone walks the bids from the
+// top, passed in list order as remaining accounts, until the supply runs out.
+// The last bid reached sets the clearing price. Can be cranked in chunks.
+#[derive(Accounts)]
+pub struct TallyBatch<'info> {
+    #[account(
+        seeds = [b"house", auction_house.name.as_bytes()],
+        bump = auction_house.bump,
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+    #[account(
+        mut,
+        seeds = [b"auction", auction_house.key().as_ref(), auction.seller.as_ref(), auction.mint_a.as_ref(), auction.mint_b.as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+        constraint = !auction.cancelled @ AuctionError::AuctionCancelled,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+}
+
+impl<'info> TallyBatch<'info> {
+    pub fn tally_batch(&mut self, bids: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(
+            self.auction.now()? >= self.auction.end,
+            AuctionError::AuctionNotEnded
+        );
+        require!(
+            matches!(self.auction.kind, AuctionKind::Batch { filling: false, .. }),
+            AuctionError::WrongSettlementPhase
+        );
+
+        for info in bids {
+            let bid = Account::<BidState>::try_from(info)?;
+            require!(
+                bid.auction == self.auction.key(),
+                AuctionError::InvalidBidOrder
+            );
+            self.auction.fill_batch_bid(info.key(), &bid)?;
+            if let AuctionKind::Batch {
+                ref mut clearing_price,
+                ..
+            } = self.auction.kind
+            {
+                *clearing_price = bid.price;
+            }
+        }
+
+        // the walk is over once the cursor runs off the list, start filling
+        if let AuctionKind::Batch {
+            supply,
+            head,
+            ref mut cursor,
+            ref mut unfilled,
+            ref mut filling,
+            ..
+        } = self.auction.kind
+        {
+            if cursor.is_none() {
+                *cursor = head;
+                *unfilled = supply;
+                *filling = true;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,32 +1,42 @@
 use crate::errors::AuctionError;
-use crate::state::{Auction, AuctionHouse, BidState};
+use crate::events::BidWithdrawn;
+use crate::instructions::is_native_mint;
+use crate::state::{Auction, AuctionHouse, AuctionKind, BidState};
+use crate::token_extensions::harvest_withheld;
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{close_account, CloseAccount};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
 };
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
     #[account(mut)]
     pub bidder: Signer<'info>,
+    // mutable to collect withheld transfer fees before closing accounts
+    #[account(mut)]
     pub mint_b: Box<InterfaceAccount<'info, Mint>>,
     #[account(
         seeds = [b"house", auction_house.name.as_bytes()],
         bump = auction_house.bump,
     )]
     pub auction_house: Box<Account<'info, AuctionHouse>>,
+    // mutable to count the withdrawn bid out
     /// CHECK: This is unchecked, because the account may or may not exist at this point.
+    #[account(mut)]
     pub auction: AccountInfo<'info>,
+    // left out in native SOL auctions, closing the escrow unwraps the bid
     #[account(
         init_if_needed,
         payer = bidder,
         associated_token::mint = mint_b,
         associated_token::authority = bidder,
     )]
-    pub bidder_mint_b_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub bidder_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     #[account(
         mut,
         associated_token::mint = mint_b,
@@ -49,18 +59,35 @@ pub struct Withdraw<'info> {
 impl<'info> Withdraw<'info> {
     pub fn withdraw(&mut self) -> Result<()> {
         msg!("withdraw");
-        // If the `auction` account does not exist (no data)
-        // then no additional verification is necessary.
-        // If the `auction` account does exist, it must be Auction,
-        // and the withdrawing bidder must not be the highest bidder.
+        // If the `auction` account does not exist (no data) it was settled
+        // and no additional verification is necessary. A cancelled auction
+        // lets every bidder go. Otherwise the withdrawing bidder must not be
+        // the highest bidder.
         if !self.auction.data_is_empty() {
             msg!("auction still exists...");
-            let auction = Auction::try_deserialize(&mut self.auction.data.borrow().as_ref())?;
-            require!(
-                auction.bidder != Some(self.bid_state.bidder),
-                AuctionError::NotEligibleToWithdraw
-            );
-            msg!("and i'm not highest bidder");
+            let mut auction = Auction::try_deserialize(&mut self.auction.data.borrow().as_ref())?;
+            auction.close_bid();
+            if !auction.cancelled {
+                require!(
+                    auction.bidder != Some(self.bid_state.bidder),
+                    AuctionError::NotEligibleToWithdraw
+                );
+                msg!("and i'm not highest bidder");
+                // batch bids stay locked until settlement closes the auction
+                require!(
+                    !matches!(auction.kind, AuctionKind::Batch { .. }),
+                    AuctionError::NotEligibleToWithdraw
+                );
+                // a sealed bid that missed the reveal phase waits for the seller's penalty
+                require!(
+                    self.bid_state.commitment.is_none() || auction.now()? < auction.end,
+                    AuctionError::NotEligibleToWithdraw
+                );
+                if self.bid_state.commitment.is_some() {
+                    auction.uncommit_bid();
+                }
+            }
+            auction.try_serialize(&mut self.auction.data.borrow_mut().as_mut())?;
         }
 
         let seeds = &[
@@ -71,19 +98,34 @@ impl<'info> Withdraw<'info> {
         ];
         let signer_seeds = &[&seeds[..]];
 
-        let cpi_program = self.token_program.to_account_info();
+        match &self.bidder_mint_b_ata {
+            Some(bidder_mint_b_ata) => {
+                let cpi_program = self.token_program.to_account_info();
 
-        let transfer_accounts = TransferChecked {
-            from: self.bidder_escrow.to_account_info(),
-            to: self.bidder_mint_b_ata.to_account_info(),
-            mint: self.mint_b.to_account_info(),
-            authority: self.bid_state.to_account_info(),
-        };
+                let transfer_accounts = TransferChecked {
+                    from: self.bidder_escrow.to_account_info(),
+                    to: bidder_mint_b_ata.to_account_info(),
+                    mint: self.mint_b.to_account_info(),
+                    authority: self.bid_state.to_account_info(),
+                };
 
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, transfer_accounts, signer_seeds);
+                let cpi_ctx =
+                    CpiContext::new_with_signer(cpi_program, transfer_accounts, signer_seeds);
 
-        msg!("transfering back to bidder");
-        transfer_checked(cpi_ctx, self.bidder_escrow.amount, self.mint_b.decimals)?;
+                msg!("transfering back to bidder");
+                transfer_checked(cpi_ctx, self.bidder_escrow.amount, self.mint_b.decimals)?;
+            }
+            None => require!(
+                is_native_mint(&self.mint_b.key()),
+                AuctionError::MissingTokenAccount
+            ),
+        }
+
+        emit!(BidWithdrawn {
+            auction: self.auction.key(),
+            bidder: self.bidder.key(),
+            amount: self.bidder_escrow.amount,
+        });
 
         // close escrow
         let seeds = &[
@@ -94,6 +136,12 @@ impl<'info> Withdraw<'info> {
         ];
         let signer_seeds = &[&seeds[..]];
 
+        harvest_withheld(
+            self.token_program.to_account_info(),
+            self.mint_b.to_account_info(),
+            self.bidder_escrow.to_account_info(),
+        )?;
+
         let accounts = CloseAccount {
             account: self.bidder_escrow.to_account_info(),
             destination: self.bidder.to_account_info(),
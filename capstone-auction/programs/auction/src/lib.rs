@@ -1,11 +1,16 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::clock::Slot;
-mod errors;
+pub mod errors;
+mod events;
+mod gate;
 mod instructions;
-mod state;
+mod nft;
+pub mod state;
+mod stats;
+mod token_extensions;
 
 // use errors::*;
 use instructions::*;
+use state::{EndCondition, Gate, KeeperReward};
 
 declare_id!("6k28s3kLLzHwdDbuw6BVKSxK6oH1FUJQiWPeoFomnGrF");
 
@@ -14,25 +19,236 @@ pub mod auction {
 
     use super::*;
 
-    pub fn init_house(ctx: Context<InitHouse>, fee: u16, name: String) -> Result<()> {
-        ctx.accounts.init_house(fee, &ctx.bumps, name)?;
+    pub fn init_house(
+        ctx: Context<InitHouse>,
+        fee: u16,
+        cancel_penalty_bps: u16,
+        name: String,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_house(fee, cancel_penalty_bps, &ctx.bumps, name)?;
+        Ok(())
+    }
+
+    pub fn update_house_fee(ctx: Context<ManageHouse>, fee: u16) -> Result<()> {
+        ctx.accounts.update_house_fee(fee)?;
+        Ok(())
+    }
+
+    pub fn propose_admin(ctx: Context<ManageHouse>, pending_admin: Pubkey) -> Result<()> {
+        ctx.accounts.propose_admin(pending_admin)?;
+        Ok(())
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        ctx.accounts.accept_admin()?;
+        Ok(())
+    }
+
+    pub fn pause_house(ctx: Context<ManageHouse>, paused: bool) -> Result<()> {
+        ctx.accounts.pause_house(paused)?;
+        Ok(())
+    }
+
+    pub fn set_keeper_reward(ctx: Context<ManageHouse>, keeper_reward: KeeperReward) -> Result<()> {
+        ctx.accounts.set_keeper_reward(keeper_reward)?;
+        Ok(())
+    }
+
+    pub fn sweep_fees(ctx: Context<SweepFees>) -> Result<()> {
+        ctx.accounts.sweep_fees()?;
+        Ok(())
+    }
+
+    pub fn sweep_lamports(ctx: Context<SweepLamports>, amount: u64) -> Result<()> {
+        ctx.accounts.sweep_lamports(amount)?;
         Ok(())
     }
 
+    pub fn fund_keeper_pool(ctx: Context<ManageKeeperPool>, amount: u64) -> Result<()> {
+        ctx.accounts.fund_keeper_pool(amount)?;
+        Ok(())
+    }
+
+    pub fn withdraw_keeper_pool(ctx: Context<ManageKeeperPool>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw_keeper_pool(amount, &ctx.bumps)?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn init_auction(
         ctx: Context<InitAuction>,
+        seed: u64,
+        starting_price: u64,
+        buy_now_price: u64,
+        end_condition: EndCondition,
+        end: u64,
+        amount: u64,
+        decimal: u8,
+        extension_window: u64,
+        extension: u64,
+        max_extension: u64,
+        min_increment: u64,
+        min_increment_bps: u16,
+        reserve_price: u64,
+        reserve_hash: [u8; 32],
+        gate: Option<Gate>,
+    ) -> Result<()> {
+        ctx.accounts.init_auction(
+            seed,
+            starting_price,
+            buy_now_price,
+            end_condition,
+            end,
+            amount,
+            decimal,
+            extension_window,
+            extension,
+            max_extension,
+            min_increment,
+            min_increment_bps,
+            reserve_price,
+            reserve_hash,
+            gate,
+            &ctx.bumps,
+        )?;
+        Ok(())
+    }
+
+    pub fn reveal_reserve(
+        ctx: Context<RevealReserve>,
+        reserve_price: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts.reveal_reserve(reserve_price, salt)?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_dutch_auction(
+        ctx: Context<InitAuction>,
+        seed: u64,
+        start_price: u64,
+        floor_price: u64,
+        step: u64,
+        end_condition: EndCondition,
+        end: u64,
+        amount: u64,
+        decimal: u8,
+    ) -> Result<()> {
+        ctx.accounts.init_dutch_auction(
+            seed,
+            start_price,
+            floor_price,
+            step,
+            end_condition,
+            end,
+            amount,
+            decimal,
+            &ctx.bumps,
+        )?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_sealed_auction(
+        ctx: Context<InitAuction>,
+        seed: u64,
         starting_price: u64,
-        end: Slot,
+        end_condition: EndCondition,
+        end: u64,
+        reveal_end: u64,
+        amount: u64,
+        decimal: u8,
+        second_price: bool,
+        penalty_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts.init_sealed_auction(
+            seed,
+            starting_price,
+            end_condition,
+            end,
+            reveal_end,
+            amount,
+            decimal,
+            second_price,
+            penalty_bps,
+            &ctx.bumps,
+        )?;
+        Ok(())
+    }
+
+    pub fn init_batch_auction(
+        ctx: Context<InitAuction>,
+        seed: u64,
+        min_price: u64,
+        end_condition: EndCondition,
+        end: u64,
         amount: u64,
         decimal: u8,
+    ) -> Result<()> {
+        ctx.accounts.init_batch_auction(
+            seed,
+            min_price,
+            end_condition,
+            end,
+            amount,
+            decimal,
+            &ctx.bumps,
+        )?;
+        Ok(())
+    }
+
+    pub fn buy_now(ctx: Context<BuyNow>, max_price: u64) -> Result<()> {
+        ctx.accounts.buy_now(max_price, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn bid(
+        ctx: Context<Bid>,
+        price: u64,
+        proof: Vec<[u8; 32]>,
+        max_price: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.bid(price, max_price, &proof, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn commit_bid(ctx: Context<Bid>, commitment: [u8; 32], deposit: u64) -> Result<()> {
+        ctx.accounts.commit_bid(commitment, deposit, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn batch_bid(ctx: Context<BatchBid>, quantity: u64, price: u64) -> Result<()> {
+        ctx.accounts.batch_bid(quantity, price, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn tally_batch<'info>(ctx: Context<'_, '_, 'info, 'info, TallyBatch<'info>>) -> Result<()> {
+        ctx.accounts.tally_batch(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn settle_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleBatch<'info>>,
     ) -> Result<()> {
         ctx.accounts
-            .init_auction(starting_price, end, amount, decimal, &ctx.bumps)?;
+            .settle_batch(ctx.remaining_accounts, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn reveal_bid(ctx: Context<RevealBid>, price: u64, salt: [u8; 32]) -> Result<()> {
+        ctx.accounts.reveal_bid(price, salt)?;
+        Ok(())
+    }
+
+    pub fn penalize_bid(ctx: Context<PenalizeBid>) -> Result<()> {
+        ctx.accounts.penalize_bid()?;
         Ok(())
     }
 
-    pub fn bid(ctx: Context<Bid>, price: u64) -> Result<()> {
-        ctx.accounts.bid(price, &ctx.bumps)?;
+    pub fn raise_bid(ctx: Context<RaiseBid>, price: u64, max_price: Option<u64>) -> Result<()> {
+        ctx.accounts.raise_bid(price, max_price)?;
         Ok(())
     }
 
@@ -41,9 +257,9 @@ pub mod auction {
         Ok(())
     }
 
-    pub fn finalize(ctx: Context<Finalize>) -> Result<()> {
+    pub fn finalize<'info>(ctx: Context<'_, '_, 'info, 'info, Finalize<'info>>) -> Result<()> {
         msg!("finalize");
-        ctx.accounts.finalize()?;
+        ctx.accounts.finalize(ctx.remaining_accounts, &ctx.bumps)?;
         Ok(())
     }
 
@@ -51,4 +267,19 @@ pub mod auction {
         ctx.accounts.cancel()?;
         Ok(())
     }
+
+    pub fn cancel_expired(ctx: Context<CancelExpired>) -> Result<()> {
+        ctx.accounts.cancel_expired(&ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn cancel_early(ctx: Context<CancelEarly>) -> Result<()> {
+        ctx.accounts.cancel_early()?;
+        Ok(())
+    }
+
+    pub fn close_cancelled(ctx: Context<CloseCancelled>) -> Result<()> {
+        ctx.accounts.close_cancelled()?;
+        Ok(())
+    }
 }
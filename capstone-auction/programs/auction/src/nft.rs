@@ -0,0 +1,127 @@
+use std::ops::Deref;
+
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::mpl_token_metadata::{
+    self, accounts::Metadata, instructions::TransferV1CpiBuilder, types::TokenStandard,
+};
+
+use crate::errors::AuctionError;
+
+// Metaplex NFTs pay royalties to their creators when sold, and programmable
+// NFTs (pNFTs) sit frozen in their token accounts, so only the token metadata
+// program can move them.
+
+// the mint's Metaplex metadata, `None` for mints without any. `metadata` must
+// be the mint's metadata PDA, which callers check with a seeds constraint.
+pub(crate) fn read_metadata(metadata: &AccountInfo) -> Result<Option<Metadata>> {
+    if *metadata.owner != mpl_token_metadata::ID || metadata.data_is_empty() {
+        return Ok(None);
+    }
+    let data = metadata.try_borrow_data()?;
+    Ok(Some(Metadata::safe_deserialize(&data)?))
+}
+
+pub(crate) fn is_programmable(metadata: Option<&Metadata>) -> bool {
+    matches!(
+        metadata.and_then(|metadata| metadata.token_standard.as_ref()),
+        Some(
+            TokenStandard::ProgrammableNonFungible | TokenStandard::ProgrammableNonFungibleEdition
+        )
+    )
+}
+
+// the creators' cut of `price`, in the order of the metadata's creators and
+// leaving out those without a share. Rounding leftovers stay with the seller.
+pub(crate) fn royalties(metadata: &Metadata, price: u64) -> Result<Vec<(Pubkey, u64)>> {
+    let total = price
+        .checked_mul(u64::from(metadata.seller_fee_basis_points))
+        .ok_or(AuctionError::ArithematicOverflow)?
+        / 10_000;
+
+    let creators = metadata.creators.as_deref().unwrap_or_default();
+    creators
+        .iter()
+        .filter(|creator| creator.share > 0)
+        .map(|creator| {
+            let amount = total
+                .checked_mul(u64::from(creator.share))
+                .ok_or(AuctionError::ArithematicOverflow)?
+                / 100;
+            Ok((creator.address, amount))
+        })
+        .collect()
+}
+
+// whether `royalties` pays anyone at all
+pub(crate) fn pays_royalties(metadata: &Metadata) -> bool {
+    metadata.seller_fee_basis_points > 0
+        && metadata
+            .creators
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .any(|creator| creator.share > 0)
+}
+
+// optional pNFT accounts of an instruction, required once the mint turns out
+// to be programmable
+pub(crate) fn required<'a, 'info: 'a, T: Deref<Target = AccountInfo<'info>>>(
+    account: &'a Option<T>,
+) -> Result<&'a AccountInfo<'info>> {
+    Ok(account
+        .as_deref()
+        .ok_or(AuctionError::MissingProgrammableAccounts)?)
+}
+
+// what the token metadata program needs to move a pNFT from one token account
+// to another. The token records are those of the two token accounts.
+pub(crate) struct ProgrammableTransfer<'a, 'info> {
+    pub token_metadata_program: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub edition: &'a AccountInfo<'info>,
+    pub token_record: &'a AccountInfo<'info>,
+    pub destination_token_record: &'a AccountInfo<'info>,
+    pub authorization_rules_program: Option<&'a AccountInfo<'info>>,
+    pub authorization_rules: Option<&'a AccountInfo<'info>>,
+    pub sysvar_instructions: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ProgrammableTransfer<'a, 'info> {
+    // `token_owner` signs, with `signer_seeds` when it is a PDA
+    pub fn transfer(
+        &self,
+        token: &'a AccountInfo<'info>,
+        token_owner: &'a AccountInfo<'info>,
+        destination_token: &'a AccountInfo<'info>,
+        destination_owner: &'a AccountInfo<'info>,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        TransferV1CpiBuilder::new(self.token_metadata_program)
+            .token(token)
+            .token_owner(token_owner)
+            .destination_token(destination_token)
+            .destination_owner(destination_owner)
+            .mint(self.mint)
+            .metadata(self.metadata)
+            .edition(Some(self.edition))
+            .token_record(Some(self.token_record))
+            .destination_token_record(Some(self.destination_token_record))
+            .authority(token_owner)
+            .payer(self.payer)
+            .system_program(self.system_program)
+            .sysvar_instructions(self.sysvar_instructions)
+            .spl_token_program(self.token_program)
+            .spl_ata_program(self.associated_token_program)
+            .authorization_rules_program(self.authorization_rules_program)
+            .authorization_rules(self.authorization_rules)
+            .amount(amount)
+            .invoke_signed(signer_seeds)?;
+        Ok(())
+    }
+}
@@ -1,4 +1,8 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::errors::AuctionError;
+use crate::state::BidState;
 
 #[account]
 #[derive(InitSpace)]
@@ -6,9 +10,398 @@ pub struct Auction {
     pub seller: Pubkey,
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
+    // chosen by the seller so the same mints can be auctioned several times
+    // at once, part of the auction's seeds.
+    pub seed: u64,
     pub bump: u8,
     pub end: u64,
     pub highest_price: u64,
     pub decimal: u8,
     pub bidder: Option<Pubkey>,
+    // `end` and every other deadline below is measured in slots or unix
+    // seconds, depending on `end_condition`.
+    pub end_condition: EndCondition,
+    // anti-sniping: a bid landing within `extension_window` of `end` pushes
+    // `end` forward by `extension`, but never past `max_end`.
+    pub extension_window: u64,
+    pub extension: u64,
+    pub max_end: u64,
+    // a new bid must beat the highest price by the larger of the two.
+    pub min_increment: u64,
+    pub min_increment_bps: u16,
+    // a hidden reserve is committed as hash(reserve_price, salt) and only
+    // counts once the seller reveals it. Unrevealed by `reserve_deadline` it
+    // counts as met.
+    pub reserve_price: u64,
+    pub reserve_hash: Option<[u8; 32]>,
+    pub kind: AuctionKind,
+    // a bid at or above this price ends an english auction right away.
+    pub buy_now_price: Option<u64>,
+    // english auctions only take bids from bidders who get past the gate.
+    pub gate: Option<Gate>,
+    // the highest bidder's proxy ceiling, see `place_bid`.
+    pub proxy_max: Option<u64>,
+    // set by `cancel_early`. The lot is back with the seller and the account
+    // stays so every bidder can still `withdraw`, the highest one included.
+    pub cancelled: bool,
+    // bid states not closed yet. A cancelled auction is closed once this is
+    // back to zero, see `close_cancelled`.
+    pub open_bids: u32,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum Gate {
+    // the root of a merkle tree over the allowed bidders, bidders pass their
+    // proof, see `allowlist_leaf` and `allowlist_node`.
+    Allowlist { root: [u8; 32] },
+    // bidders hold at least `min_amount` of `mint`.
+    Token { mint: Pubkey, min_amount: u64 },
+    // bidders hold an NFT of this verified Metaplex collection.
+    Collection { collection: Pubkey },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum AuctionKind {
+    English,
+    // the price falls linearly from `start_price` at `start` to
+    // `floor_price` at `end`, stepping down every `step`.
+    Dutch {
+        start_price: u64,
+        floor_price: u64,
+        start: u64,
+        step: u64,
+    },
+    // bids are committed until `end` and revealed until `reveal_end`.
+    // `second_highest` starts at the starting price and tracks the runner-up
+    // reveal, which the winner pays when `second_price` is set. `unrevealed`
+    // counts the commitments not yet revealed, penalized or withdrawn, the
+    // auction only settles once it is back to zero.
+    Sealed {
+        reveal_end: u64,
+        second_price: bool,
+        second_highest: u64,
+        penalty_bps: u16,
+        unrevealed: u32,
+    },
+    // uniform-price auction of `supply` units. Bids form a list ordered by
+    // price starting at `head`, ties in arrival order. After `end` the list is
+    // walked from `cursor` twice: once to find `clearing_price`, then again
+    // with `filling` set to hand out the units. `unfilled` counts the units
+    // not yet handed out in the current walk.
+    Batch {
+        supply: u64,
+        head: Option<Pubkey>,
+        cursor: Option<Pubkey>,
+        unfilled: u64,
+        clearing_price: u64,
+        filling: bool,
+    },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum EndCondition {
+    Slot,
+    UnixTimestamp,
+}
+
+impl EndCondition {
+    pub fn now(&self) -> Result<u64> {
+        let clock = Clock::get()?;
+        Ok(match self {
+            EndCondition::Slot => clock.slot,
+            EndCondition::UnixTimestamp => u64::try_from(clock.unix_timestamp).unwrap_or_default(),
+        })
+    }
+
+    // how long after settling a seller has to reveal a hidden reserve, about an hour.
+    pub fn reserve_reveal_window(&self) -> u64 {
+        match self {
+            EndCondition::Slot => 9_000,
+            EndCondition::UnixTimestamp => 3_600,
+        }
+    }
+}
+
+impl Auction {
+    pub fn now(&self) -> Result<u64> {
+        self.end_condition.now()
+    }
+
+    // shared by `bid` and `raise_bid`. A `max_price` makes it a proxy bid:
+    // rivals bidding up to it are outbid right away on the bidder's behalf,
+    // just above their own ceiling. Returns whether the bidder leads.
+    pub fn place_bid(
+        &mut self,
+        bidder: Pubkey,
+        price: u64,
+        max_price: Option<u64>,
+        now: u64,
+    ) -> Result<bool> {
+        require!(
+            self.kind == AuctionKind::English,
+            AuctionError::WrongAuctionKind
+        );
+        require!(now < self.end, AuctionError::AuctionEnded);
+        let ceiling = max_price.unwrap_or(price);
+        require!(ceiling >= price, AuctionError::MaxPriceTooLow);
+
+        // a leading proxy only moves its ceiling, the price is left to rivals
+        if let Some(max_price) = max_price.filter(|_| self.bidder == Some(bidder)) {
+            require!(
+                max_price >= self.highest_price,
+                AuctionError::MaxPriceTooLow
+            );
+            self.proxy_max = Some(max_price);
+            return Ok(true);
+        }
+
+        let min_bid = self.min_bid().ok_or(AuctionError::ArithematicOverflow)?;
+        require!(price >= min_bid, AuctionError::PriceTooLow);
+
+        let leader_max = match self.bidder {
+            Some(leader) if leader != bidder => self.proxy_max,
+            _ => None,
+        };
+        match leader_max {
+            // ties go to the earlier bid
+            Some(leader_max) if ceiling <= leader_max => {
+                let price = self
+                    .outbid(ceiling)
+                    .map_or(leader_max, |price| price.min(leader_max));
+                self.raise_to(price, now);
+                Ok(false)
+            }
+            _ => {
+                // beating a proxy takes no more than just above its ceiling
+                let price = leader_max
+                    .and_then(|leader_max| self.outbid(leader_max))
+                    .map_or(price, |outbid| outbid.clamp(price, ceiling));
+                self.bidder = Some(bidder);
+                self.proxy_max = max_price;
+                self.raise_to(price, now);
+                Ok(true)
+            }
+        }
+    }
+
+    fn raise_to(&mut self, price: u64, now: u64) {
+        self.highest_price = price;
+        match self.buy_now_price {
+            Some(buy_now_price) if price >= buy_now_price => self.end = now,
+            _ => self.extend_end(now),
+        }
+    }
+
+    pub fn extend_end(&mut self, now: u64) {
+        if self.extension_window == 0 || self.end.saturating_sub(now) > self.extension_window {
+            return;
+        }
+        self.end = self.end.saturating_add(self.extension).min(self.max_end);
+    }
+
+    // the lowest price the next bid may offer.
+    pub fn min_bid(&self) -> Option<u64> {
+        if self.bidder.is_none() {
+            // highest_price holds starting_price - 1 until the first bid.
+            return self.highest_price.checked_add(1);
+        }
+        self.outbid(self.highest_price)
+    }
+
+    // the lowest price beating `price` by the minimum increment.
+    pub fn outbid(&self, price: u64) -> Option<u64> {
+        let increment_bps = u128::from(price)
+            .checked_mul(u128::from(self.min_increment_bps))?
+            .checked_div(10_000)?;
+        let increment = u64::try_from(increment_bps)
+            .ok()?
+            .max(self.min_increment)
+            .max(1);
+        price.checked_add(increment)
+    }
+
+    // unrevealed sealed bids count, they still owe the seller a penalty.
+    pub fn has_bids(&self) -> bool {
+        self.bidder.is_some()
+            || self.unrevealed_bids() > 0
+            || matches!(self.kind, AuctionKind::Batch { head: Some(_), .. })
+    }
+
+    pub fn unrevealed_bids(&self) -> u32 {
+        match self.kind {
+            AuctionKind::Sealed { unrevealed, .. } => unrevealed,
+            _ => 0,
+        }
+    }
+
+    // a bid state was created.
+    pub fn open_bid(&mut self) -> Result<()> {
+        self.open_bids = self
+            .open_bids
+            .checked_add(1)
+            .ok_or(AuctionError::ArithematicOverflow)?;
+        Ok(())
+    }
+
+    // a bid state was closed.
+    pub fn close_bid(&mut self) {
+        self.open_bids = self.open_bids.saturating_sub(1);
+    }
+
+    pub fn commit_bid(&mut self) -> Result<()> {
+        let AuctionKind::Sealed {
+            ref mut unrevealed, ..
+        } = self.kind
+        else {
+            return err!(AuctionError::WrongAuctionKind);
+        };
+        *unrevealed = unrevealed
+            .checked_add(1)
+            .ok_or(AuctionError::ArithematicOverflow)?;
+        Ok(())
+    }
+
+    // a commitment was revealed, penalized or withdrawn.
+    pub fn uncommit_bid(&mut self) {
+        if let AuctionKind::Sealed {
+            ref mut unrevealed, ..
+        } = self.kind
+        {
+            *unrevealed = unrevealed.saturating_sub(1);
+        }
+    }
+
+    pub fn reserve_met(&self, now: u64) -> bool {
+        match self.reserve_hash {
+            // withholding the reserve doesn't let the seller back out
+            Some(_) => now >= self.reserve_deadline(),
+            None => self.highest_price >= self.reserve_price,
+        }
+    }
+
+    pub fn reserve_deadline(&self) -> u64 {
+        self.settles_at()
+            .saturating_add(self.end_condition.reserve_reveal_window())
+    }
+
+    // from when on the auction can be finalized: a hidden reserve gets until
+    // its deadline to be revealed.
+    pub fn finalizes_at(&self) -> u64 {
+        match self.reserve_hash {
+            Some(_) => self.reserve_deadline(),
+            None => self.settles_at(),
+        }
+    }
+
+    // from when on the auction can be finalized or cancelled.
+    pub fn settles_at(&self) -> u64 {
+        match self.kind {
+            AuctionKind::Sealed { reveal_end, .. } => reveal_end,
+            _ => self.end,
+        }
+    }
+
+    // the price the winner pays.
+    pub fn settlement_price(&self) -> u64 {
+        match self.kind {
+            AuctionKind::Sealed {
+                second_price: true,
+                second_highest,
+                ..
+            } => second_highest.min(self.highest_price),
+            _ => self.highest_price,
+        }
+    }
+
+    pub fn reveal_bid(&mut self, bidder: Pubkey, price: u64, now: u64) -> Result<()> {
+        let AuctionKind::Sealed {
+            reveal_end,
+            ref mut second_highest,
+            ref mut unrevealed,
+            ..
+        } = self.kind
+        else {
+            return err!(AuctionError::WrongAuctionKind);
+        };
+        require!(
+            now >= self.end && now < reveal_end,
+            AuctionError::NotInRevealPhase
+        );
+        *unrevealed = unrevealed.saturating_sub(1);
+
+        // ties go to whoever revealed first
+        if price > self.highest_price {
+            if self.bidder.is_some() {
+                *second_highest = (*second_highest).max(self.highest_price);
+            }
+            self.bidder = Some(bidder);
+            self.highest_price = price;
+        } else if self.bidder.is_some() {
+            *second_highest = (*second_highest).max(price);
+        }
+        Ok(())
+    }
+
+    // hands the bid at the batch cursor as much of what is left in the current
+    // walk as it asked for and moves the cursor on. Returns the units filled.
+    pub fn fill_batch_bid(&mut self, bid_state: Pubkey, bid: &BidState) -> Result<u64> {
+        let AuctionKind::Batch {
+            ref mut cursor,
+            ref mut unfilled,
+            ..
+        } = self.kind
+        else {
+            return err!(AuctionError::WrongAuctionKind);
+        };
+        require!(*cursor == Some(bid_state), AuctionError::InvalidBidOrder);
+
+        let fill = bid.quantity.min(*unfilled);
+        *unfilled -= fill;
+        *cursor = if *unfilled == 0 { None } else { bid.next };
+        Ok(fill)
+    }
+
+    // the price a dutch auction can be bought at right `now`.
+    pub fn dutch_price(&self, now: u64) -> Option<u64> {
+        let AuctionKind::Dutch {
+            start_price,
+            floor_price,
+            start,
+            step,
+        } = self.kind
+        else {
+            return None;
+        };
+
+        let duration = self.end.checked_sub(start)?;
+        let elapsed = now.saturating_sub(start).min(duration);
+        let elapsed = elapsed - elapsed % step.max(1);
+        let decay = u128::from(start_price.checked_sub(floor_price)?)
+            .checked_mul(u128::from(elapsed))?
+            .checked_div(u128::from(duration))?;
+        start_price.checked_sub(u64::try_from(decay).ok()?)
+    }
+}
+
+pub fn reserve_hash(reserve_price: u64, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[&reserve_price.to_le_bytes(), salt]).to_bytes()
+}
+
+// leaves and inner nodes are hashed with different prefixes, so an inner node
+// cannot pass for a bidder.
+pub fn allowlist_leaf(bidder: &Pubkey) -> [u8; 32] {
+    hashv(&[&[0], bidder.as_ref()]).to_bytes()
+}
+
+// the children are sorted, so proofs need no left or right flags.
+pub fn allowlist_node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[&[1], left, right]).to_bytes()
+}
+
+pub fn verify_allowlist(root: &[u8; 32], bidder: &Pubkey, proof: &[[u8; 32]]) -> bool {
+    let node = proof.iter().fold(allowlist_leaf(bidder), |node, sibling| {
+        allowlist_node(&node, sibling)
+    });
+    node == *root
 }
@@ -5,7 +5,24 @@ use anchor_lang::prelude::*;
 pub struct AuctionHouse {
     pub admin: Pubkey,
     pub fee: u16,
+    // share of the highest bid a seller pays the highest bidder to cancel early
+    pub cancel_penalty_bps: u16,
     pub bump: u8,
+    // set by `propose_admin` until the new admin accepts
+    pub pending_admin: Option<Pubkey>,
+    // a paused house takes no new auctions or bids
+    pub paused: bool,
+    // paid to whoever settles an ended auction
+    pub keeper_reward: KeeperReward,
     #[max_len(32)]
     pub name: String,
 }
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum KeeperReward {
+    None,
+    // share of the house fee in basis points, paid in the purchase mint
+    FeeShare { bps: u16 },
+    // fixed tip out of the house's keeper pool, see `fund_keeper_pool`
+    Lamports { amount: u64 },
+}
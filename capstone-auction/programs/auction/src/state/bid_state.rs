@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
 
 #[account]
 #[derive(Debug, InitSpace)]
@@ -6,4 +7,21 @@ pub struct BidState {
     pub bidder: Pubkey,
     pub auction: Pubkey, // make sure the bidder withdraw from the correct auction
     pub bump: u8,
+    pub price: u64, // latest price offered, escrow holds enough to cover it
+    pub commitment: Option<[u8; 32]>, // sealed bid not yet revealed
+    pub quantity: u64, // batch auctions: units of mint_a wanted at `price`
+    pub next: Option<Pubkey>, // batch auctions: the bid ranked below this one
+    pub max_price: Option<u64>, // proxy bids: the ceiling, escrow covers it instead of `price`
+}
+
+impl BidState {
+    // the price the escrow has to cover
+    pub fn covered_price(&self) -> u64 {
+        self.max_price.unwrap_or(self.price)
+    }
+}
+
+// the bidder is part of the commitment so it cannot be copied by someone else.
+pub fn bid_commitment(price: u64, salt: &[u8; 32], bidder: &Pubkey) -> [u8; 32] {
+    hashv(&[&price.to_le_bytes(), salt, bidder.as_ref()]).to_bytes()
 }
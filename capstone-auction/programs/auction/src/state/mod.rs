@@ -6,3 +6,6 @@ pub use auction::*;
 
 pub mod bid_state;
 pub use bid_state::*;
+
+pub mod user_stats;
+pub use user_stats::*;
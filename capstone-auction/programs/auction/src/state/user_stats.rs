@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+
+// A wallet's history in a house, kept after its auctions and bids are closed.
+// Sellers open theirs with their first auction, bidders with their first bid
+// or purchase. Batch auctions, settled bid by bid, only count towards
+// `auctions_created`.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct UserStats {
+    pub wallet: Pubkey,
+    pub auction_house: Pubkey,
+    pub bump: u8,
+    pub auctions_created: u64,
+    // ended in a sale
+    pub auctions_completed: u64,
+    // closed unsold by the seller or a keeper, see `cancel`, `cancel_early`
+    // and `cancel_expired`
+    pub auctions_cancelled: u64,
+    pub bids_placed: u64,
+    pub auctions_won: u64,
+    // bought and sold, per quote mint. Starts with room for one mint and
+    // grows when the wallet first trades in another, see `open_stats`.
+    #[max_len(1)]
+    pub volume: Vec<MintVolume>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct MintVolume {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+// counters saturate, keeping score must never fail a trade
+impl UserStats {
+    pub fn is_open(&self) -> bool {
+        self.wallet != Pubkey::default()
+    }
+
+    // the account size once `mint` has an entry
+    pub fn space_with(&self, mint: &Pubkey) -> usize {
+        let mints = self.volume.len() + usize::from(self.volume(mint).is_none());
+        8 + UserStats::INIT_SPACE + mints.saturating_sub(1) * MintVolume::INIT_SPACE
+    }
+
+    pub fn volume(&self, mint: &Pubkey) -> Option<u64> {
+        self.volume
+            .iter()
+            .find(|volume| volume.mint == *mint)
+            .map(|volume| volume.amount)
+    }
+
+    // only mints `open_stats` made room for are counted
+    pub fn add_volume(&mut self, mint: &Pubkey, amount: u64) {
+        if let Some(volume) = self.volume.iter_mut().find(|volume| volume.mint == *mint) {
+            volume.amount = volume.amount.saturating_add(amount);
+        }
+    }
+
+    pub fn record_created(&mut self) {
+        self.auctions_created = self.auctions_created.saturating_add(1);
+    }
+
+    pub fn record_completed(&mut self) {
+        self.auctions_completed = self.auctions_completed.saturating_add(1);
+    }
+
+    pub fn record_cancelled(&mut self) {
+        self.auctions_cancelled = self.auctions_cancelled.saturating_add(1);
+    }
+
+    pub fn record_bid(&mut self) {
+        self.bids_placed = self.bids_placed.saturating_add(1);
+    }
+
+    pub fn record_won(&mut self) {
+        self.auctions_won = self.auctions_won.saturating_add(1);
+    }
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::state::{MintVolume, UserStats};
+
+// Readies `stats`, created with `init_if_needed`, for a wallet trading in
+// `mint`: a new account gets its owner, and `payer` pays for the room of a
+// mint the wallet hasn't traded in before. Later instructions of the auction
+// only update what is already there.
+pub(crate) fn open_stats<'info>(
+    stats: &mut Account<'info, UserStats>,
+    wallet: &Pubkey,
+    auction_house: &Pubkey,
+    bump: u8,
+    mint: &Pubkey,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if !stats.is_open() {
+        stats.wallet = *wallet;
+        stats.auction_house = *auction_house;
+        stats.bump = bump;
+    }
+    if stats.volume(mint).is_some() {
+        return Ok(());
+    }
+
+    let space = stats.space_with(mint);
+    let info = stats.to_account_info();
+    if space > info.data_len() {
+        let rent = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(info.lamports());
+        if rent > 0 {
+            let transfer_accounts = Transfer {
+                from: payer.to_account_info(),
+                to: info.clone(),
+            };
+            transfer(
+                CpiContext::new(system_program.to_account_info(), transfer_accounts),
+                rent,
+            )?;
+        }
+        info.realloc(space, false)?;
+    }
+    stats.volume.push(MintVolume {
+        mint: *mint,
+        amount: 0,
+    });
+    Ok(())
+}
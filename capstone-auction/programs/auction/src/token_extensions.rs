@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        non_transferable::NonTransferable,
+        permanent_delegate::PermanentDelegate,
+        transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+};
+use anchor_spl::token_2022_extensions::{
+    harvest_withheld_tokens_to_mint, HarvestWithheldTokensToMint,
+};
+use anchor_spl::token_interface::Mint;
+
+use crate::errors::AuctionError;
+
+// Token-2022 mints can carry extensions that change what a transfer does. Plain
+// SPL mints have none, so everything here is a no-op for them.
+
+// rejects mints the auction can't hold safely: non-transferable tokens can't
+// leave the vault or escrow, and a permanent delegate can take them out.
+pub(crate) fn check_mint(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+
+    require!(
+        mint.get_extension::<NonTransferable>().is_err(),
+        AuctionError::NonTransferableMint
+    );
+    if let Ok(permanent_delegate) = mint.get_extension::<PermanentDelegate>() {
+        require!(
+            Option::<Pubkey>::from(permanent_delegate.delegate).is_none(),
+            AuctionError::PermanentDelegateMint
+        );
+    }
+    Ok(())
+}
+
+// the amount to send so that `amount` arrives after the mint's transfer fee
+pub(crate) fn amount_with_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(amount);
+    }
+    let data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(amount);
+    };
+
+    let fee = transfer_fee_config
+        .calculate_inverse_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or(AuctionError::ArithematicOverflow)?;
+    Ok(amount
+        .checked_add(fee)
+        .ok_or(AuctionError::ArithematicOverflow)?)
+}
+
+// transfer fees are withheld in the receiving account and block closing it,
+// so they are moved to the mint first. `mint` has to be writable.
+pub(crate) fn harvest_withheld<'info>(
+    token_program: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    account: AccountInfo<'info>,
+) -> Result<()> {
+    if *account.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let withheld = {
+        let data = account.try_borrow_data()?;
+        let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+        account
+            .get_extension::<TransferFeeAmount>()
+            .map_or(0, |fee_amount| u64::from(fee_amount.withheld_amount))
+    };
+    if withheld == 0 {
+        return Ok(());
+    }
+
+    harvest_withheld_tokens_to_mint(
+        CpiContext::new(
+            token_program.clone(),
+            HarvestWithheldTokensToMint {
+                token_program_id: token_program,
+                mint,
+            },
+        ),
+        vec![account],
+    )
+}
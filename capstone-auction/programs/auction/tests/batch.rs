@@ -0,0 +1,256 @@
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use auction::errors::AuctionError;
+use auction::state::{Auction, AuctionKind, EndCondition, KeeperReward};
+use common::*;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const FUNDS: u64 = 1_000_000;
+const MIN_PRICE: u64 = 1_000_000;
+
+// AMOUNT units for sale until slot 100
+fn batch() -> auction::instruction::InitBatchAuction {
+    auction::instruction::InitBatchAuction {
+        seed: 0,
+        min_price: MIN_PRICE,
+        end_condition: EndCondition::Slot,
+        end: 100,
+        amount: AMOUNT,
+        decimal: DECIMALS,
+    }
+}
+
+fn started(env: &mut Env) -> (Market, [Keypair; 3]) {
+    let market = Market::new(env);
+    market.init(env, batch()).unwrap();
+    let bidders = [(); 3].map(|_| market.bidder(env, FUNDS));
+    (market, bidders)
+}
+
+#[test]
+fn batch_bids_are_checked() {
+    let mut env = Env::new();
+    let (market, [bidder, _, _]) = started(&mut env);
+
+    assert_error(
+        market.bid(&mut env, &bidder, MIN_PRICE),
+        AuctionError::WrongAuctionKind,
+    );
+    assert_error(
+        market.batch_bid(&mut env, &bidder, 10, MIN_PRICE - 1, None, None),
+        AuctionError::PriceTooLow,
+    );
+    assert_error(
+        market.batch_bid(&mut env, &bidder, 0, MIN_PRICE, None, None),
+        AuctionError::InvalidQuantity,
+    );
+    assert_error(
+        market.batch_bid(&mut env, &bidder, AMOUNT + 1, MIN_PRICE, None, None),
+        AuctionError::InvalidQuantity,
+    );
+
+    env.warp_to(100);
+    assert_error(
+        market.batch_bid(&mut env, &bidder, 10, MIN_PRICE, None, None),
+        AuctionError::AuctionEnded,
+    );
+}
+
+#[test]
+fn bids_must_be_linked_in_price_order() {
+    let mut env = Env::new();
+    let (market, [bidder, bidder2, bidder3]) = started(&mut env);
+    let (first, second) = (bidder.pubkey(), bidder2.pubkey());
+
+    market
+        .batch_bid(&mut env, &bidder, 100, 3_000_000, None, None)
+        .unwrap();
+    // a lower bid can't take the head
+    assert_error(
+        market.batch_bid(&mut env, &bidder2, 100, 2_000_000, None, Some(&first)),
+        AuctionError::InvalidBidOrder,
+    );
+    // ties go after the earlier bid
+    assert_error(
+        market.batch_bid(&mut env, &bidder2, 100, 3_000_000, None, Some(&first)),
+        AuctionError::InvalidBidOrder,
+    );
+    market
+        .batch_bid(&mut env, &bidder2, 100, 3_000_000, Some(&first), None)
+        .unwrap();
+    // the successor has to be the one the predecessor points at
+    assert_error(
+        market.batch_bid(&mut env, &bidder3, 100, 2_000_000, Some(&first), None),
+        AuctionError::InvalidBidOrder,
+    );
+    market
+        .batch_bid(&mut env, &bidder3, 100, 2_000_000, Some(&second), None)
+        .unwrap();
+
+    let auction: Auction = env.account(&market.auction());
+    assert!(matches!(
+        auction.kind,
+        AuctionKind::Batch { head: Some(head), .. } if head == market.bid_state(&first)
+    ));
+}
+
+#[test]
+fn units_go_to_the_best_bids_at_the_clearing_price() {
+    let mut env = Env::new();
+    let (market, [bidder, bidder2, bidder3]) = started(&mut env);
+    let keeper = env.user();
+    let seller = market.seller.pubkey();
+    let [first, second, third]: [Pubkey; 3] =
+        [&bidder, &bidder2, &bidder3].map(|bidder| bidder.pubkey());
+
+    // ranked first, third, second
+    market
+        .batch_bid(&mut env, &bidder, 600, 3_000_000, None, None)
+        .unwrap();
+    market
+        .batch_bid(&mut env, &bidder2, 300, 2_000_000, Some(&first), None)
+        .unwrap();
+    market
+        .batch_bid(
+            &mut env,
+            &bidder3,
+            400,
+            2_500_000,
+            Some(&first),
+            Some(&second),
+        )
+        .unwrap();
+
+    assert_error(
+        market.tally_batch(&mut env, &keeper, &[first]),
+        AuctionError::AuctionNotEnded,
+    );
+    env.warp_to(100);
+    assert_error(
+        market.settle_batch(&mut env, &keeper, &[first]),
+        AuctionError::WrongSettlementPhase,
+    );
+    assert_error(
+        market.tally_batch(&mut env, &keeper, &[third]),
+        AuctionError::InvalidBidOrder,
+    );
+    assert_error(
+        market.withdraw(&mut env, &bidder2),
+        AuctionError::NotEligibleToWithdraw,
+    );
+
+    // the supply runs out with the third bid, which sets the price
+    market
+        .tally_batch(&mut env, &keeper, &[first, third])
+        .unwrap();
+    let auction: Auction = env.account(&market.auction());
+    assert!(matches!(
+        auction.kind,
+        AuctionKind::Batch {
+            clearing_price: 2_500_000,
+            filling: true,
+            ..
+        }
+    ));
+    assert_error(
+        market.tally_batch(&mut env, &keeper, &[first]),
+        AuctionError::WrongSettlementPhase,
+    );
+
+    market.settle_batch(&mut env, &keeper, &[first]).unwrap();
+    market.settle_batch(&mut env, &keeper, &[third]).unwrap();
+    assert!(!env.exists(&market.auction()));
+    assert!(!env.exists(&market.vault()));
+
+    assert_eq!(env.token_balance(&market.ata(&first, &market.mint_a)), 600);
+    assert_eq!(env.token_balance(&market.ata(&third, &market.mint_a)), 400);
+    // 1500 + 1000 less the 1% fee on each fill
+    assert_eq!(
+        env.token_balance(&market.ata(&seller, &market.mint_b)),
+        2475
+    );
+    assert_eq!(env.token_balance(&market.treasury()), 25);
+
+    // what the clearing price left in the escrows goes back
+    for bidder in [&bidder, &bidder2, &bidder3] {
+        market.withdraw(&mut env, bidder).unwrap();
+    }
+    assert_eq!(
+        env.token_balance(&market.ata(&first, &market.mint_b)),
+        FUNDS - 1500
+    );
+    assert_eq!(
+        env.token_balance(&market.ata(&second, &market.mint_b)),
+        FUNDS
+    );
+    assert_eq!(
+        env.token_balance(&market.ata(&third, &market.mint_b)),
+        FUNDS - 1000
+    );
+}
+
+#[test]
+fn unsold_units_go_back_to_the_seller() {
+    let mut env = Env::new();
+    let (market, [bidder, _, _]) = started(&mut env);
+    let keeper = env.user();
+    let seller = market.seller.pubkey();
+    let seller_tokens = env.token_balance(&market.ata(&seller, &market.mint_a));
+
+    market
+        .batch_bid(&mut env, &bidder, 100, 2_000_000, None, None)
+        .unwrap();
+    env.warp_to(100);
+    market
+        .tally_batch(&mut env, &keeper, &[bidder.pubkey()])
+        .unwrap();
+    market
+        .settle_batch(&mut env, &keeper, &[bidder.pubkey()])
+        .unwrap();
+
+    assert_eq!(
+        env.token_balance(&market.ata(&seller, &market.mint_a)),
+        seller_tokens + AMOUNT - 100
+    );
+    assert!(!env.exists(&market.auction()));
+}
+
+#[test]
+fn keepers_get_a_share_of_the_batch_fees() {
+    let mut env = Env::new();
+    let (market, [bidder, _, _]) = started(&mut env);
+    let admin = market.admin.pubkey();
+    let ix = market.manage_ix(
+        &admin,
+        auction::instruction::SetKeeperReward {
+            keeper_reward: KeeperReward::FeeShare { bps: 5000 },
+        },
+    );
+    env.send(&[ix], &market.admin, &[]).unwrap();
+
+    market
+        .batch_bid(&mut env, &bidder, 100, 2_000_000, None, None)
+        .unwrap();
+    env.warp_to(100);
+    let keeper = env.user();
+    market
+        .tally_batch(&mut env, &keeper, &[bidder.pubkey()])
+        .unwrap();
+
+    let keeper_ata = env.mint_to(
+        &market.mint_b,
+        &market.mint_authority,
+        &keeper.pubkey(),
+        0,
+        &market.token_program,
+    );
+    let ix = market.settle_batch_ix(&keeper.pubkey(), Some(keeper_ata), &[bidder.pubkey()]);
+    env.send(&[ix], &keeper, &[]).unwrap();
+    assert!(env.token_balance(&keeper_ata) > 0);
+    assert_eq!(
+        env.token_balance(&keeper_ata),
+        env.token_balance(&market.treasury())
+    );
+}
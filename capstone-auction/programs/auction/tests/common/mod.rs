@@ -0,0 +1,1111 @@
+// every test binary uses a different part of the harness
+#![allow(dead_code)]
+// `TxResult` keeps litesvm's failed transaction as is, its logs are what
+// `assert_error` prints
+#![allow(clippy::result_large_err)]
+
+use anchor_lang::{
+    prelude::Pubkey, system_program, AccountDeserialize, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{
+    associated_token::{
+        get_associated_token_address_with_program_id,
+        spl_associated_token_account::{
+            self, instruction::create_associated_token_account_idempotent,
+        },
+    },
+    metadata::mpl_token_metadata,
+    token::spl_token,
+    token_2022::spl_token_2022::{
+        self,
+        extension::{ExtensionType, StateWithExtensions},
+        solana_program::program_pack::Pack,
+    },
+};
+use auction::errors::AuctionError;
+use auction::state::bid_commitment;
+use litesvm::{
+    types::{FailedTransactionMetadata, TransactionMetadata},
+    LiteSVM,
+};
+use solana_sdk::{
+    account::Account,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    signature::Keypair,
+    signer::Signer,
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+
+// The compiled program running in an in-process SVM, with builders for its
+// instructions. `anchor build` has to produce target/deploy/auction.so first,
+// the tests only build with the `test-sbf` feature.
+
+pub const PROGRAM_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../target/deploy/auction.so"
+);
+
+pub const HOUSE_FEE: u16 = 100;
+pub const CANCEL_PENALTY_BPS: u16 = 500;
+
+pub const DECIMALS: u8 = 6;
+// 1000 units at 2 mint_b each, with the price given in 6 decimals
+pub const AMOUNT: u64 = 1000;
+pub const PRICE: u64 = 2_000_000;
+
+pub type TxResult = Result<TransactionMetadata, FailedTransactionMetadata>;
+
+pub struct Env {
+    pub svm: LiteSVM,
+}
+
+impl Default for Env {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Env {
+    pub fn new() -> Self {
+        let mut svm = LiteSVM::new();
+        svm.add_program_from_file(auction::ID, PROGRAM_PATH)
+            .expect("run `anchor build` before `cargo test --features test-sbf`");
+        let mut env = Self { svm };
+        env.set_native_mint();
+        env
+    }
+
+    // native SOL auctions use the wrapped SOL mint as mint_b
+    fn set_native_mint(&mut self) {
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: None.into(),
+            supply: 0,
+            decimals: 9,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        }
+        .pack_into_slice(&mut data);
+        self.set_account(spl_token::native_mint::ID, spl_token::ID, data);
+    }
+
+    pub fn set_account(&mut self, address: Pubkey, owner: Pubkey, data: Vec<u8>) {
+        let lamports = self.svm.minimum_balance_for_rent_exemption(data.len());
+        let _ = self.svm.set_account(
+            address,
+            Account {
+                lamports,
+                data,
+                owner,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    // a funded wallet
+    pub fn user(&mut self) -> Keypair {
+        let user = Keypair::new();
+        self.svm
+            .airdrop(&user.pubkey(), 100 * LAMPORTS_PER_SOL)
+            .unwrap();
+        user
+    }
+
+    pub fn send(&mut self, ixs: &[Instruction], payer: &Keypair, signers: &[&Keypair]) -> TxResult {
+        // finalize makes a lot of CPIs
+        let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(1_400_000)];
+        instructions.extend_from_slice(ixs);
+        let mut all_signers = vec![payer];
+        all_signers.extend_from_slice(signers);
+
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &all_signers,
+            self.svm.latest_blockhash(),
+        );
+        // a new blockhash per transaction, so retrying one is not a duplicate
+        let result = self.svm.send_transaction(tx);
+        self.svm.expire_blockhash();
+        result
+    }
+
+    pub fn slot(&self) -> u64 {
+        self.svm.get_sysvar::<solana_sdk::clock::Clock>().slot
+    }
+
+    pub fn warp_to(&mut self, slot: u64) {
+        self.svm.warp_to_slot(slot);
+    }
+
+    pub fn exists(&self, address: &Pubkey) -> bool {
+        self.svm
+            .get_account(address)
+            .is_some_and(|account| account.lamports > 0)
+    }
+
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.svm
+            .get_account(address)
+            .map_or(0, |account| account.lamports)
+    }
+
+    pub fn account<T: AccountDeserialize>(&self, address: &Pubkey) -> T {
+        let account = self.svm.get_account(address).expect("account not found");
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub fn token_balance(&self, address: &Pubkey) -> u64 {
+        let Some(account) = self.svm.get_account(address) else {
+            return 0;
+        };
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+            .map_or(0, |account| account.base.amount)
+    }
+
+    // `init` initializes the `extensions`, it runs before the mint itself
+    pub fn create_mint(
+        &mut self,
+        authority: &Keypair,
+        decimals: u8,
+        token_program: &Pubkey,
+        extensions: &[ExtensionType],
+        init: impl FnOnce(&Pubkey) -> Vec<Instruction>,
+    ) -> Pubkey {
+        let mint = Keypair::new();
+        let space = if *token_program == spl_token::ID {
+            spl_token::state::Mint::LEN
+        } else {
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(extensions)
+                .unwrap()
+        };
+        let lamports = self.svm.minimum_balance_for_rent_exemption(space);
+
+        let mut ixs = vec![system_instruction::create_account(
+            &authority.pubkey(),
+            &mint.pubkey(),
+            lamports,
+            space as u64,
+            token_program,
+        )];
+        ixs.extend(init(&mint.pubkey()));
+        ixs.push(
+            spl_token_2022::instruction::initialize_mint2(
+                token_program,
+                &mint.pubkey(),
+                &authority.pubkey(),
+                None,
+                decimals,
+            )
+            .unwrap(),
+        );
+        self.send(&ixs, authority, &[&mint]).unwrap();
+        mint.pubkey()
+    }
+
+    // mints `amount` into the owner's ATA, creating it if needed
+    pub fn mint_to(
+        &mut self,
+        mint: &Pubkey,
+        authority: &Keypair,
+        owner: &Pubkey,
+        amount: u64,
+        token_program: &Pubkey,
+    ) -> Pubkey {
+        let ata = get_associated_token_address_with_program_id(owner, mint, token_program);
+        let ixs = [
+            create_associated_token_account_idempotent(
+                &authority.pubkey(),
+                owner,
+                mint,
+                token_program,
+            ),
+            spl_token_2022::instruction::mint_to(
+                token_program,
+                mint,
+                &ata,
+                &authority.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap(),
+        ];
+        self.send(&ixs, authority, &[]).unwrap();
+        ata
+    }
+}
+
+pub fn assert_error(result: TxResult, error: AuctionError) {
+    let failed = result.expect_err("transaction should fail");
+    assert!(
+        matches!(
+            failed.err,
+            TransactionError::InstructionError(_, InstructionError::Custom(code))
+                if code == u32::from(error)
+        ),
+        "expected {error:?}, got {:?}\n{}",
+        failed.err,
+        failed.meta.logs.join("\n"),
+    );
+}
+
+pub fn house_address(name: &str) -> Pubkey {
+    Pubkey::find_program_address(&[b"house", name.as_bytes()], &auction::ID).0
+}
+
+pub fn auction_address(
+    house: &Pubkey,
+    seller: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    seed: u64,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"auction",
+            house.as_ref(),
+            seller.as_ref(),
+            mint_a.as_ref(),
+            mint_b.as_ref(),
+            &seed.to_le_bytes(),
+        ],
+        &auction::ID,
+    )
+    .0
+}
+
+pub fn bid_address(auction: &Pubkey, bidder: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"bid", auction.as_ref(), bidder.as_ref()], &auction::ID).0
+}
+
+pub fn stats_address(house: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"stats", house.as_ref(), wallet.as_ref()], &auction::ID).0
+}
+
+pub fn treasury_address(house: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"treasury", house.as_ref(), mint.as_ref()], &auction::ID).0
+}
+
+pub fn keeper_pool_address(house: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"keeper_pool", house.as_ref()], &auction::ID).0
+}
+
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"metadata", mpl_token_metadata::ID.as_ref(), mint.as_ref()],
+        &mpl_token_metadata::ID,
+    )
+    .0
+}
+
+pub fn init_house_ix(admin: &Pubkey, name: &str, fee: u16, cancel_penalty_bps: u16) -> Instruction {
+    Instruction {
+        program_id: auction::ID,
+        accounts: auction::accounts::InitHouse {
+            admin: *admin,
+            auction_house: house_address(name),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: auction::instruction::InitHouse {
+            fee,
+            cancel_penalty_bps,
+            name: name.to_string(),
+        }
+        .data(),
+    }
+}
+
+// init data of an english auction of AMOUNT at PRICE, without any extras
+pub fn english(end: u64) -> auction::instruction::InitAuction {
+    auction::instruction::InitAuction {
+        seed: 0,
+        starting_price: PRICE,
+        buy_now_price: 0,
+        end_condition: auction::state::EndCondition::Slot,
+        end,
+        amount: AMOUNT,
+        decimal: DECIMALS,
+        extension_window: 0,
+        extension: 0,
+        max_extension: 0,
+        min_increment: 0,
+        min_increment_bps: 0,
+        reserve_price: 0,
+        reserve_hash: [0; 32],
+        gate: None,
+    }
+}
+
+// A house with a seller holding mint_a and bidders paying in mint_b.
+pub struct Market {
+    pub admin: Keypair,
+    pub seller: Keypair,
+    pub mint_authority: Keypair,
+    pub house: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub token_program: Pubkey,
+    // the seed of the auction the helpers below address
+    pub seed: u64,
+}
+
+impl Market {
+    pub fn new(env: &mut Env) -> Self {
+        let mint_authority = env.user();
+        let mint_a = env.create_mint(&mint_authority, DECIMALS, &spl_token::ID, &[], |_| vec![]);
+        let mint_b = env.create_mint(&mint_authority, DECIMALS, &spl_token::ID, &[], |_| vec![]);
+        let market = Self::with_mints(env, mint_authority, mint_a, mint_b, spl_token::ID);
+        market.fund_seller(env, 1_000_000);
+        market
+    }
+
+    // bids in native SOL
+    pub fn native(env: &mut Env) -> Self {
+        let mint_authority = env.user();
+        let mint_a = env.create_mint(&mint_authority, DECIMALS, &spl_token::ID, &[], |_| vec![]);
+        let market = Self::with_mints(
+            env,
+            mint_authority,
+            mint_a,
+            spl_token::native_mint::ID,
+            spl_token::ID,
+        );
+        market.fund_seller(env, 1_000_000);
+        market
+    }
+
+    // the seller gets no tokens yet
+    pub fn with_mints(
+        env: &mut Env,
+        mint_authority: Keypair,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        token_program: Pubkey,
+    ) -> Self {
+        let admin = env.user();
+        let seller = env.user();
+        // named after the admin, so a test can open several houses
+        let name = &admin.pubkey().to_string()[..16];
+        env.send(
+            &[init_house_ix(
+                &admin.pubkey(),
+                name,
+                HOUSE_FEE,
+                CANCEL_PENALTY_BPS,
+            )],
+            &admin,
+            &[],
+        )
+        .unwrap();
+        Self {
+            admin,
+            seller,
+            mint_authority,
+            house: house_address(name),
+            mint_a,
+            mint_b,
+            token_program,
+            seed: 0,
+        }
+    }
+
+    // the same seller and mints, addressing the auction under `seed`
+    pub fn lot(&self, seed: u64) -> Self {
+        Self {
+            admin: self.admin.insecure_clone(),
+            seller: self.seller.insecure_clone(),
+            mint_authority: self.mint_authority.insecure_clone(),
+            seed,
+            ..*self
+        }
+    }
+
+    pub fn fund_seller(&self, env: &mut Env, amount: u64) {
+        env.mint_to(
+            &self.mint_a,
+            &self.mint_authority,
+            &self.seller.pubkey(),
+            amount,
+            &self.token_program,
+        );
+    }
+
+    // a wallet holding `amount` of mint_b
+    pub fn bidder(&self, env: &mut Env, amount: u64) -> Keypair {
+        let bidder = env.user();
+        if !self.is_native() {
+            env.mint_to(
+                &self.mint_b,
+                &self.mint_authority,
+                &bidder.pubkey(),
+                amount,
+                &self.token_program,
+            );
+        }
+        bidder
+    }
+
+    pub fn is_native(&self) -> bool {
+        self.mint_b == spl_token::native_mint::ID
+    }
+
+    pub fn ata(&self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, mint, &self.token_program)
+    }
+
+    pub fn auction(&self) -> Pubkey {
+        auction_address(
+            &self.house,
+            &self.seller.pubkey(),
+            &self.mint_a,
+            &self.mint_b,
+            self.seed,
+        )
+    }
+
+    pub fn vault(&self) -> Pubkey {
+        self.ata(&self.auction(), &self.mint_a)
+    }
+
+    pub fn bid_state(&self, bidder: &Pubkey) -> Pubkey {
+        bid_address(&self.auction(), bidder)
+    }
+
+    pub fn escrow(&self, bidder: &Pubkey) -> Pubkey {
+        self.ata(&self.bid_state(bidder), &self.mint_b)
+    }
+
+    pub fn stats(&self, wallet: &Pubkey) -> Pubkey {
+        stats_address(&self.house, wallet)
+    }
+
+    pub fn treasury(&self) -> Pubkey {
+        treasury_address(&self.house, &self.mint_b)
+    }
+
+    // the bidder's mint_b ATA, left out when bidding native SOL
+    fn bidder_mint_b_ata(&self, bidder: &Pubkey) -> Option<Pubkey> {
+        (!self.is_native()).then(|| self.ata(bidder, &self.mint_b))
+    }
+
+    // native SOL auctions pay the house in lamports
+    fn token_treasury(&self) -> Option<Pubkey> {
+        (!self.is_native()).then(|| self.treasury())
+    }
+
+    fn ix(&self, accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: auction::ID,
+            accounts: accounts.to_account_metas(None),
+            data: data.data(),
+        }
+    }
+
+    pub fn manage_ix(&self, admin: &Pubkey, data: impl InstructionData) -> Instruction {
+        self.ix(
+            auction::accounts::ManageHouse {
+                admin: *admin,
+                auction_house: self.house,
+            },
+            data,
+        )
+    }
+
+    pub fn accept_admin_ix(&self, pending_admin: &Pubkey) -> Instruction {
+        self.ix(
+            auction::accounts::AcceptAdmin {
+                pending_admin: *pending_admin,
+                auction_house: self.house,
+            },
+            auction::instruction::AcceptAdmin {},
+        )
+    }
+
+    pub fn sweep_fees_ix(&self, admin: &Pubkey, destination: &Pubkey) -> Instruction {
+        self.ix(
+            auction::accounts::SweepFees {
+                admin: *admin,
+                auction_house: self.house,
+                mint: self.mint_b,
+                treasury: self.treasury(),
+                destination: *destination,
+                token_program: self.token_program,
+            },
+            auction::instruction::SweepFees {},
+        )
+    }
+
+    pub fn sweep_lamports_ix(
+        &self,
+        admin: &Pubkey,
+        destination: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        self.ix(
+            auction::accounts::SweepLamports {
+                admin: *admin,
+                auction_house: self.house,
+                destination: *destination,
+            },
+            auction::instruction::SweepLamports { amount },
+        )
+    }
+
+    pub fn fund_keeper_pool_ix(&self, admin: &Pubkey, amount: u64) -> Instruction {
+        self.ix(
+            self.keeper_pool_accounts(admin),
+            auction::instruction::FundKeeperPool { amount },
+        )
+    }
+
+    pub fn withdraw_keeper_pool_ix(&self, admin: &Pubkey, amount: u64) -> Instruction {
+        self.ix(
+            self.keeper_pool_accounts(admin),
+            auction::instruction::WithdrawKeeperPool { amount },
+        )
+    }
+
+    fn keeper_pool_accounts(&self, admin: &Pubkey) -> auction::accounts::ManageKeeperPool {
+        auction::accounts::ManageKeeperPool {
+            admin: *admin,
+            auction_house: self.house,
+            keeper_pool: keeper_pool_address(&self.house),
+            system_program: system_program::ID,
+        }
+    }
+
+    // any of the four init instructions
+    pub fn init_ix(&self, data: impl InstructionData) -> Instruction {
+        let seller = self.seller.pubkey();
+        self.ix(
+            auction::accounts::InitAuction {
+                seller,
+                auction_house: self.house,
+                auction: self.auction(),
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                seller_mint_a_ata: self.ata(&seller, &self.mint_a),
+                vault: self.vault(),
+                seller_stats: self.stats(&seller),
+                metadata: metadata_address(&self.mint_a),
+                edition: None,
+                seller_token_record: None,
+                vault_token_record: None,
+                authorization_rules: None,
+                authorization_rules_program: None,
+                sysvar_instructions: None,
+                token_metadata_program: None,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+            },
+            data,
+        )
+    }
+
+    pub fn init(&self, env: &mut Env, data: impl InstructionData) -> TxResult {
+        env.send(&[self.init_ix(data)], &self.seller, &[])
+    }
+
+    pub fn reveal_reserve_ix(&self, reserve_price: u64, salt: [u8; 32]) -> Instruction {
+        self.ix(
+            auction::accounts::RevealReserve {
+                seller: self.seller.pubkey(),
+                auction_house: self.house,
+                auction: self.auction(),
+            },
+            auction::instruction::RevealReserve {
+                reserve_price,
+                salt,
+            },
+        )
+    }
+
+    fn bid_accounts(&self, bidder: &Pubkey) -> auction::accounts::Bid {
+        auction::accounts::Bid {
+            bidder: *bidder,
+            mint_a: self.mint_a,
+            mint_b: self.mint_b,
+            auction_house: self.house,
+            auction: self.auction(),
+            bidder_mint_b_ata: self.bidder_mint_b_ata(bidder),
+            bid_state: self.bid_state(bidder),
+            bidder_escrow: self.escrow(bidder),
+            vault: self.vault(),
+            bidder_stats: self.stats(bidder),
+            associated_token_program: spl_associated_token_account::ID,
+            token_program: self.token_program,
+            system_program: system_program::ID,
+            gate_token_account: None,
+            gate_metadata: None,
+        }
+    }
+
+    pub fn bid_ix(&self, bidder: &Pubkey, price: u64) -> Instruction {
+        self.gated_bid_ix(bidder, price, vec![], None, None)
+    }
+
+    // a bid carrying what `gate::check_gate` looks at
+    pub fn gated_bid_ix(
+        &self,
+        bidder: &Pubkey,
+        price: u64,
+        proof: Vec<[u8; 32]>,
+        gate_token_account: Option<Pubkey>,
+        gate_metadata: Option<Pubkey>,
+    ) -> Instruction {
+        self.ix(
+            auction::accounts::Bid {
+                gate_token_account,
+                gate_metadata,
+                ..self.bid_accounts(bidder)
+            },
+            auction::instruction::Bid {
+                price,
+                proof,
+                max_price: None,
+            },
+        )
+    }
+
+    pub fn gated_bid(
+        &self,
+        env: &mut Env,
+        bidder: &Keypair,
+        price: u64,
+        proof: Vec<[u8; 32]>,
+        gate_token_account: Option<Pubkey>,
+        gate_metadata: Option<Pubkey>,
+    ) -> TxResult {
+        let ix = self.gated_bid_ix(
+            &bidder.pubkey(),
+            price,
+            proof,
+            gate_token_account,
+            gate_metadata,
+        );
+        env.send(&[ix], bidder, &[])
+    }
+
+    pub fn bid(&self, env: &mut Env, bidder: &Keypair, price: u64) -> TxResult {
+        let ix = self.bid_ix(&bidder.pubkey(), price);
+        env.send(&[ix], bidder, &[])
+    }
+
+    // bids `price`, rivals are outbid on the bidder's behalf up to `max_price`
+    pub fn proxy_bid(
+        &self,
+        env: &mut Env,
+        bidder: &Keypair,
+        price: u64,
+        max_price: u64,
+    ) -> TxResult {
+        let ix = self.ix(
+            self.bid_accounts(&bidder.pubkey()),
+            auction::instruction::Bid {
+                price,
+                proof: vec![],
+                max_price: Some(max_price),
+            },
+        );
+        env.send(&[ix], bidder, &[])
+    }
+
+    pub fn raise_bid(&self, env: &mut Env, bidder: &Keypair, price: u64) -> TxResult {
+        self.proxy_raise_bid(env, bidder, price, None)
+    }
+
+    pub fn proxy_raise_bid(
+        &self,
+        env: &mut Env,
+        bidder: &Keypair,
+        price: u64,
+        max_price: Option<u64>,
+    ) -> TxResult {
+        let bidder_key = bidder.pubkey();
+        let ix = self.ix(
+            auction::accounts::RaiseBid {
+                bidder: bidder_key,
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                auction_house: self.house,
+                auction: self.auction(),
+                bidder_mint_b_ata: self.bidder_mint_b_ata(&bidder_key),
+                bid_state: self.bid_state(&bidder_key),
+                bidder_escrow: self.escrow(&bidder_key),
+                vault: self.vault(),
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            auction::instruction::RaiseBid { price, max_price },
+        );
+        env.send(&[ix], bidder, &[])
+    }
+
+    // commits to `price`, returns the salt to reveal it with
+    pub fn commit_bid(
+        &self,
+        env: &mut Env,
+        bidder: &Keypair,
+        price: u64,
+        deposit: u64,
+    ) -> (TxResult, [u8; 32]) {
+        let salt = Keypair::new().pubkey().to_bytes();
+        let commitment = bid_commitment(price, &salt, &bidder.pubkey());
+        let ix = self.ix(
+            self.bid_accounts(&bidder.pubkey()),
+            auction::instruction::CommitBid {
+                commitment,
+                deposit,
+            },
+        );
+        (env.send(&[ix], bidder, &[]), salt)
+    }
+
+    pub fn reveal_bid(
+        &self,
+        env: &mut Env,
+        bidder: &Keypair,
+        price: u64,
+        salt: [u8; 32],
+    ) -> TxResult {
+        let bidder_key = bidder.pubkey();
+        let ix = self.ix(
+            auction::accounts::RevealBid {
+                bidder: bidder_key,
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                auction_house: self.house,
+                auction: self.auction(),
+                bid_state: self.bid_state(&bidder_key),
+                bidder_escrow: self.escrow(&bidder_key),
+                vault: self.vault(),
+            },
+            auction::instruction::RevealBid { price, salt },
+        );
+        env.send(&[ix], bidder, &[])
+    }
+
+    pub fn penalize_bid(&self, env: &mut Env, payer: &Keypair, bidder: &Pubkey) -> TxResult {
+        let seller = self.seller.pubkey();
+        let ix = self.ix(
+            auction::accounts::PenalizeBid {
+                payer: payer.pubkey(),
+                seller,
+                bidder: *bidder,
+                mint_b: self.mint_b,
+                auction_house: self.house,
+                auction: self.auction(),
+                bid_state: self.bid_state(bidder),
+                bidder_escrow: self.escrow(bidder),
+                seller_mint_b_ata: self.bidder_mint_b_ata(&seller),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            auction::instruction::PenalizeBid {},
+        );
+        env.send(&[ix], payer, &[])
+    }
+
+    // `prev` and `next` are the bidders whose bids the new one goes between
+    pub fn batch_bid(
+        &self,
+        env: &mut Env,
+        bidder: &Keypair,
+        quantity: u64,
+        price: u64,
+        prev: Option<&Pubkey>,
+        next: Option<&Pubkey>,
+    ) -> TxResult {
+        let bidder_key = bidder.pubkey();
+        let ix = self.ix(
+            auction::accounts::BatchBid {
+                bidder: bidder_key,
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                auction_house: self.house,
+                auction: self.auction(),
+                bidder_mint_b_ata: self.bidder_mint_b_ata(&bidder_key),
+                bid_state: self.bid_state(&bidder_key),
+                bidder_escrow: self.escrow(&bidder_key),
+                prev_bid_state: prev.map(|prev| self.bid_state(prev)),
+                next_bid_state: next.map(|next| self.bid_state(next)),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            auction::instruction::BatchBid { quantity, price },
+        );
+        env.send(&[ix], bidder, &[])
+    }
+
+    // walks the bids of `bidders`, in list order
+    pub fn tally_batch(&self, env: &mut Env, payer: &Keypair, bidders: &[Pubkey]) -> TxResult {
+        let mut ix = self.ix(
+            auction::accounts::TallyBatch {
+                auction_house: self.house,
+                auction: self.auction(),
+            },
+            auction::instruction::TallyBatch {},
+        );
+        ix.accounts.extend(
+            bidders
+                .iter()
+                .map(|bidder| AccountMeta::new_readonly(self.bid_state(bidder), false)),
+        );
+        env.send(&[ix], payer, &[])
+    }
+
+    pub fn settle_batch(&self, env: &mut Env, payer: &Keypair, bidders: &[Pubkey]) -> TxResult {
+        let ix = self.settle_batch_ix(&payer.pubkey(), None, bidders);
+        env.send(&[ix], payer, &[])
+    }
+
+    pub fn settle_batch_ix(
+        &self,
+        payer: &Pubkey,
+        payer_mint_b_ata: Option<Pubkey>,
+        bidders: &[Pubkey],
+    ) -> Instruction {
+        let seller = self.seller.pubkey();
+        let mut ix = self.ix(
+            auction::accounts::SettleBatch {
+                payer: *payer,
+                seller,
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                auction_house: self.house,
+                keeper_pool: keeper_pool_address(&self.house),
+                auction: self.auction(),
+                vault: self.vault(),
+                seller_mint_a_ata: self.ata(&seller, &self.mint_a),
+                seller_mint_b_ata: self.bidder_mint_b_ata(&seller),
+                treasury: self.token_treasury(),
+                payer_mint_b_ata,
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            auction::instruction::SettleBatch {},
+        );
+        for bidder in bidders {
+            ix.accounts.extend([
+                AccountMeta::new(self.bid_state(bidder), false),
+                AccountMeta::new(self.escrow(bidder), false),
+                AccountMeta::new(*bidder, false),
+                AccountMeta::new(self.ata(bidder, &self.mint_a), false),
+            ]);
+        }
+        ix
+    }
+
+    pub fn withdraw(&self, env: &mut Env, bidder: &Keypair) -> TxResult {
+        let bidder_key = bidder.pubkey();
+        let ix = self.ix(
+            auction::accounts::Withdraw {
+                bidder: bidder_key,
+                mint_b: self.mint_b,
+                auction_house: self.house,
+                auction: self.auction(),
+                bidder_mint_b_ata: self.bidder_mint_b_ata(&bidder_key),
+                bidder_escrow: self.escrow(&bidder_key),
+                bid_state: self.bid_state(&bidder_key),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            auction::instruction::Withdraw {},
+        );
+        env.send(&[ix], bidder, &[])
+    }
+
+    // `creators` are the royalty accounts that follow the named ones
+    pub fn finalize_ix(
+        &self,
+        payer: &Pubkey,
+        bidder: &Pubkey,
+        payer_mint_b_ata: Option<Pubkey>,
+        creators: Vec<AccountMeta>,
+    ) -> Instruction {
+        let seller = self.seller.pubkey();
+        let mut ix = self.ix(
+            auction::accounts::Finalize {
+                payer: *payer,
+                seller,
+                bidder: *bidder,
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                auction_house: self.house,
+                keeper_pool: keeper_pool_address(&self.house),
+                auction: self.auction(),
+                bid_state: self.bid_state(bidder),
+                bidder_escrow: self.escrow(bidder),
+                vault: self.vault(),
+                bidder_stats: self.stats(bidder),
+                seller_stats: self.stats(&seller),
+                bidder_mint_a_ata: self.ata(bidder, &self.mint_a),
+                seller_mint_b_ata: self.ata(&seller, &self.mint_b),
+                treasury: self.token_treasury(),
+                payer_mint_b_ata,
+                seller_mint_a_ata: self.ata(&seller, &self.mint_a),
+                bidder_mint_b_ata: self.ata(bidder, &self.mint_b),
+                metadata: metadata_address(&self.mint_a),
+                edition: None,
+                vault_token_record: None,
+                destination_token_record: None,
+                authorization_rules: None,
+                authorization_rules_program: None,
+                sysvar_instructions: None,
+                token_metadata_program: None,
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            auction::instruction::Finalize {},
+        );
+        ix.accounts.extend(creators);
+        ix
+    }
+
+    pub fn finalize(&self, env: &mut Env, payer: &Keypair, bidder: &Pubkey) -> TxResult {
+        let ix = self.finalize_ix(&payer.pubkey(), bidder, None, vec![]);
+        env.send(&[ix], payer, &[])
+    }
+
+    pub fn buy_now(&self, env: &mut Env, buyer: &Keypair, max_price: u64) -> TxResult {
+        let (buyer_key, seller) = (buyer.pubkey(), self.seller.pubkey());
+        let ix = self.ix(
+            auction::accounts::BuyNow {
+                buyer: buyer_key,
+                seller,
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                auction_house: self.house,
+                auction: self.auction(),
+                vault: self.vault(),
+                buyer_stats: self.stats(&buyer_key),
+                seller_stats: self.stats(&seller),
+                buyer_mint_b_ata: self.bidder_mint_b_ata(&buyer_key),
+                buyer_mint_a_ata: self.ata(&buyer_key, &self.mint_a),
+                seller_mint_b_ata: self.bidder_mint_b_ata(&seller),
+                treasury: self.token_treasury(),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            auction::instruction::BuyNow { max_price },
+        );
+        env.send(&[ix], buyer, &[])
+    }
+
+    pub fn cancel(&self, env: &mut Env) -> TxResult {
+        let seller = self.seller.pubkey();
+        let ix = self.ix(
+            auction::accounts::Cancel {
+                seller,
+                auction_house: self.house,
+                auction: self.auction(),
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                seller_mint_a_ata: self.ata(&seller, &self.mint_a),
+                vault: self.vault(),
+                seller_stats: self.stats(&seller),
+                metadata: metadata_address(&self.mint_a),
+                edition: None,
+                vault_token_record: None,
+                seller_token_record: None,
+                authorization_rules: None,
+                authorization_rules_program: None,
+                sysvar_instructions: None,
+                token_metadata_program: None,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+            },
+            auction::instruction::Cancel {},
+        );
+        env.send(&[ix], &self.seller, &[])
+    }
+
+    pub fn cancel_expired(&self, env: &mut Env, payer: &Keypair) -> TxResult {
+        let seller = self.seller.pubkey();
+        let ix = self.ix(
+            auction::accounts::CancelExpired {
+                payer: payer.pubkey(),
+                seller,
+                auction_house: self.house,
+                keeper_pool: keeper_pool_address(&self.house),
+                auction: self.auction(),
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                seller_mint_a_ata: self.ata(&seller, &self.mint_a),
+                vault: self.vault(),
+                seller_stats: self.stats(&seller),
+                metadata: metadata_address(&self.mint_a),
+                edition: None,
+                vault_token_record: None,
+                seller_token_record: None,
+                authorization_rules: None,
+                authorization_rules_program: None,
+                sysvar_instructions: None,
+                token_metadata_program: None,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+            },
+            auction::instruction::CancelExpired {},
+        );
+        env.send(&[ix], payer, &[])
+    }
+
+    // `highest_bidder` pays the penalty to them, left out there is none to pay
+    pub fn cancel_early(&self, env: &mut Env, highest_bidder: Option<&Pubkey>) -> TxResult {
+        let seller = self.seller.pubkey();
+        let ix = self.ix(
+            auction::accounts::CancelEarly {
+                seller,
+                auction_house: self.house,
+                auction: self.auction(),
+                mint_a: self.mint_a,
+                mint_b: self.mint_b,
+                seller_mint_a_ata: self.ata(&seller, &self.mint_a),
+                vault: self.vault(),
+                seller_stats: self.stats(&seller),
+                metadata: metadata_address(&self.mint_a),
+                edition: None,
+                vault_token_record: None,
+                seller_token_record: None,
+                authorization_rules: None,
+                authorization_rules_program: None,
+                sysvar_instructions: None,
+                token_metadata_program: None,
+                seller_mint_b_ata: highest_bidder.and_then(|_| self.bidder_mint_b_ata(&seller)),
+                highest_bid_state: highest_bidder.map(|bidder| self.bid_state(bidder)),
+                highest_bidder_escrow: highest_bidder.map(|bidder| self.escrow(bidder)),
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                token_program: self.token_program,
+            },
+            auction::instruction::CancelEarly {},
+        );
+        env.send(&[ix], &self.seller, &[])
+    }
+
+    pub fn close_cancelled(&self, env: &mut Env) -> TxResult {
+        let ix = self.ix(
+            auction::accounts::CloseCancelled {
+                seller: self.seller.pubkey(),
+                auction_house: self.house,
+                auction: self.auction(),
+            },
+            auction::instruction::CloseCancelled {},
+        );
+        env.send(&[ix], &self.seller, &[])
+    }
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use auction::errors::AuctionError;
+use auction::state::EndCondition;
+use common::*;
+use solana_sdk::signer::Signer;
+
+const START_PRICE: u64 = 4_000_000;
+const FLOOR_PRICE: u64 = 2_000_000;
+
+// falls from START_PRICE to FLOOR_PRICE at `end`, in steps of 10 slots
+fn dutch(end: u64) -> auction::instruction::InitDutchAuction {
+    auction::instruction::InitDutchAuction {
+        seed: 0,
+        start_price: START_PRICE,
+        floor_price: FLOOR_PRICE,
+        step: 10,
+        end_condition: EndCondition::Slot,
+        end,
+        amount: AMOUNT,
+        decimal: DECIMALS,
+    }
+}
+
+#[test]
+fn init_checks_the_schedule() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    env.warp_to(10);
+
+    assert_error(market.init(&mut env, dutch(10)), AuctionError::InvalidEnd);
+    assert_error(
+        market.init(
+            &mut env,
+            auction::instruction::InitDutchAuction {
+                floor_price: START_PRICE + 1,
+                ..dutch(110)
+            },
+        ),
+        AuctionError::InvalidSchedule,
+    );
+    assert_error(
+        market.init(
+            &mut env,
+            auction::instruction::InitDutchAuction {
+                step: 0,
+                ..dutch(110)
+            },
+        ),
+        AuctionError::InvalidSchedule,
+    );
+}
+
+#[test]
+fn buy_now_pays_the_current_price() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    env.warp_to(10);
+    market.init(&mut env, dutch(110)).unwrap();
+    let buyer = market.bidder(&mut env, 1_000_000);
+    let seller = market.seller.pubkey();
+
+    // 25 slots in, the price has taken two steps of 200_000
+    env.warp_to(35);
+    assert_error(
+        market.buy_now(&mut env, &buyer, 3_599_999),
+        AuctionError::PriceTooLow,
+    );
+    market.buy_now(&mut env, &buyer, 3_600_000).unwrap();
+
+    assert_eq!(
+        env.token_balance(&market.ata(&buyer.pubkey(), &market.mint_a)),
+        AMOUNT
+    );
+    assert_eq!(
+        env.token_balance(&market.ata(&buyer.pubkey(), &market.mint_b)),
+        1_000_000 - 3600
+    );
+    assert_eq!(
+        env.token_balance(&market.ata(&seller, &market.mint_b)),
+        3564
+    );
+    assert_eq!(env.token_balance(&market.treasury()), 36);
+    assert!(!env.exists(&market.auction()));
+    assert!(!env.exists(&market.vault()));
+}
+
+#[test]
+fn paused_houses_sell_nothing() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    env.warp_to(10);
+    market.init(&mut env, dutch(110)).unwrap();
+    let buyer = market.bidder(&mut env, 1_000_000);
+    let pause = |paused| {
+        market.manage_ix(
+            &market.admin.pubkey(),
+            auction::instruction::PauseHouse { paused },
+        )
+    };
+
+    env.send(&[pause(true)], &market.admin, &[]).unwrap();
+    assert_error(
+        market.buy_now(&mut env, &buyer, START_PRICE),
+        AuctionError::HousePaused,
+    );
+    env.send(&[pause(false)], &market.admin, &[]).unwrap();
+    market.buy_now(&mut env, &buyer, START_PRICE).unwrap();
+}
+
+#[test]
+fn unsold_lot_goes_back_after_the_end() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    env.warp_to(10);
+    market.init(&mut env, dutch(110)).unwrap();
+    let buyer = market.bidder(&mut env, 1_000_000);
+
+    assert_error(
+        market.bid(&mut env, &buyer, START_PRICE),
+        AuctionError::WrongAuctionKind,
+    );
+
+    env.warp_to(110);
+    assert_error(
+        market.buy_now(&mut env, &buyer, START_PRICE),
+        AuctionError::AuctionEnded,
+    );
+    market.cancel(&mut env).unwrap();
+    assert!(!env.exists(&market.auction()));
+}
@@ -0,0 +1,703 @@
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use auction::errors::AuctionError;
+use auction::state::{reserve_hash, Auction, KeeperReward};
+use common::*;
+use solana_sdk::{instruction::AccountMeta, rent::Rent, signature::Keypair, signer::Signer};
+
+const FUNDS: u64 = 1_000_000;
+
+fn started(env: &mut Env, data: auction::instruction::InitAuction) -> (Market, Keypair, Keypair) {
+    let market = Market::new(env);
+    market.init(env, data).unwrap();
+    let bidder = market.bidder(env, FUNDS);
+    let bidder2 = market.bidder(env, FUNDS);
+    (market, bidder, bidder2)
+}
+
+#[test]
+fn init_checks_its_arguments() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    env.warp_to(100);
+
+    assert_error(
+        market.init(&mut env, english(100)),
+        AuctionError::InvalidEnd,
+    );
+    assert_error(
+        market.init(
+            &mut env,
+            auction::instruction::InitAuction {
+                reserve_price: 1,
+                reserve_hash: [1; 32],
+                ..english(200)
+            },
+        ),
+        AuctionError::InvalidReserve,
+    );
+    assert_error(
+        market.init(
+            &mut env,
+            auction::instruction::InitAuction {
+                buy_now_price: PRICE - 1,
+                ..english(200)
+            },
+        ),
+        AuctionError::PriceTooLow,
+    );
+    assert_error(
+        market.init(
+            &mut env,
+            auction::instruction::InitAuction {
+                max_extension: 1,
+                ..english(u64::MAX)
+            },
+        ),
+        AuctionError::ArithematicOverflow,
+    );
+
+    market.init(&mut env, english(200)).unwrap();
+    assert_eq!(env.token_balance(&market.vault()), AMOUNT);
+    let auction: Auction = env.account(&market.auction());
+    assert_eq!(auction.end, 200);
+    assert_eq!(auction.bidder, None);
+}
+
+#[test]
+fn bids_must_beat_the_minimum() {
+    let mut env = Env::new();
+    let (market, bidder, bidder2) = started(
+        &mut env,
+        auction::instruction::InitAuction {
+            min_increment: 100_000,
+            min_increment_bps: 1000,
+            ..english(100)
+        },
+    );
+
+    assert_error(
+        market.bid(&mut env, &bidder, PRICE - 1),
+        AuctionError::PriceTooLow,
+    );
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    assert_eq!(env.token_balance(&market.escrow(&bidder.pubkey())), 2000);
+
+    // 10% of the highest price beats the fixed increment
+    assert_error(
+        market.bid(&mut env, &bidder2, PRICE + 199_999),
+        AuctionError::PriceTooLow,
+    );
+    market.bid(&mut env, &bidder2, PRICE + 200_000).unwrap();
+    let auction: Auction = env.account(&market.auction());
+    assert_eq!(auction.bidder, Some(bidder2.pubkey()));
+    assert_eq!(auction.highest_price, 2_200_000);
+
+    // raising only deposits the difference
+    market.raise_bid(&mut env, &bidder, 3_000_000).unwrap();
+    assert_eq!(env.token_balance(&market.escrow(&bidder.pubkey())), 3000);
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_b)),
+        FUNDS - 3000
+    );
+    assert_error(
+        market.raise_bid(&mut env, &bidder2, 3_000_000),
+        AuctionError::PriceTooLow,
+    );
+}
+
+#[test]
+fn late_bids_extend_the_end_up_to_the_cap() {
+    let mut env = Env::new();
+    let (market, bidder, bidder2) = started(
+        &mut env,
+        auction::instruction::InitAuction {
+            extension_window: 10,
+            extension: 20,
+            max_extension: 30,
+            ..english(100)
+        },
+    );
+
+    // outside the window nothing changes
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    assert_eq!(env.account::<Auction>(&market.auction()).end, 100);
+
+    env.warp_to(95);
+    market.raise_bid(&mut env, &bidder, 3_000_000).unwrap();
+    assert_eq!(env.account::<Auction>(&market.auction()).end, 120);
+
+    env.warp_to(115);
+    market.bid(&mut env, &bidder2, 4_000_000).unwrap();
+    assert_eq!(env.account::<Auction>(&market.auction()).end, 130);
+
+    env.warp_to(130);
+    assert_error(
+        market.raise_bid(&mut env, &bidder, 5_000_000),
+        AuctionError::AuctionEnded,
+    );
+}
+
+#[test]
+fn bidding_at_the_buy_now_price_ends_the_auction() {
+    let mut env = Env::new();
+    let (market, bidder, bidder2) = started(
+        &mut env,
+        auction::instruction::InitAuction {
+            buy_now_price: 5_000_000,
+            ..english(100)
+        },
+    );
+
+    market.bid(&mut env, &bidder, 5_000_000).unwrap();
+    assert_eq!(env.account::<Auction>(&market.auction()).end, env.slot());
+    assert_error(
+        market.bid(&mut env, &bidder2, 6_000_000),
+        AuctionError::AuctionEnded,
+    );
+
+    let keeper = env.user();
+    market
+        .finalize(&mut env, &keeper, &bidder.pubkey())
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_a)),
+        AMOUNT
+    );
+}
+
+#[test]
+fn only_outbid_bidders_withdraw() {
+    let mut env = Env::new();
+    let (market, bidder, bidder2) = started(&mut env, english(100));
+
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    assert_error(
+        market.withdraw(&mut env, &bidder),
+        AuctionError::NotEligibleToWithdraw,
+    );
+
+    market.bid(&mut env, &bidder2, 3_000_000).unwrap();
+    market.withdraw(&mut env, &bidder).unwrap();
+    assert!(!env.exists(&market.bid_state(&bidder.pubkey())));
+    assert!(!env.exists(&market.escrow(&bidder.pubkey())));
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_b)),
+        FUNDS
+    );
+}
+
+#[test]
+fn finalize_pays_seller_winner_and_house() {
+    let mut env = Env::new();
+    let (market, bidder, bidder2) = started(&mut env, english(100));
+    let seller = market.seller.pubkey();
+
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    market.bid(&mut env, &bidder2, 3_000_000).unwrap();
+    assert_error(
+        market.finalize(&mut env, &market.seller, &bidder2.pubkey()),
+        AuctionError::NotEligibleToWithdraw,
+    );
+
+    env.warp_to(100);
+    market
+        .finalize(&mut env, &market.seller, &bidder2.pubkey())
+        .unwrap();
+
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder2.pubkey(), &market.mint_a)),
+        AMOUNT
+    );
+    // 3000 less the 1% house fee
+    assert_eq!(
+        env.token_balance(&market.ata(&seller, &market.mint_b)),
+        2970
+    );
+    assert_eq!(env.token_balance(&market.treasury()), 30);
+    assert!(!env.exists(&market.auction()));
+    assert!(!env.exists(&market.vault()));
+    assert!(!env.exists(&market.bid_state(&bidder2.pubkey())));
+
+    // the losing bid comes back once the auction is gone
+    market.withdraw(&mut env, &bidder).unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_b)),
+        FUNDS
+    );
+
+    let admin = market.admin.pubkey();
+    let destination = env.mint_to(
+        &market.mint_b,
+        &market.mint_authority,
+        &admin,
+        0,
+        &market.token_program,
+    );
+    let stranger = env.user();
+    let ix = market.sweep_fees_ix(&stranger.pubkey(), &destination);
+    assert_error(env.send(&[ix], &stranger, &[]), AuctionError::NotHouseAdmin);
+    let ix = market.sweep_fees_ix(&admin, &destination);
+    env.send(&[ix], &market.admin, &[]).unwrap();
+    assert_eq!(env.token_balance(&destination), 30);
+    assert_eq!(env.token_balance(&market.treasury()), 0);
+}
+
+#[test]
+fn sellers_run_several_lots_of_the_same_mints() {
+    let mut env = Env::new();
+    let (market, bidder, bidder2) = started(&mut env, english(100));
+    let lot = market.lot(7);
+
+    // the first lot already lives under seed 0
+    assert!(market.init(&mut env, english(150)).is_err());
+    lot.init(
+        &mut env,
+        auction::instruction::InitAuction {
+            seed: 7,
+            ..english(200)
+        },
+    )
+    .unwrap();
+    assert_ne!(lot.auction(), market.auction());
+    assert_eq!(env.token_balance(&lot.vault()), AMOUNT);
+    assert_eq!(env.account::<Auction>(&lot.auction()).seed, 7);
+
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    lot.bid(&mut env, &bidder, 3_000_000).unwrap();
+    lot.bid(&mut env, &bidder2, 4_000_000).unwrap();
+    assert_eq!(
+        env.account::<Auction>(&market.auction()).bidder,
+        Some(bidder.pubkey())
+    );
+
+    env.warp_to(100);
+    market
+        .finalize(&mut env, &market.seller, &bidder.pubkey())
+        .unwrap();
+    assert!(!env.exists(&market.auction()));
+    assert_eq!(
+        env.account::<Auction>(&lot.auction()).highest_price,
+        4_000_000
+    );
+    lot.withdraw(&mut env, &bidder).unwrap();
+}
+
+#[test]
+fn hidden_reserve_counts_once_revealed() {
+    let mut env = Env::new();
+    let salt = [7; 32];
+    let (market, bidder, _) = started(
+        &mut env,
+        auction::instruction::InitAuction {
+            reserve_hash: reserve_hash(2_500_000, &salt),
+            ..english(100)
+        },
+    );
+    market.bid(&mut env, &bidder, 3_000_000).unwrap();
+
+    let ix = market.reveal_reserve_ix(2_400_000, salt);
+    assert_error(
+        env.send(&[ix], &market.seller, &[]),
+        AuctionError::InvalidReserve,
+    );
+    let ix = market.reveal_reserve_ix(2_500_000, salt);
+    env.send(&[ix], &market.seller, &[]).unwrap();
+    let auction: Auction = env.account(&market.auction());
+    assert_eq!(auction.reserve_price, 2_500_000);
+    assert_eq!(auction.reserve_hash, None);
+
+    env.warp_to(100);
+    market
+        .finalize(&mut env, &market.seller, &bidder.pubkey())
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_a)),
+        AMOUNT
+    );
+}
+
+#[test]
+fn winner_cannot_finalize_before_the_reserve_is_revealed() {
+    let mut env = Env::new();
+    let salt = [7; 32];
+    let (market, bidder, _) = started(
+        &mut env,
+        auction::instruction::InitAuction {
+            reserve_hash: reserve_hash(2_500_000, &salt),
+            ..english(100)
+        },
+    );
+    market.bid(&mut env, &bidder, 3_000_000).unwrap();
+
+    // finalizing while the reserve is hidden would refund a winning bid
+    env.warp_to(100);
+    assert_error(
+        market.finalize(&mut env, &bidder, &bidder.pubkey()),
+        AuctionError::NotEligibleToWithdraw,
+    );
+    let ix = market.reveal_reserve_ix(2_500_000, salt);
+    env.send(&[ix], &market.seller, &[]).unwrap();
+    market
+        .finalize(&mut env, &bidder, &bidder.pubkey())
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_a)),
+        AMOUNT
+    );
+}
+
+#[test]
+fn unmet_reserve_returns_lot_and_bid() {
+    let mut env = Env::new();
+    let (market, bidder, _) = started(
+        &mut env,
+        auction::instruction::InitAuction {
+            reserve_hash: reserve_hash(5_000_000, &[7; 32]),
+            ..english(100)
+        },
+    );
+    let seller = market.seller.pubkey();
+    let seller_tokens = env.token_balance(&market.ata(&seller, &market.mint_a));
+
+    market.bid(&mut env, &bidder, 3_000_000).unwrap();
+    env.warp_to(100);
+    let ix = market.reveal_reserve_ix(5_000_000, [7; 32]);
+    env.send(&[ix], &market.seller, &[]).unwrap();
+    market
+        .finalize(&mut env, &market.seller, &bidder.pubkey())
+        .unwrap();
+
+    assert_eq!(
+        env.token_balance(&market.ata(&seller, &market.mint_a)),
+        seller_tokens + AMOUNT
+    );
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_b)),
+        FUNDS
+    );
+    assert_eq!(env.token_balance(&market.treasury()), 0);
+}
+
+#[test]
+fn withheld_reserve_counts_as_met_after_the_deadline() {
+    let mut env = Env::new();
+    let salt = [7; 32];
+    let (market, bidder, _) = started(
+        &mut env,
+        auction::instruction::InitAuction {
+            reserve_hash: reserve_hash(5_000_000, &salt),
+            ..english(100)
+        },
+    );
+    market.bid(&mut env, &bidder, 3_000_000).unwrap();
+
+    // the seller has until the deadline to reveal, finalizing waits for it
+    env.warp_to(100);
+    assert_error(
+        market.finalize(&mut env, &market.seller, &bidder.pubkey()),
+        AuctionError::NotEligibleToWithdraw,
+    );
+    let deadline = env.account::<Auction>(&market.auction()).reserve_deadline();
+    assert_eq!(deadline, 9_100);
+
+    env.warp_to(deadline);
+    let ix = market.reveal_reserve_ix(5_000_000, salt);
+    assert_error(
+        env.send(&[ix], &market.seller, &[]),
+        AuctionError::NotInRevealPhase,
+    );
+    market
+        .finalize(&mut env, &market.seller, &bidder.pubkey())
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_a)),
+        AMOUNT
+    );
+}
+
+#[test]
+fn cancel_needs_an_ended_auction_without_bids() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    let seller = market.seller.pubkey();
+    let seller_tokens = env.token_balance(&market.ata(&seller, &market.mint_a));
+    market.init(&mut env, english(100)).unwrap();
+
+    assert_error(market.cancel(&mut env), AuctionError::NotEligibleToWithdraw);
+    env.warp_to(100);
+    market.cancel(&mut env).unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&seller, &market.mint_a)),
+        seller_tokens
+    );
+    assert!(!env.exists(&market.auction()));
+
+    let (market, bidder, _) = started(&mut env, english(200));
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    env.warp_to(200);
+    assert_error(market.cancel(&mut env), AuctionError::NotEligibleToWithdraw);
+    let keeper = env.user();
+    assert_error(
+        market.cancel_expired(&mut env, &keeper),
+        AuctionError::NotEligibleToWithdraw,
+    );
+}
+
+#[test]
+fn anyone_cancels_an_expired_auction() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    let seller = market.seller.pubkey();
+    let seller_tokens = env.token_balance(&market.ata(&seller, &market.mint_a));
+    market.init(&mut env, english(100)).unwrap();
+    let keeper = env.user();
+
+    assert_error(
+        market.cancel_expired(&mut env, &keeper),
+        AuctionError::NotEligibleToWithdraw,
+    );
+    env.warp_to(100);
+    market.cancel_expired(&mut env, &keeper).unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&seller, &market.mint_a)),
+        seller_tokens
+    );
+    assert!(!env.exists(&market.auction()));
+}
+
+#[test]
+fn cancel_early_pays_the_highest_bidder() {
+    let mut env = Env::new();
+    let (market, bidder, _) = started(&mut env, english(100));
+    let seller = market.seller.pubkey();
+    env.mint_to(
+        &market.mint_b,
+        &market.mint_authority,
+        &seller,
+        FUNDS,
+        &market.token_program,
+    );
+
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    assert_error(
+        market.cancel_early(&mut env, None),
+        AuctionError::MissingPenaltyAccounts,
+    );
+    market
+        .cancel_early(&mut env, Some(&bidder.pubkey()))
+        .unwrap();
+    assert!(env.account::<Auction>(&market.auction()).cancelled);
+    assert!(!env.exists(&market.vault()));
+
+    // 5% of the 2000 bid on top of the bid itself
+    assert_eq!(env.token_balance(&market.escrow(&bidder.pubkey())), 2100);
+    market.withdraw(&mut env, &bidder).unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_b)),
+        FUNDS + 100
+    );
+}
+
+#[test]
+fn cancelled_auctions_close_once_every_bid_is_withdrawn() {
+    let mut env = Env::new();
+    let (market, bidder, bidder2) = started(&mut env, english(100));
+    env.mint_to(
+        &market.mint_b,
+        &market.mint_authority,
+        &market.seller.pubkey(),
+        FUNDS,
+        &market.token_program,
+    );
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    market.bid(&mut env, &bidder2, 3_000_000).unwrap();
+    assert_error(
+        market.close_cancelled(&mut env),
+        AuctionError::AuctionNotCancelled,
+    );
+
+    market
+        .cancel_early(&mut env, Some(&bidder2.pubkey()))
+        .unwrap();
+    assert_eq!(env.account::<Auction>(&market.auction()).open_bids, 2);
+    market.withdraw(&mut env, &bidder).unwrap();
+    assert_error(
+        market.close_cancelled(&mut env),
+        AuctionError::BidsOutstanding,
+    );
+    market.withdraw(&mut env, &bidder2).unwrap();
+
+    let rent = env.lamports(&market.auction());
+    let seller_before = env.lamports(&market.seller.pubkey());
+    market.close_cancelled(&mut env).unwrap();
+    assert!(!env.exists(&market.auction()));
+    // less the transaction fee
+    assert_eq!(
+        env.lamports(&market.seller.pubkey()),
+        seller_before + rent - 5000
+    );
+
+    // the seed is free again, and without bids the auction closes right away
+    market.init(&mut env, english(100)).unwrap();
+    market.cancel_early(&mut env, None).unwrap();
+    assert!(!env.exists(&market.auction()));
+}
+
+#[test]
+fn cancelled_auctions_take_no_more_bids() {
+    let mut env = Env::new();
+    let (market, bidder, bidder2) = started(&mut env, english(100));
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    env.mint_to(
+        &market.mint_b,
+        &market.mint_authority,
+        &market.seller.pubkey(),
+        FUNDS,
+        &market.token_program,
+    );
+    market
+        .cancel_early(&mut env, Some(&bidder.pubkey()))
+        .unwrap();
+
+    assert_error(
+        market.bid(&mut env, &bidder2, 3_000_000),
+        AuctionError::AuctionCancelled,
+    );
+    assert_error(
+        market.raise_bid(&mut env, &bidder, 3_000_000),
+        AuctionError::AuctionCancelled,
+    );
+    env.warp_to(100);
+    assert_error(
+        market.finalize(&mut env, &bidder, &bidder.pubkey()),
+        AuctionError::AuctionCancelled,
+    );
+    market.withdraw(&mut env, &bidder).unwrap();
+}
+
+#[test]
+fn cancel_early_only_before_the_end() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    market.init(&mut env, english(100)).unwrap();
+
+    env.warp_to(100);
+    assert_error(
+        market.cancel_early(&mut env, None),
+        AuctionError::AuctionEnded,
+    );
+}
+
+#[test]
+fn keepers_get_a_share_of_the_fee() {
+    let mut env = Env::new();
+    let (market, bidder, _) = started(&mut env, english(100));
+    let admin = market.admin.pubkey();
+    let ix = market.manage_ix(
+        &admin,
+        auction::instruction::SetKeeperReward {
+            keeper_reward: KeeperReward::FeeShare { bps: 5000 },
+        },
+    );
+    env.send(&[ix], &market.admin, &[]).unwrap();
+
+    market.bid(&mut env, &bidder, 3_000_000).unwrap();
+    env.warp_to(100);
+
+    let keeper = env.user();
+    let keeper_ata = env.mint_to(
+        &market.mint_b,
+        &market.mint_authority,
+        &keeper.pubkey(),
+        0,
+        &market.token_program,
+    );
+    let ix = market.finalize_ix(&keeper.pubkey(), &bidder.pubkey(), Some(keeper_ata), vec![]);
+    env.send(&[ix], &keeper, &[]).unwrap();
+    assert_eq!(env.token_balance(&keeper_ata), 15);
+    assert_eq!(env.token_balance(&market.treasury()), 15);
+}
+
+#[test]
+fn keepers_get_a_lamport_tip() {
+    let mut env = Env::new();
+    let (market, bidder, _) = started(&mut env, english(100));
+    let admin = market.admin.pubkey();
+    let ix = market.manage_ix(
+        &admin,
+        auction::instruction::SetKeeperReward {
+            keeper_reward: KeeperReward::Lamports { amount: 5000 },
+        },
+    );
+    let fund = market.fund_keeper_pool_ix(&admin, Rent::default().minimum_balance(0) + 3000);
+    env.send(&[ix, fund], &market.admin, &[]).unwrap();
+
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    env.warp_to(100);
+
+    // the tip is capped by what the pool holds above its rent, and never
+    // touches the house account
+    let keeper = env.user();
+    let pool = keeper_pool_address(&market.house);
+    let (pool_before, house_before) = (env.lamports(&pool), env.lamports(&market.house));
+    market
+        .finalize(&mut env, &keeper, &bidder.pubkey())
+        .unwrap();
+    assert_eq!(env.lamports(&pool), pool_before - 3000);
+    assert_eq!(env.lamports(&market.house), house_before);
+}
+
+#[test]
+fn sellers_get_no_tip_for_their_own_auctions() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    let admin = market.admin.pubkey();
+    let ix = market.manage_ix(
+        &admin,
+        auction::instruction::SetKeeperReward {
+            keeper_reward: KeeperReward::Lamports { amount: 5000 },
+        },
+    );
+    let fund = market.fund_keeper_pool_ix(&admin, 1_000_000);
+    env.send(&[ix, fund], &market.admin, &[]).unwrap();
+
+    // listing and cancelling expired auctions must not drain the pool
+    market.init(&mut env, english(100)).unwrap();
+    env.warp_to(100);
+    market.cancel_expired(&mut env, &market.seller).unwrap();
+    assert_eq!(env.lamports(&keeper_pool_address(&market.house)), 1_000_000);
+}
+
+#[test]
+fn bids_in_tokens_need_a_token_account() {
+    let mut env = Env::new();
+    let (market, bidder, _) = started(&mut env, english(100));
+
+    let mut ix = market.bid_ix(&bidder.pubkey(), PRICE);
+    // bidder_mint_b_ata left out
+    ix.accounts[5] = AccountMeta::new_readonly(auction::ID, false);
+    assert_error(
+        env.send(&[ix], &bidder, &[]),
+        AuctionError::MissingTokenAccount,
+    );
+}
+
+#[test]
+fn wrong_auction_kind_is_rejected() {
+    let mut env = Env::new();
+    let (market, bidder, _) = started(&mut env, english(100));
+
+    assert_error(
+        market.buy_now(&mut env, &bidder, u64::MAX),
+        AuctionError::WrongAuctionKind,
+    );
+    let (result, _) = market.commit_bid(&mut env, &bidder, PRICE, 2000);
+    assert_error(result, AuctionError::WrongAuctionKind);
+    assert_error(
+        market.batch_bid(&mut env, &bidder, 10, PRICE, None, None),
+        AuctionError::WrongAuctionKind,
+    );
+}
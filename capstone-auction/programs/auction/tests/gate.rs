@@ -0,0 +1,240 @@
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use anchor_lang::AnchorSerialize;
+use anchor_spl::{
+    metadata::mpl_token_metadata::{
+        self,
+        accounts::Metadata,
+        types::{Collection, Key, TokenStandard},
+    },
+    token::spl_token,
+};
+use auction::errors::AuctionError;
+use auction::state::{allowlist_leaf, allowlist_node, Auction, Gate};
+use common::*;
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+const FUNDS: u64 = 1_000_000;
+
+fn gated(env: &mut Env, gate: Gate) -> Market {
+    let market = Market::new(env);
+    market
+        .init(
+            env,
+            auction::instruction::InitAuction {
+                gate: Some(gate),
+                ..english(100)
+            },
+        )
+        .unwrap();
+    market
+}
+
+// writes the metadata of an NFT, verified into `collection` or not
+fn set_metadata(env: &mut Env, mint: &Pubkey, collection: Option<Collection>) {
+    let metadata = Metadata {
+        key: Key::MetadataV1,
+        update_authority: Pubkey::new_unique(),
+        mint: *mint,
+        name: "NFT".to_string(),
+        symbol: String::new(),
+        uri: String::new(),
+        seller_fee_basis_points: 0,
+        creators: None,
+        primary_sale_happened: true,
+        is_mutable: false,
+        edition_nonce: None,
+        token_standard: Some(TokenStandard::NonFungible),
+        collection,
+        uses: None,
+        collection_details: None,
+        programmable_config: None,
+    };
+    env.set_account(
+        metadata_address(mint),
+        mpl_token_metadata::ID,
+        metadata.try_to_vec().unwrap(),
+    );
+}
+
+#[test]
+fn allowlisted_bidders_prove_their_place() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    let allowed = market.bidder(&mut env, FUNDS);
+    let allowed2 = market.bidder(&mut env, FUNDS);
+    let outsider = market.bidder(&mut env, FUNDS);
+    let leaf = allowlist_leaf(&allowed.pubkey());
+    let leaf2 = allowlist_leaf(&allowed2.pubkey());
+    market
+        .init(
+            &mut env,
+            auction::instruction::InitAuction {
+                gate: Some(Gate::Allowlist {
+                    root: allowlist_node(&leaf, &leaf2),
+                }),
+                ..english(100)
+            },
+        )
+        .unwrap();
+
+    assert_error(
+        market.bid(&mut env, &allowed, PRICE),
+        AuctionError::NotAllowedToBid,
+    );
+    // someone else's proof doesn't get an outsider in
+    assert_error(
+        market.gated_bid(&mut env, &outsider, PRICE, vec![leaf2], None, None),
+        AuctionError::NotAllowedToBid,
+    );
+    market
+        .gated_bid(&mut env, &allowed, PRICE, vec![leaf2], None, None)
+        .unwrap();
+    market
+        .gated_bid(&mut env, &allowed2, 3_000_000, vec![leaf], None, None)
+        .unwrap();
+
+    // raising doesn't ask for the proof again
+    market.raise_bid(&mut env, &allowed, 4_000_000).unwrap();
+    let auction: Auction = env.account(&market.auction());
+    assert_eq!(auction.bidder, Some(allowed.pubkey()));
+}
+
+#[test]
+fn token_gates_need_enough_of_the_mint() {
+    let mut env = Env::new();
+    let issuer = env.user();
+    let gate_mint = env.create_mint(&issuer, 0, &spl_token::ID, &[], |_| vec![]);
+    let market = gated(
+        &mut env,
+        Gate::Token {
+            mint: gate_mint,
+            min_amount: 10,
+        },
+    );
+    let holder = market.bidder(&mut env, FUNDS);
+    let small_holder = market.bidder(&mut env, FUNDS);
+    let holder_account = env.mint_to(&gate_mint, &issuer, &holder.pubkey(), 10, &spl_token::ID);
+    let small_account = env.mint_to(
+        &gate_mint,
+        &issuer,
+        &small_holder.pubkey(),
+        9,
+        &spl_token::ID,
+    );
+
+    assert_error(
+        market.bid(&mut env, &holder, PRICE),
+        AuctionError::NotAllowedToBid,
+    );
+    assert_error(
+        market.gated_bid(
+            &mut env,
+            &small_holder,
+            PRICE,
+            vec![],
+            Some(small_account),
+            None,
+        ),
+        AuctionError::NotAllowedToBid,
+    );
+    // the account has to be the bidder's own
+    assert_error(
+        market.gated_bid(
+            &mut env,
+            &small_holder,
+            PRICE,
+            vec![],
+            Some(holder_account),
+            None,
+        ),
+        AuctionError::NotAllowedToBid,
+    );
+    // an account of another mint doesn't count
+    let purchase_account = market.ata(&small_holder.pubkey(), &market.mint_b);
+    assert_error(
+        market.gated_bid(
+            &mut env,
+            &small_holder,
+            PRICE,
+            vec![],
+            Some(purchase_account),
+            None,
+        ),
+        AuctionError::NotAllowedToBid,
+    );
+    market
+        .gated_bid(&mut env, &holder, PRICE, vec![], Some(holder_account), None)
+        .unwrap();
+}
+
+#[test]
+fn collection_gates_need_a_verified_nft() {
+    let mut env = Env::new();
+    let collection = Pubkey::new_unique();
+    let market = gated(&mut env, Gate::Collection { collection });
+    let issuer = env.user();
+    let bidder = market.bidder(&mut env, FUNDS);
+
+    // an NFT someone claims into the collection without verifying it
+    let unverified = env.create_mint(&issuer, 0, &spl_token::ID, &[], |_| vec![]);
+    set_metadata(
+        &mut env,
+        &unverified,
+        Some(Collection {
+            verified: false,
+            key: collection,
+        }),
+    );
+    let unverified_account = env.mint_to(&unverified, &issuer, &bidder.pubkey(), 1, &spl_token::ID);
+    assert_error(
+        market.gated_bid(
+            &mut env,
+            &bidder,
+            PRICE,
+            vec![],
+            Some(unverified_account),
+            Some(metadata_address(&unverified)),
+        ),
+        AuctionError::NotAllowedToBid,
+    );
+
+    let nft = env.create_mint(&issuer, 0, &spl_token::ID, &[], |_| vec![]);
+    set_metadata(
+        &mut env,
+        &nft,
+        Some(Collection {
+            verified: true,
+            key: collection,
+        }),
+    );
+    let nft_account = env.mint_to(&nft, &issuer, &bidder.pubkey(), 1, &spl_token::ID);
+    // the metadata has to be the held NFT's
+    assert_error(
+        market.gated_bid(
+            &mut env,
+            &bidder,
+            PRICE,
+            vec![],
+            Some(unverified_account),
+            Some(metadata_address(&nft)),
+        ),
+        AuctionError::NotAllowedToBid,
+    );
+    assert_error(
+        market.gated_bid(&mut env, &bidder, PRICE, vec![], Some(nft_account), None),
+        AuctionError::NotAllowedToBid,
+    );
+    market
+        .gated_bid(
+            &mut env,
+            &bidder,
+            PRICE,
+            vec![],
+            Some(nft_account),
+            Some(metadata_address(&nft)),
+        )
+        .unwrap();
+}
@@ -0,0 +1,214 @@
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use auction::errors::AuctionError;
+use auction::state::{AuctionHouse, KeeperReward};
+use common::*;
+use solana_sdk::{signer::Signer, system_instruction};
+
+#[test]
+fn init_house_checks_name_fee_and_penalty() {
+    let mut env = Env::new();
+    let admin = env.user();
+
+    let long_name = "x".repeat(32);
+    let ix = init_house_ix(&admin.pubkey(), &long_name, HOUSE_FEE, 0);
+    assert_error(env.send(&[ix], &admin, &[]), AuctionError::NameTooLong);
+
+    let ix = init_house_ix(&admin.pubkey(), "fee", 10_001, 0);
+    assert_error(env.send(&[ix], &admin, &[]), AuctionError::InvalidFee);
+
+    let ix = init_house_ix(&admin.pubkey(), "penalty", HOUSE_FEE, 10_001);
+    assert_error(env.send(&[ix], &admin, &[]), AuctionError::InvalidPenalty);
+
+    let ix = init_house_ix(&admin.pubkey(), "house", HOUSE_FEE, 10_000);
+    env.send(&[ix], &admin, &[]).unwrap();
+    let house: AuctionHouse = env.account(&house_address("house"));
+    assert_eq!(house.admin, admin.pubkey());
+    assert_eq!(house.fee, HOUSE_FEE);
+    assert_eq!(house.cancel_penalty_bps, 10_000);
+    assert!(!house.paused);
+}
+
+#[test]
+fn only_the_admin_updates_the_fee() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    let stranger = env.user();
+
+    let ix = market.manage_ix(
+        &stranger.pubkey(),
+        auction::instruction::UpdateHouseFee { fee: 0 },
+    );
+    assert_error(env.send(&[ix], &stranger, &[]), AuctionError::NotHouseAdmin);
+
+    let ix = market.manage_ix(
+        &market.admin.pubkey(),
+        auction::instruction::UpdateHouseFee { fee: 10_001 },
+    );
+    assert_error(
+        env.send(&[ix], &market.admin, &[]),
+        AuctionError::InvalidFee,
+    );
+
+    let ix = market.manage_ix(
+        &market.admin.pubkey(),
+        auction::instruction::UpdateHouseFee { fee: 250 },
+    );
+    env.send(&[ix], &market.admin, &[]).unwrap();
+    let house: AuctionHouse = env.account(&market.house);
+    assert_eq!(house.fee, 250);
+}
+
+#[test]
+fn admin_changes_hands_once_accepted() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    let new_admin = env.user();
+    let stranger = env.user();
+
+    let ix = market.manage_ix(
+        &market.admin.pubkey(),
+        auction::instruction::ProposeAdmin {
+            pending_admin: new_admin.pubkey(),
+        },
+    );
+    env.send(&[ix], &market.admin, &[]).unwrap();
+
+    let ix = market.accept_admin_ix(&stranger.pubkey());
+    assert_error(env.send(&[ix], &stranger, &[]), AuctionError::NotHouseAdmin);
+
+    let ix = market.accept_admin_ix(&new_admin.pubkey());
+    env.send(&[ix], &new_admin, &[]).unwrap();
+    let house: AuctionHouse = env.account(&market.house);
+    assert_eq!(house.admin, new_admin.pubkey());
+    assert_eq!(house.pending_admin, None);
+
+    // the old admin is out
+    let ix = market.manage_ix(
+        &market.admin.pubkey(),
+        auction::instruction::PauseHouse { paused: true },
+    );
+    assert_error(
+        env.send(&[ix], &market.admin, &[]),
+        AuctionError::NotHouseAdmin,
+    );
+}
+
+#[test]
+fn paused_house_takes_no_auctions_or_bids() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    let bidder = market.bidder(&mut env, 1_000_000);
+    let end = env.slot() + 100;
+    let pause = |paused| {
+        market.manage_ix(
+            &market.admin.pubkey(),
+            auction::instruction::PauseHouse { paused },
+        )
+    };
+
+    env.send(&[pause(true)], &market.admin, &[]).unwrap();
+    assert_error(
+        market.init(&mut env, english(end)),
+        AuctionError::HousePaused,
+    );
+    env.send(&[pause(false)], &market.admin, &[]).unwrap();
+    market.init(&mut env, english(end)).unwrap();
+
+    env.send(&[pause(true)], &market.admin, &[]).unwrap();
+    assert_error(
+        market.bid(&mut env, &bidder, PRICE),
+        AuctionError::HousePaused,
+    );
+    env.send(&[pause(false)], &market.admin, &[]).unwrap();
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+}
+
+#[test]
+fn keeper_fee_share_is_capped() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+
+    let ix = market.manage_ix(
+        &market.admin.pubkey(),
+        auction::instruction::SetKeeperReward {
+            keeper_reward: KeeperReward::FeeShare { bps: 10_001 },
+        },
+    );
+    assert_error(
+        env.send(&[ix], &market.admin, &[]),
+        AuctionError::InvalidFee,
+    );
+
+    let keeper_reward = KeeperReward::Lamports { amount: 5000 };
+    let ix = market.manage_ix(
+        &market.admin.pubkey(),
+        auction::instruction::SetKeeperReward { keeper_reward },
+    );
+    env.send(&[ix], &market.admin, &[]).unwrap();
+    let house: AuctionHouse = env.account(&market.house);
+    assert_eq!(house.keeper_reward, keeper_reward);
+}
+
+#[test]
+fn sweep_lamports_leaves_the_rent() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    let destination = env.user();
+    let admin = market.admin.pubkey();
+
+    let ix = market.sweep_lamports_ix(&admin, &destination.pubkey(), 1);
+    assert_error(
+        env.send(&[ix], &market.admin, &[]),
+        AuctionError::InsufficientHouseLamports,
+    );
+
+    let fund = system_instruction::transfer(&admin, &market.house, 10_000);
+    env.send(&[fund], &market.admin, &[]).unwrap();
+    let stranger = env.user();
+    let ix = market.sweep_lamports_ix(&stranger.pubkey(), &destination.pubkey(), 10_000);
+    assert_error(env.send(&[ix], &stranger, &[]), AuctionError::NotHouseAdmin);
+
+    let before = env.lamports(&destination.pubkey());
+    let ix = market.sweep_lamports_ix(&admin, &destination.pubkey(), 10_000);
+    env.send(&[ix], &market.admin, &[]).unwrap();
+    assert_eq!(env.lamports(&destination.pubkey()), before + 10_000);
+
+    let ix = market.sweep_lamports_ix(&admin, &destination.pubkey(), 1);
+    assert_error(
+        env.send(&[ix], &market.admin, &[]),
+        AuctionError::InsufficientHouseLamports,
+    );
+}
+
+#[test]
+fn admin_funds_and_withdraws_the_keeper_pool() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    let admin = market.admin.pubkey();
+    let pool = keeper_pool_address(&market.house);
+
+    let stranger = env.user();
+    let ix = market.fund_keeper_pool_ix(&stranger.pubkey(), 1_000_000);
+    assert_error(env.send(&[ix], &stranger, &[]), AuctionError::NotHouseAdmin);
+
+    let ix = market.fund_keeper_pool_ix(&admin, 1_000_000);
+    env.send(&[ix], &market.admin, &[]).unwrap();
+    assert_eq!(env.lamports(&pool), 1_000_000);
+    let house_lamports = env.lamports(&market.house);
+
+    let ix = market.withdraw_keeper_pool_ix(&stranger.pubkey(), 1_000_000);
+    assert_error(env.send(&[ix], &stranger, &[]), AuctionError::NotHouseAdmin);
+    let ix = market.withdraw_keeper_pool_ix(&admin, 1_000_001);
+    assert_error(
+        env.send(&[ix], &market.admin, &[]),
+        AuctionError::InsufficientHouseLamports,
+    );
+
+    let ix = market.withdraw_keeper_pool_ix(&admin, 1_000_000);
+    env.send(&[ix], &market.admin, &[]).unwrap();
+    assert_eq!(env.lamports(&pool), 0);
+    assert_eq!(env.lamports(&market.house), house_lamports);
+}
@@ -0,0 +1,228 @@
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use auction::state::EndCondition;
+use common::*;
+use solana_sdk::signer::Signer;
+
+// mint_b is wrapped SOL with 9 decimals: 1000 units at 2 cost 2_000_000 lamports
+
+#[test]
+fn bids_in_lamports_are_refunded_in_lamports() {
+    let mut env = Env::new();
+    let market = Market::native(&mut env);
+    market.init(&mut env, english(100)).unwrap();
+    let bidder = market.bidder(&mut env, 0);
+    let bidder2 = market.bidder(&mut env, 0);
+
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    assert_eq!(
+        env.token_balance(&market.escrow(&bidder.pubkey())),
+        2_000_000
+    );
+    market.bid(&mut env, &bidder2, 3_000_000).unwrap();
+
+    // the escrow's wrapped SOL and both rents come back
+    let refund = env.lamports(&market.escrow(&bidder.pubkey()))
+        + env.lamports(&market.bid_state(&bidder.pubkey()));
+    let before = env.lamports(&bidder.pubkey());
+    market.withdraw(&mut env, &bidder).unwrap();
+    // less the transaction fee
+    assert_eq!(env.lamports(&bidder.pubkey()), before + refund - 5000);
+    assert!(!env.exists(&market.escrow(&bidder.pubkey())));
+}
+
+#[test]
+fn finalize_pays_out_lamports() {
+    let mut env = Env::new();
+    let market = Market::native(&mut env);
+    market.init(&mut env, english(100)).unwrap();
+    let bidder = market.bidder(&mut env, 0);
+    let keeper = env.user();
+    let seller = market.seller.pubkey();
+
+    market.bid(&mut env, &bidder, 3_000_000).unwrap();
+    env.warp_to(100);
+
+    // the seller also gets the rent of the auction and the vault back
+    let seller_before =
+        env.lamports(&seller) + env.lamports(&market.auction()) + env.lamports(&market.vault());
+    let house_before = env.lamports(&market.house);
+    market
+        .finalize(&mut env, &keeper, &bidder.pubkey())
+        .unwrap();
+
+    assert_eq!(env.lamports(&seller), seller_before + 2_970_000);
+    assert_eq!(env.lamports(&market.house), house_before + 30_000);
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_a)),
+        AMOUNT
+    );
+    assert!(!env.exists(&market.bid_state(&bidder.pubkey())));
+    assert!(!env.exists(&market.escrow(&bidder.pubkey())));
+}
+
+#[test]
+fn dutch_lots_are_bought_with_lamports() {
+    let mut env = Env::new();
+    let market = Market::native(&mut env);
+    market
+        .init(
+            &mut env,
+            auction::instruction::InitDutchAuction {
+                seed: 0,
+                start_price: PRICE,
+                floor_price: PRICE,
+                step: 10,
+                end_condition: EndCondition::Slot,
+                end: 100,
+                amount: AMOUNT,
+                decimal: DECIMALS,
+            },
+        )
+        .unwrap();
+    let buyer = market.bidder(&mut env, 0);
+    let seller = market.seller.pubkey();
+
+    // the seller also gets the rent of the auction and the vault back
+    let seller_before =
+        env.lamports(&seller) + env.lamports(&market.auction()) + env.lamports(&market.vault());
+    let house_before = env.lamports(&market.house);
+    market.buy_now(&mut env, &buyer, PRICE).unwrap();
+
+    assert_eq!(env.lamports(&seller), seller_before + 1_980_000);
+    assert_eq!(env.lamports(&market.house), house_before + 20_000);
+    assert_eq!(
+        env.token_balance(&market.ata(&buyer.pubkey(), &market.mint_a)),
+        AMOUNT
+    );
+    // no wrapped SOL accounts to pay rent for
+    assert!(!env.exists(&market.ata(&seller, &market.mint_b)));
+    assert!(!env.exists(&market.treasury()));
+}
+
+#[test]
+fn cancel_early_penalties_are_paid_in_lamports() {
+    let mut env = Env::new();
+    let market = Market::native(&mut env);
+    market.init(&mut env, english(100)).unwrap();
+    let bidder = market.bidder(&mut env, 0);
+    market.bid(&mut env, &bidder, 3_000_000).unwrap();
+
+    // the seller gets the vault's rent back and pays 5% of the bid, and the
+    // transaction fee
+    let seller_before = env.lamports(&market.seller.pubkey()) + env.lamports(&market.vault());
+    market
+        .cancel_early(&mut env, Some(&bidder.pubkey()))
+        .unwrap();
+    assert_eq!(
+        env.lamports(&market.seller.pubkey()),
+        seller_before - 150_000 - 5000
+    );
+    assert_eq!(
+        env.token_balance(&market.escrow(&bidder.pubkey())),
+        3_150_000
+    );
+
+    let before = env.lamports(&bidder.pubkey());
+    let refund = env.lamports(&market.escrow(&bidder.pubkey()))
+        + env.lamports(&market.bid_state(&bidder.pubkey()));
+    market.withdraw(&mut env, &bidder).unwrap();
+    assert_eq!(env.lamports(&bidder.pubkey()), before + refund - 5000);
+}
+
+#[test]
+fn batch_fills_are_paid_in_lamports() {
+    let mut env = Env::new();
+    let market = Market::native(&mut env);
+    market
+        .init(
+            &mut env,
+            auction::instruction::InitBatchAuction {
+                seed: 0,
+                min_price: PRICE,
+                end_condition: EndCondition::Slot,
+                end: 100,
+                amount: AMOUNT,
+                decimal: DECIMALS,
+            },
+        )
+        .unwrap();
+    let bidder = market.bidder(&mut env, 0);
+    let keeper = env.user();
+    let seller = market.seller.pubkey();
+    market
+        .batch_bid(&mut env, &bidder, AMOUNT, PRICE, None, None)
+        .unwrap();
+    env.warp_to(100);
+    market
+        .tally_batch(&mut env, &keeper, &[bidder.pubkey()])
+        .unwrap();
+
+    // the seller also gets the rent of the auction and the vault back, the
+    // bidder that of the escrow and the bid state
+    let seller_before =
+        env.lamports(&seller) + env.lamports(&market.auction()) + env.lamports(&market.vault());
+    let bidder_before = env.lamports(&bidder.pubkey())
+        + env.lamports(&market.escrow(&bidder.pubkey()))
+        + env.lamports(&market.bid_state(&bidder.pubkey()));
+    let house_before = env.lamports(&market.house);
+    market
+        .settle_batch(&mut env, &keeper, &[bidder.pubkey()])
+        .unwrap();
+
+    assert_eq!(env.lamports(&seller), seller_before + 1_980_000);
+    assert_eq!(env.lamports(&market.house), house_before + 20_000);
+    assert_eq!(env.lamports(&bidder.pubkey()), bidder_before - 2_000_000);
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_a)),
+        AMOUNT
+    );
+    assert!(!env.exists(&market.bid_state(&bidder.pubkey())));
+    assert!(!env.exists(&market.escrow(&bidder.pubkey())));
+    assert!(!env.exists(&market.treasury()));
+}
+
+#[test]
+fn sealed_penalties_are_paid_in_lamports() {
+    let mut env = Env::new();
+    let market = Market::native(&mut env);
+    market
+        .init(
+            &mut env,
+            auction::instruction::InitSealedAuction {
+                seed: 0,
+                starting_price: PRICE,
+                end_condition: EndCondition::Slot,
+                end: 100,
+                reveal_end: 200,
+                amount: AMOUNT,
+                decimal: DECIMALS,
+                second_price: false,
+                penalty_bps: 1000,
+            },
+        )
+        .unwrap();
+    let bidder = market.bidder(&mut env, 0);
+    let keeper = env.user();
+    let seller = market.seller.pubkey();
+    let (result, _) = market.commit_bid(&mut env, &bidder, 3_000_000, 3_000_000);
+    result.unwrap();
+    env.warp_to(200);
+
+    // the rest of the deposit and both rents go straight back
+    let seller_before = env.lamports(&seller);
+    let bidder_before = env.lamports(&bidder.pubkey())
+        + env.lamports(&market.escrow(&bidder.pubkey()))
+        + env.lamports(&market.bid_state(&bidder.pubkey()));
+    market
+        .penalize_bid(&mut env, &keeper, &bidder.pubkey())
+        .unwrap();
+
+    assert_eq!(env.lamports(&seller), seller_before + 300_000);
+    assert_eq!(env.lamports(&bidder.pubkey()), bidder_before - 300_000);
+    assert!(!env.exists(&market.bid_state(&bidder.pubkey())));
+    assert!(!env.exists(&market.escrow(&bidder.pubkey())));
+    assert!(!env.exists(&market.ata(&seller, &market.mint_b)));
+}
@@ -0,0 +1,358 @@
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use anchor_lang::AnchorSerialize;
+use anchor_spl::{
+    metadata::mpl_token_metadata::{
+        self,
+        accounts::Metadata,
+        types::{Creator, Key, TokenStandard},
+    },
+    token::spl_token,
+};
+use auction::errors::AuctionError;
+use auction::state::EndCondition;
+use common::*;
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+// The token metadata program isn't loaded, the metadata accounts are written
+// directly. That covers royalties and the checks made before any pNFT CPI.
+
+const FUNDS: u64 = 10_000_000;
+
+// a single NFT with 5% royalties
+fn nft_market(env: &mut Env, token_standard: TokenStandard, creators: Vec<Creator>) -> Market {
+    let authority = env.user();
+    let mint_b = env.create_mint(&authority, DECIMALS, &spl_token::ID, &[], |_| vec![]);
+    nft_market_in(env, authority, mint_b, token_standard, creators)
+}
+
+fn nft_market_in(
+    env: &mut Env,
+    authority: Keypair,
+    mint_b: Pubkey,
+    token_standard: TokenStandard,
+    creators: Vec<Creator>,
+) -> Market {
+    let mint_a = env.create_mint(&authority, 0, &spl_token::ID, &[], |_| vec![]);
+
+    let metadata = Metadata {
+        key: Key::MetadataV1,
+        update_authority: authority.pubkey(),
+        mint: mint_a,
+        name: "NFT".to_string(),
+        symbol: String::new(),
+        uri: String::new(),
+        seller_fee_basis_points: 500,
+        creators: Some(creators),
+        primary_sale_happened: true,
+        is_mutable: false,
+        edition_nonce: None,
+        token_standard: Some(token_standard),
+        collection: None,
+        uses: None,
+        collection_details: None,
+        programmable_config: None,
+    };
+    env.set_account(
+        metadata_address(&mint_a),
+        mpl_token_metadata::ID,
+        metadata.try_to_vec().unwrap(),
+    );
+
+    let market = Market::with_mints(env, authority, mint_a, mint_b, spl_token::ID);
+    market.fund_seller(env, 1);
+    market
+}
+
+fn creator(address: Pubkey, share: u8) -> Creator {
+    Creator {
+        address,
+        verified: true,
+        share,
+    }
+}
+
+fn one_nft(end: u64) -> auction::instruction::InitAuction {
+    auction::instruction::InitAuction {
+        amount: 1,
+        ..english(end)
+    }
+}
+
+#[test]
+fn creators_get_their_royalties() {
+    let mut env = Env::new();
+    let [first, second, unpaid] = [(); 3].map(|_| Pubkey::new_unique());
+    let market = nft_market(
+        &mut env,
+        TokenStandard::NonFungible,
+        vec![creator(first, 70), creator(second, 30), creator(unpaid, 0)],
+    );
+    market.init(&mut env, one_nft(100)).unwrap();
+    let bidder = market.bidder(&mut env, FUNDS);
+    let keeper = env.user();
+    let seller = market.seller.pubkey();
+
+    // the NFT sells for 2_000_000
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    env.warp_to(100);
+
+    let royalty_accounts = |creators: &[Pubkey]| {
+        creators
+            .iter()
+            .flat_map(|creator| {
+                [
+                    AccountMeta::new_readonly(*creator, false),
+                    AccountMeta::new(market.ata(creator, &market.mint_b), false),
+                ]
+            })
+            .collect::<Vec<_>>()
+    };
+    for creators in [vec![], vec![first], vec![second, first]] {
+        let ix = market.finalize_ix(
+            &keeper.pubkey(),
+            &bidder.pubkey(),
+            None,
+            royalty_accounts(&creators),
+        );
+        assert_error(
+            env.send(&[ix], &keeper, &[]),
+            AuctionError::InvalidCreatorAccount,
+        );
+    }
+    // each creator's ATA, not just any account
+    let mut accounts = royalty_accounts(&[first, second]);
+    accounts[1] = AccountMeta::new(market.ata(&second, &market.mint_b), false);
+    let ix = market.finalize_ix(&keeper.pubkey(), &bidder.pubkey(), None, accounts);
+    assert_error(
+        env.send(&[ix], &keeper, &[]),
+        AuctionError::InvalidCreatorAccount,
+    );
+
+    let ix = market.finalize_ix(
+        &keeper.pubkey(),
+        &bidder.pubkey(),
+        None,
+        royalty_accounts(&[first, second]),
+    );
+    env.send(&[ix], &keeper, &[]).unwrap();
+
+    // 5% royalties and the 1% house fee come out of the seller's proceeds
+    assert_eq!(
+        env.token_balance(&market.ata(&first, &market.mint_b)),
+        70_000
+    );
+    assert_eq!(
+        env.token_balance(&market.ata(&second, &market.mint_b)),
+        30_000
+    );
+    assert_eq!(env.token_balance(&market.treasury()), 20_000);
+    assert_eq!(
+        env.token_balance(&market.ata(&seller, &market.mint_b)),
+        1_880_000
+    );
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_a)),
+        1
+    );
+}
+
+#[test]
+fn royalties_too_small_for_an_empty_wallet_go_to_the_seller() {
+    let mut env = Env::new();
+    let paid = env.user().pubkey();
+    let empty = Pubkey::new_unique();
+    let authority = env.user();
+    let market = nft_market_in(
+        &mut env,
+        authority,
+        spl_token::native_mint::ID,
+        TokenStandard::NonFungible,
+        vec![creator(paid, 70), creator(empty, 30)],
+    );
+    // the NFT sells for 2_000_000 lamports
+    let init = auction::instruction::InitAuction {
+        starting_price: 2000,
+        ..one_nft(100)
+    };
+    market.init(&mut env, init).unwrap();
+    let bidder = market.bidder(&mut env, 0);
+    market.bid(&mut env, &bidder, 2000).unwrap();
+    env.warp_to(100);
+
+    let keeper = env.user();
+    let seller = market.seller.pubkey();
+    let seller_before =
+        env.lamports(&seller) + env.lamports(&market.auction()) + env.lamports(&market.vault());
+    let paid_before = env.lamports(&paid);
+    let creators = vec![
+        AccountMeta::new(paid, false),
+        AccountMeta::new(empty, false),
+    ];
+    let ix = market.finalize_ix(&keeper.pubkey(), &bidder.pubkey(), None, creators);
+    env.send(&[ix], &keeper, &[]).unwrap();
+
+    // 30_000 lamports can't make an empty wallet rent exempt
+    assert_eq!(env.lamports(&paid), paid_before + 70_000);
+    assert!(!env.exists(&empty));
+    assert_eq!(env.lamports(&seller), seller_before + 1_880_000 + 30_000);
+}
+
+#[test]
+fn programmable_nfts_need_their_accounts() {
+    let mut env = Env::new();
+    let market = nft_market(
+        &mut env,
+        TokenStandard::ProgrammableNonFungible,
+        vec![creator(Pubkey::new_unique(), 100)],
+    );
+
+    assert_error(
+        market.init(&mut env, one_nft(100)),
+        AuctionError::MissingProgrammableAccounts,
+    );
+}
+
+#[test]
+fn programmable_nfts_only_sell_in_english_and_sealed_auctions() {
+    let mut env = Env::new();
+    let market = nft_market(
+        &mut env,
+        TokenStandard::ProgrammableNonFungible,
+        vec![creator(Pubkey::new_unique(), 100)],
+    );
+
+    assert_error(
+        market.init(
+            &mut env,
+            auction::instruction::InitDutchAuction {
+                seed: 0,
+                start_price: PRICE,
+                floor_price: PRICE,
+                step: 1,
+                end_condition: EndCondition::Slot,
+                end: 100,
+                amount: 1,
+                decimal: DECIMALS,
+            },
+        ),
+        AuctionError::ProgrammableNftUnsupported,
+    );
+    assert_error(
+        market.init(
+            &mut env,
+            auction::instruction::InitAuction {
+                buy_now_price: PRICE * 2,
+                ..one_nft(100)
+            },
+        ),
+        AuctionError::ProgrammableNftUnsupported,
+    );
+}
+
+#[test]
+fn royalty_lots_only_sell_in_english_and_sealed_auctions() {
+    let mut env = Env::new();
+    let market = nft_market(
+        &mut env,
+        TokenStandard::NonFungible,
+        vec![creator(Pubkey::new_unique(), 100)],
+    );
+
+    assert_error(
+        market.init(
+            &mut env,
+            auction::instruction::InitDutchAuction {
+                seed: 0,
+                start_price: PRICE,
+                floor_price: PRICE,
+                step: 1,
+                end_condition: EndCondition::Slot,
+                end: 100,
+                amount: 1,
+                decimal: DECIMALS,
+            },
+        ),
+        AuctionError::RoyaltiesUnsupported,
+    );
+    assert_error(
+        market.init(
+            &mut env,
+            auction::instruction::InitBatchAuction {
+                seed: 0,
+                min_price: PRICE,
+                end_condition: EndCondition::Slot,
+                end: 100,
+                amount: 1,
+                decimal: DECIMALS,
+            },
+        ),
+        AuctionError::RoyaltiesUnsupported,
+    );
+}
+
+#[test]
+fn lots_without_royalties_sell_in_dutch_auctions() {
+    let mut env = Env::new();
+    let market = nft_market(
+        &mut env,
+        TokenStandard::NonFungible,
+        vec![creator(Pubkey::new_unique(), 0)],
+    );
+
+    market
+        .init(
+            &mut env,
+            auction::instruction::InitDutchAuction {
+                seed: 0,
+                start_price: PRICE,
+                floor_price: PRICE,
+                step: 1,
+                end_condition: EndCondition::Slot,
+                end: 100,
+                amount: 1,
+                decimal: DECIMALS,
+            },
+        )
+        .unwrap();
+}
+
+// the lot was deposited as a plain NFT, then made programmable, so the
+// cancel instructions have to go through the token metadata program
+fn make_programmable(env: &mut Env, market: &Market) {
+    let address = metadata_address(&market.mint_a);
+    let mut metadata =
+        Metadata::safe_deserialize(&env.svm.get_account(&address).unwrap().data).unwrap();
+    metadata.token_standard = Some(TokenStandard::ProgrammableNonFungible);
+    env.set_account(
+        address,
+        mpl_token_metadata::ID,
+        metadata.try_to_vec().unwrap(),
+    );
+}
+
+#[test]
+fn programmable_nfts_need_their_accounts_to_be_cancelled() {
+    let mut env = Env::new();
+    let market = nft_market(
+        &mut env,
+        TokenStandard::NonFungible,
+        vec![creator(Pubkey::new_unique(), 100)],
+    );
+    market.init(&mut env, one_nft(100)).unwrap();
+    make_programmable(&mut env, &market);
+
+    assert_error(
+        market.cancel_early(&mut env, None),
+        AuctionError::MissingProgrammableAccounts,
+    );
+
+    env.warp_to(100);
+    let keeper = env.user();
+    assert_error(
+        market.cancel_expired(&mut env, &keeper),
+        AuctionError::MissingProgrammableAccounts,
+    );
+}
@@ -0,0 +1,184 @@
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use auction::errors::AuctionError;
+use auction::state::{Auction, BidState};
+use common::*;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+const FUNDS: u64 = 1_000_000;
+
+fn started(env: &mut Env) -> (Market, Keypair, Keypair) {
+    let market = Market::new(env);
+    market
+        .init(
+            env,
+            auction::instruction::InitAuction {
+                min_increment: 100_000,
+                ..english(100)
+            },
+        )
+        .unwrap();
+    let bidder = market.bidder(env, FUNDS);
+    let bidder2 = market.bidder(env, FUNDS);
+    (market, bidder, bidder2)
+}
+
+fn auction(env: &Env, market: &Market) -> Auction {
+    env.account(&market.auction())
+}
+
+#[test]
+fn proxies_hold_the_lead_up_to_their_max() {
+    let mut env = Env::new();
+    let (market, bidder, bidder2) = started(&mut env);
+
+    assert_error(
+        market.proxy_bid(&mut env, &bidder, 3_000_000, 2_900_000),
+        AuctionError::MaxPriceTooLow,
+    );
+    market
+        .proxy_bid(&mut env, &bidder, PRICE, 5_000_000)
+        .unwrap();
+    // the escrow covers the max, the visible price stays at the bid
+    assert_eq!(env.token_balance(&market.escrow(&bidder.pubkey())), 5000);
+    assert_eq!(auction(&env, &market).highest_price, PRICE);
+    let bid_state: BidState = env.account(&market.bid_state(&bidder.pubkey()));
+    assert_eq!(bid_state.max_price, Some(5_000_000));
+
+    // a lower bid is placed, and outbid by one increment right away
+    market.bid(&mut env, &bidder2, 3_000_000).unwrap();
+    let state = auction(&env, &market);
+    assert_eq!(state.bidder, Some(bidder.pubkey()));
+    assert_eq!(state.highest_price, 3_100_000);
+    market.withdraw(&mut env, &bidder2).unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder2.pubkey(), &market.mint_b)),
+        FUNDS
+    );
+
+    // a tie goes to the proxy, which bid first
+    market.bid(&mut env, &bidder2, 5_000_000).unwrap();
+    let state = auction(&env, &market);
+    assert_eq!(state.bidder, Some(bidder.pubkey()));
+    assert_eq!(state.highest_price, 5_000_000);
+
+    // above the max the proxy is outbid like any other bid
+    market.raise_bid(&mut env, &bidder2, 5_100_000).unwrap();
+    let state = auction(&env, &market);
+    assert_eq!(state.bidder, Some(bidder2.pubkey()));
+    assert_eq!(state.highest_price, 5_100_000);
+    assert_eq!(state.proxy_max, None);
+    market.withdraw(&mut env, &bidder).unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_b)),
+        FUNDS
+    );
+}
+
+#[test]
+fn proxies_outbid_proxies_just_above_their_max() {
+    let mut env = Env::new();
+    let (market, bidder, bidder2) = started(&mut env);
+
+    market
+        .proxy_bid(&mut env, &bidder, PRICE, 3_000_000)
+        .unwrap();
+    market
+        .proxy_bid(&mut env, &bidder2, 2_100_000, 6_000_000)
+        .unwrap();
+    let state = auction(&env, &market);
+    assert_eq!(state.bidder, Some(bidder2.pubkey()));
+    assert_eq!(state.highest_price, 3_100_000);
+    assert_eq!(state.proxy_max, Some(6_000_000));
+
+    // the outbid proxy comes back with a higher max, still below the leader's
+    market
+        .proxy_raise_bid(&mut env, &bidder, 3_200_000, Some(4_000_000))
+        .unwrap();
+    assert_eq!(env.token_balance(&market.escrow(&bidder.pubkey())), 4000);
+    let state = auction(&env, &market);
+    assert_eq!(state.bidder, Some(bidder2.pubkey()));
+    assert_eq!(state.highest_price, 4_100_000);
+
+    // the leader may raise their max, a raise without one ends the proxy
+    market
+        .proxy_raise_bid(&mut env, &bidder2, 4_200_000, Some(7_000_000))
+        .unwrap();
+    assert_eq!(env.token_balance(&market.escrow(&bidder2.pubkey())), 7000);
+    let state = auction(&env, &market);
+    assert_eq!(state.highest_price, 4_100_000);
+    assert_eq!(state.proxy_max, Some(7_000_000));
+    market.raise_bid(&mut env, &bidder2, 4_300_000).unwrap();
+    let state = auction(&env, &market);
+    assert_eq!(state.highest_price, 4_300_000);
+    assert_eq!(state.proxy_max, None);
+}
+
+#[test]
+fn leaders_raising_their_max_keep_their_price() {
+    let mut env = Env::new();
+    let (market, bidder, bidder2) = started(&mut env);
+
+    market
+        .proxy_bid(&mut env, &bidder, PRICE, 3_000_000)
+        .unwrap();
+    market
+        .proxy_raise_bid(&mut env, &bidder, PRICE, Some(5_000_000))
+        .unwrap();
+    let state = auction(&env, &market);
+    assert_eq!(state.bidder, Some(bidder.pubkey()));
+    assert_eq!(state.highest_price, PRICE);
+    assert_eq!(state.proxy_max, Some(5_000_000));
+    let bid_state: BidState = env.account(&market.bid_state(&bidder.pubkey()));
+    assert_eq!(bid_state.price, PRICE);
+    assert_eq!(bid_state.max_price, Some(5_000_000));
+    assert_eq!(env.token_balance(&market.escrow(&bidder.pubkey())), 5000);
+
+    // the max can't drop below the price already reached
+    market.bid(&mut env, &bidder2, 3_000_000).unwrap();
+    assert_eq!(auction(&env, &market).highest_price, 3_100_000);
+    assert_error(
+        market.proxy_raise_bid(&mut env, &bidder, PRICE, Some(3_000_000)),
+        AuctionError::MaxPriceTooLow,
+    );
+
+    // the new max still holds off rivals
+    market.raise_bid(&mut env, &bidder2, 4_000_000).unwrap();
+    let state = auction(&env, &market);
+    assert_eq!(state.bidder, Some(bidder.pubkey()));
+    assert_eq!(state.highest_price, 4_100_000);
+}
+
+#[test]
+fn proxy_winners_pay_the_visible_price() {
+    let mut env = Env::new();
+    let (market, bidder, bidder2) = started(&mut env);
+    let seller = market.seller.pubkey();
+
+    market
+        .proxy_bid(&mut env, &bidder, PRICE, 5_000_000)
+        .unwrap();
+    market.bid(&mut env, &bidder2, 3_000_000).unwrap();
+
+    env.warp_to(100);
+    market
+        .finalize(&mut env, &market.seller, &bidder.pubkey())
+        .unwrap();
+
+    // 3100 less the 1% house fee, the rest of the escrow goes back
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_a)),
+        AMOUNT
+    );
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_b)),
+        FUNDS - 3100
+    );
+    assert_eq!(env.token_balance(&market.treasury()), 31);
+    assert_eq!(
+        env.token_balance(&market.ata(&seller, &market.mint_b)),
+        3069
+    );
+}
@@ -0,0 +1,341 @@
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use auction::errors::AuctionError;
+use auction::state::{Auction, AuctionKind, EndCondition};
+use common::*;
+use solana_sdk::signer::Signer;
+
+const FUNDS: u64 = 1_000_000;
+
+// bids are committed until slot 100 and revealed until slot 200
+fn sealed(second_price: bool) -> auction::instruction::InitSealedAuction {
+    auction::instruction::InitSealedAuction {
+        seed: 0,
+        starting_price: PRICE,
+        end_condition: EndCondition::Slot,
+        end: 100,
+        reveal_end: 200,
+        amount: AMOUNT,
+        decimal: DECIMALS,
+        second_price,
+        penalty_bps: 1000,
+    }
+}
+
+#[test]
+fn init_checks_the_schedule() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+
+    assert_error(
+        market.init(
+            &mut env,
+            auction::instruction::InitSealedAuction {
+                reveal_end: 100,
+                ..sealed(false)
+            },
+        ),
+        AuctionError::InvalidSchedule,
+    );
+    assert_error(
+        market.init(
+            &mut env,
+            auction::instruction::InitSealedAuction {
+                penalty_bps: 10_001,
+                ..sealed(false)
+            },
+        ),
+        AuctionError::InvalidSchedule,
+    );
+    env.warp_to(100);
+    assert_error(
+        market.init(&mut env, sealed(false)),
+        AuctionError::InvalidEnd,
+    );
+}
+
+#[test]
+fn bids_are_committed_then_revealed() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    market.init(&mut env, sealed(false)).unwrap();
+    let bidder = market.bidder(&mut env, FUNDS);
+    let late = market.bidder(&mut env, FUNDS);
+
+    assert_error(
+        market.bid(&mut env, &bidder, PRICE),
+        AuctionError::WrongAuctionKind,
+    );
+    let (result, salt) = market.commit_bid(&mut env, &bidder, 3_000_000, 2000);
+    result.unwrap();
+    // the deposit doesn't give the price away
+    assert_eq!(env.token_balance(&market.escrow(&bidder.pubkey())), 2000);
+    assert_error(
+        market.reveal_bid(&mut env, &bidder, 3_000_000, salt),
+        AuctionError::NotInRevealPhase,
+    );
+
+    env.warp_to(100);
+    let (result, _) = market.commit_bid(&mut env, &late, PRICE, 2000);
+    assert_error(result, AuctionError::AuctionEnded);
+
+    assert_error(
+        market.reveal_bid(&mut env, &bidder, 3_000_000, [0; 32]),
+        AuctionError::InvalidCommitment,
+    );
+    // 3000 are due, the deposit only covers 2000
+    assert_error(
+        market.reveal_bid(&mut env, &bidder, 3_000_000, salt),
+        AuctionError::InsufficientCollateral,
+    );
+}
+
+#[test]
+fn reveals_close_with_the_reveal_phase() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    market.init(&mut env, sealed(false)).unwrap();
+    let bidder = market.bidder(&mut env, FUNDS);
+
+    let (result, salt) = market.commit_bid(&mut env, &bidder, PRICE, 2000);
+    result.unwrap();
+    env.warp_to(200);
+    assert_error(
+        market.reveal_bid(&mut env, &bidder, PRICE, salt),
+        AuctionError::NotInRevealPhase,
+    );
+}
+
+#[test]
+fn winner_pays_the_second_price() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    market.init(&mut env, sealed(true)).unwrap();
+    let bidder = market.bidder(&mut env, FUNDS);
+    let bidder2 = market.bidder(&mut env, FUNDS);
+    let seller = market.seller.pubkey();
+
+    let (result, salt) = market.commit_bid(&mut env, &bidder, 3_000_000, 4000);
+    result.unwrap();
+    let (result, salt2) = market.commit_bid(&mut env, &bidder2, 2_500_000, 3000);
+    result.unwrap();
+
+    env.warp_to(100);
+    market
+        .reveal_bid(&mut env, &bidder2, 2_500_000, salt2)
+        .unwrap();
+    market
+        .reveal_bid(&mut env, &bidder, 3_000_000, salt)
+        .unwrap();
+    let auction: Auction = env.account(&market.auction());
+    assert_eq!(auction.bidder, Some(bidder.pubkey()));
+    assert_eq!(auction.highest_price, 3_000_000);
+    assert!(matches!(
+        auction.kind,
+        AuctionKind::Sealed {
+            second_highest: 2_500_000,
+            ..
+        }
+    ));
+
+    assert_error(
+        market.finalize(&mut env, &market.seller, &bidder.pubkey()),
+        AuctionError::NotEligibleToWithdraw,
+    );
+    env.warp_to(200);
+    market
+        .finalize(&mut env, &market.seller, &bidder.pubkey())
+        .unwrap();
+
+    // 2500 less the 1% fee, the rest of the deposit goes back
+    assert_eq!(
+        env.token_balance(&market.ata(&seller, &market.mint_b)),
+        2475
+    );
+    assert_eq!(env.token_balance(&market.treasury()), 25);
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_b)),
+        FUNDS - 2500
+    );
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_a)),
+        AMOUNT
+    );
+
+    market.withdraw(&mut env, &bidder2).unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder2.pubkey(), &market.mint_b)),
+        FUNDS
+    );
+}
+
+#[test]
+fn unrevealed_bids_are_penalized() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    market.init(&mut env, sealed(false)).unwrap();
+    let bidder = market.bidder(&mut env, FUNDS);
+    let seller = market.seller.pubkey();
+
+    let (result, _) = market.commit_bid(&mut env, &bidder, 3_000_000, 3000);
+    result.unwrap();
+
+    // stuck once the commit phase is over
+    env.warp_to(100);
+    assert_error(
+        market.withdraw(&mut env, &bidder),
+        AuctionError::NotEligibleToWithdraw,
+    );
+    assert_error(
+        market.penalize_bid(&mut env, &market.seller, &bidder.pubkey()),
+        AuctionError::NotEligibleToWithdraw,
+    );
+
+    env.warp_to(200);
+    market
+        .penalize_bid(&mut env, &market.seller, &bidder.pubkey())
+        .unwrap();
+    assert_eq!(env.token_balance(&market.ata(&seller, &market.mint_b)), 300);
+    assert_error(
+        market.penalize_bid(&mut env, &market.seller, &bidder.pubkey()),
+        AuctionError::NotEligibleToWithdraw,
+    );
+
+    market.withdraw(&mut env, &bidder).unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_b)),
+        FUNDS - 300
+    );
+    market.cancel(&mut env).unwrap();
+}
+
+#[test]
+fn committed_bids_withdraw_before_the_end() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    market.init(&mut env, sealed(false)).unwrap();
+    let bidder = market.bidder(&mut env, FUNDS);
+
+    let (result, _) = market.commit_bid(&mut env, &bidder, 3_000_000, 3000);
+    result.unwrap();
+    market.withdraw(&mut env, &bidder).unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_b)),
+        FUNDS
+    );
+
+    // the withdrawn commitment no longer holds the auction open
+    env.warp_to(200);
+    market.cancel(&mut env).unwrap();
+}
+
+#[test]
+fn unrevealed_bids_hold_up_settlement() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    market.init(&mut env, sealed(false)).unwrap();
+    let bidder = market.bidder(&mut env, FUNDS);
+    let bidder2 = market.bidder(&mut env, FUNDS);
+    let keeper = env.user();
+
+    let (result, salt) = market.commit_bid(&mut env, &bidder, PRICE, 2000);
+    result.unwrap();
+    let (result, _) = market.commit_bid(&mut env, &bidder2, 3_000_000, 3000);
+    result.unwrap();
+    env.warp_to(100);
+    market.reveal_bid(&mut env, &bidder, PRICE, salt).unwrap();
+    let auction: Auction = env.account(&market.auction());
+    assert!(matches!(
+        auction.kind,
+        AuctionKind::Sealed { unrevealed: 1, .. }
+    ));
+
+    // settling would close the auction and let the unrevealed bid go unpunished
+    env.warp_to(200);
+    assert_error(
+        market.finalize(&mut env, &keeper, &bidder.pubkey()),
+        AuctionError::UnrevealedBids,
+    );
+    assert_error(
+        market.withdraw(&mut env, &bidder2),
+        AuctionError::NotEligibleToWithdraw,
+    );
+
+    // keepers can penalize on the seller's behalf
+    market
+        .penalize_bid(&mut env, &keeper, &bidder2.pubkey())
+        .unwrap();
+    market
+        .finalize(&mut env, &keeper, &bidder.pubkey())
+        .unwrap();
+    market.withdraw(&mut env, &bidder2).unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder2.pubkey(), &market.mint_b)),
+        FUNDS - 300
+    );
+}
+
+#[test]
+fn unrevealed_bids_keep_the_auction_from_expiring() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    market.init(&mut env, sealed(false)).unwrap();
+    let bidder = market.bidder(&mut env, FUNDS);
+    let keeper = env.user();
+
+    let (result, _) = market.commit_bid(&mut env, &bidder, 3_000_000, 3000);
+    result.unwrap();
+    env.warp_to(200);
+    assert_error(
+        market.cancel_expired(&mut env, &keeper),
+        AuctionError::NotEligibleToWithdraw,
+    );
+
+    market
+        .penalize_bid(&mut env, &keeper, &bidder.pubkey())
+        .unwrap();
+    market.cancel_expired(&mut env, &keeper).unwrap();
+    market.withdraw(&mut env, &bidder).unwrap();
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder.pubkey(), &market.mint_b)),
+        FUNDS - 300
+    );
+}
+
+#[test]
+fn penalties_only_apply_to_sealed_auctions() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    market.init(&mut env, english(100)).unwrap();
+    let bidder = market.bidder(&mut env, FUNDS);
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+
+    assert_error(
+        market.penalize_bid(&mut env, &market.seller, &bidder.pubkey()),
+        AuctionError::WrongAuctionKind,
+    );
+    let auction_end = env.account::<Auction>(&market.auction()).end;
+    env.warp_to(auction_end);
+    assert_error(
+        market.reveal_bid(&mut env, &bidder, PRICE, [0; 32]),
+        AuctionError::InvalidCommitment,
+    );
+}
+
+#[test]
+fn cancel_early_stops_at_the_reveal() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    market.init(&mut env, sealed(false)).unwrap();
+    let bidder = market.bidder(&mut env, FUNDS);
+    let (result, _) = market.commit_bid(&mut env, &bidder, 3_000_000, 3000);
+    result.unwrap();
+
+    env.warp_to(100);
+    assert_error(
+        market.cancel_early(&mut env, None),
+        AuctionError::AuctionEnded,
+    );
+}
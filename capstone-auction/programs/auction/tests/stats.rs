@@ -0,0 +1,143 @@
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use anchor_lang::Space;
+use auction::state::{MintVolume, UserStats};
+use common::*;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+const FUNDS: u64 = 1_000_000;
+
+fn stats(env: &Env, market: &Market, wallet: &Keypair) -> UserStats {
+    env.account(&market.stats(&wallet.pubkey()))
+}
+
+#[test]
+fn stats_outlive_auctions_and_bids() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    let lot = market.lot(1);
+    market.init(&mut env, english(100)).unwrap();
+    lot.init(
+        &mut env,
+        auction::instruction::InitAuction {
+            seed: 1,
+            ..english(100)
+        },
+    )
+    .unwrap();
+    let bidder = market.bidder(&mut env, FUNDS);
+    let bidder2 = market.bidder(&mut env, FUNDS);
+    env.mint_to(
+        &market.mint_b,
+        &market.mint_authority,
+        &market.seller.pubkey(),
+        FUNDS,
+        &market.token_program,
+    );
+
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    lot.bid(&mut env, &bidder, PRICE).unwrap();
+    lot.bid(&mut env, &bidder2, 3_000_000).unwrap();
+    // raising is still the one bid
+    market.raise_bid(&mut env, &bidder, 3_000_000).unwrap();
+    let paid = env.token_balance(&market.escrow(&bidder.pubkey()));
+
+    lot.cancel_early(&mut env, Some(&bidder2.pubkey())).unwrap();
+    env.warp_to(100);
+    market
+        .finalize(&mut env, &market.seller, &bidder.pubkey())
+        .unwrap();
+    assert!(!env.exists(&market.auction()));
+    assert!(!env.exists(&market.bid_state(&bidder.pubkey())));
+
+    let seller = stats(&env, &market, &market.seller);
+    assert_eq!(seller.wallet, market.seller.pubkey());
+    assert_eq!(seller.auction_house, market.house);
+    assert_eq!(seller.auctions_created, 2);
+    assert_eq!(seller.auctions_completed, 1);
+    assert_eq!(seller.auctions_cancelled, 1);
+    assert_eq!(seller.bids_placed, 0);
+    assert_eq!(seller.volume(&market.mint_b), Some(paid));
+
+    let winner = stats(&env, &market, &bidder);
+    assert_eq!(winner.bids_placed, 2);
+    assert_eq!(winner.auctions_won, 1);
+    assert_eq!(winner.volume(&market.mint_b), Some(paid));
+
+    let loser = stats(&env, &market, &bidder2);
+    assert_eq!(loser.bids_placed, 1);
+    assert_eq!(loser.auctions_won, 0);
+    assert_eq!(loser.volume(&market.mint_b), Some(0));
+}
+
+#[test]
+fn stats_grow_with_each_quote_mint() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    let other_mint = env.create_mint(
+        &market.mint_authority,
+        DECIMALS,
+        &market.token_program,
+        &[],
+        |_| vec![],
+    );
+    let other = Market {
+        mint_b: other_mint,
+        ..market.lot(0)
+    };
+    let address = market.stats(&market.seller.pubkey());
+
+    market.init(&mut env, english(100)).unwrap();
+    let size = env.svm.get_account(&address).unwrap().data.len();
+    other.init(&mut env, english(100)).unwrap();
+    assert_eq!(
+        env.svm.get_account(&address).unwrap().data.len(),
+        size + MintVolume::INIT_SPACE
+    );
+
+    let bidder = market.bidder(&mut env, FUNDS);
+    let other_bidder = other.bidder(&mut env, FUNDS);
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    other.bid(&mut env, &other_bidder, 3_000_000).unwrap();
+    env.warp_to(100);
+    market
+        .finalize(&mut env, &market.seller, &bidder.pubkey())
+        .unwrap();
+    other
+        .finalize(&mut env, &other.seller, &other_bidder.pubkey())
+        .unwrap();
+
+    let seller = stats(&env, &market, &market.seller);
+    assert_eq!(seller.auctions_completed, 2);
+    assert_eq!(seller.volume(&market.mint_b), Some(2000));
+    assert_eq!(seller.volume(&other_mint), Some(3000));
+}
+
+#[test]
+fn sellers_winning_their_own_lot_count_both_sides() {
+    let mut env = Env::new();
+    let market = Market::new(&mut env);
+    market.init(&mut env, english(100)).unwrap();
+    env.mint_to(
+        &market.mint_b,
+        &market.mint_authority,
+        &market.seller.pubkey(),
+        FUNDS,
+        &market.token_program,
+    );
+
+    market.bid(&mut env, &market.seller, PRICE).unwrap();
+    env.warp_to(100);
+    market
+        .finalize(&mut env, &market.seller, &market.seller.pubkey())
+        .unwrap();
+
+    let seller = stats(&env, &market, &market.seller);
+    assert_eq!(seller.auctions_created, 1);
+    assert_eq!(seller.bids_placed, 1);
+    assert_eq!(seller.auctions_completed, 1);
+    assert_eq!(seller.auctions_won, 1);
+    assert_eq!(seller.volume(&market.mint_b), Some(4000));
+}
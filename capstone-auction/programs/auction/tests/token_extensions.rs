@@ -0,0 +1,162 @@
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::instruction::initialize_transfer_fee_config, ExtensionType},
+    instruction::{initialize_non_transferable_mint, initialize_permanent_delegate},
+};
+use auction::errors::AuctionError;
+use auction::state::EndCondition;
+use common::*;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const FEE_BPS: u16 = 100;
+
+fn fee_mint(env: &mut Env, authority: &Keypair) -> Pubkey {
+    let authority_key = authority.pubkey();
+    env.create_mint(
+        authority,
+        DECIMALS,
+        &spl_token_2022::ID,
+        &[ExtensionType::TransferFeeConfig],
+        |mint| {
+            vec![initialize_transfer_fee_config(
+                &spl_token_2022::ID,
+                mint,
+                Some(&authority_key),
+                Some(&authority_key),
+                FEE_BPS,
+                u64::MAX,
+            )
+            .unwrap()]
+        },
+    )
+}
+
+// both mints take a 1% transfer fee
+fn fee_market(env: &mut Env) -> Market {
+    let authority = env.user();
+    let mint_a = fee_mint(env, &authority);
+    let mint_b = fee_mint(env, &authority);
+    let market = Market::with_mints(env, authority, mint_a, mint_b, spl_token_2022::ID);
+    market.fund_seller(env, 1_000_000);
+    market
+}
+
+#[test]
+fn transfer_fees_are_paid_on_top() {
+    let mut env = Env::new();
+    let market = fee_market(&mut env);
+    let bidder = market.bidder(&mut env, 1_000_000);
+    let bidder2 = market.bidder(&mut env, 1_000_000);
+    let seller = market.seller.pubkey();
+
+    market.init(&mut env, english(100)).unwrap();
+    assert_eq!(env.token_balance(&market.vault()), AMOUNT);
+
+    market.bid(&mut env, &bidder, PRICE).unwrap();
+    market.bid(&mut env, &bidder2, 3_000_000).unwrap();
+    assert_eq!(env.token_balance(&market.escrow(&bidder2.pubkey())), 3000);
+
+    // the withheld fees don't keep the escrow from closing
+    market.withdraw(&mut env, &bidder).unwrap();
+    assert!(!env.exists(&market.escrow(&bidder.pubkey())));
+
+    env.warp_to(100);
+    market
+        .finalize(&mut env, &market.seller, &bidder2.pubkey())
+        .unwrap();
+
+    // every payout loses the fee, rounded up, on the way out
+    assert_eq!(
+        env.token_balance(&market.ata(&bidder2.pubkey(), &market.mint_a)),
+        990
+    );
+    assert_eq!(
+        env.token_balance(&market.ata(&seller, &market.mint_b)),
+        2940
+    );
+    assert_eq!(env.token_balance(&market.treasury()), 29);
+    assert!(!env.exists(&market.vault()));
+}
+
+#[test]
+fn buyers_pay_the_transfer_fee_on_top() {
+    let mut env = Env::new();
+    let market = fee_market(&mut env);
+    let buyer = market.bidder(&mut env, 1_000_000);
+    let seller = market.seller.pubkey();
+
+    market
+        .init(
+            &mut env,
+            auction::instruction::InitDutchAuction {
+                seed: 0,
+                start_price: PRICE,
+                floor_price: PRICE,
+                step: 10,
+                end_condition: EndCondition::Slot,
+                end: 100,
+                amount: AMOUNT,
+                decimal: DECIMALS,
+            },
+        )
+        .unwrap();
+    market.buy_now(&mut env, &buyer, PRICE).unwrap();
+
+    // 2000 less the 1% house fee, both arrive in full
+    assert_eq!(
+        env.token_balance(&market.ata(&seller, &market.mint_b)),
+        1980
+    );
+    assert_eq!(env.token_balance(&market.treasury()), 20);
+    assert_eq!(
+        env.token_balance(&market.ata(&buyer.pubkey(), &market.mint_a)),
+        990
+    );
+}
+
+#[test]
+fn non_transferable_mints_are_rejected() {
+    let mut env = Env::new();
+    let authority = env.user();
+    let mint_a = env.create_mint(
+        &authority,
+        DECIMALS,
+        &spl_token_2022::ID,
+        &[ExtensionType::NonTransferable],
+        |mint| vec![initialize_non_transferable_mint(&spl_token_2022::ID, mint).unwrap()],
+    );
+    let mint_b = fee_mint(&mut env, &authority);
+    let market = Market::with_mints(&mut env, authority, mint_a, mint_b, spl_token_2022::ID);
+    market.fund_seller(&mut env, 1_000_000);
+
+    assert_error(
+        market.init(&mut env, english(100)),
+        AuctionError::NonTransferableMint,
+    );
+}
+
+#[test]
+fn permanent_delegates_are_rejected() {
+    let mut env = Env::new();
+    let authority = env.user();
+    let delegate = authority.pubkey();
+    let mint_a = fee_mint(&mut env, &authority);
+    let mint_b = env.create_mint(
+        &authority,
+        DECIMALS,
+        &spl_token_2022::ID,
+        &[ExtensionType::PermanentDelegate],
+        |mint| vec![initialize_permanent_delegate(&spl_token_2022::ID, mint, &delegate).unwrap()],
+    );
+    let market = Market::with_mints(&mut env, authority, mint_a, mint_b, spl_token_2022::ID);
+    market.fund_seller(&mut env, 1_000_000);
+
+    assert_error(
+        market.init(&mut env, english(100)),
+        AuctionError::PermanentDelegateMint,
+    );
+}
@@ -45,6 +45,14 @@ describe("auction", () => {
         ],
         program.programId,
     );
+    const [treasuryB] = PublicKey.findProgramAddressSync(
+        [
+            Buffer.from("treasury"),
+            auction_house.toBuffer(),
+            mintB.publicKey.toBuffer(),
+        ],
+        program.programId,
+    );
     let end: anchor.BN;
     let auction: PublicKey;
     let vault: PublicKey;
@@ -53,11 +61,22 @@ describe("auction", () => {
     let bidState: PublicKey;
     let bid2State: PublicKey;
 
+    const seed = new anchor.BN(0);
     const starting_price = new anchor.BN(2000000);
     const amount = new anchor.BN(50);
     const bidPrice = new anchor.BN(3000000);
     const bidPrice2 = new anchor.BN(4000000);
 
+    // only needed when the listing mint is a programmable NFT
+    const notProgrammable = {
+        edition: null,
+        vaultTokenRecord: null,
+        authorizationRules: null,
+        authorizationRulesProgram: null,
+        sysvarInstructions: null,
+        tokenMetadataProgram: null,
+    };
+
     const bidder2 = Keypair.generate();
     const bidder2AtaB = getAssociatedTokenAddressSync(mintB.publicKey, bidder2.publicKey, true, tokenProgram);
     const bidder2AtaA = getAssociatedTokenAddressSync(mintA.publicKey, bidder2.publicKey, true, tokenProgram);
@@ -207,7 +226,7 @@ describe("auction", () => {
             systemProgram: SystemProgram.programId
         }
 
-        const tx = await program.methods.initHouse(100, "testAuction")
+        const tx = await program.methods.initHouse(100, 0, "testAuction")
             .accountsPartial({ ...accounts })
             .signers([admin])
             .rpc();
@@ -215,6 +234,21 @@ describe("auction", () => {
         console.log("Your transaction signature", tx);
     });
 
+    it("set keeper reward", async () => {
+        const tx = await program.methods.setKeeperReward({ lamports: { amount: new anchor.BN(5000) } })
+            .accountsPartial({
+                admin: admin.publicKey,
+                auctionHouse: auction_house,
+            })
+            .signers([admin])
+            .rpc();
+        console.log("Your transaction signature", tx);
+
+        // the keeper pool is still empty, so keepers get nothing yet
+        const house = await program.account.auctionHouse.fetch(auction_house);
+        assert.equal(house.keeperReward.lamports.amount.toNumber(), 5000);
+    });
+
     it("initialize auction", async () => {
         end = new anchor.BN(await provider.connection.getSlot() + 3);
 
@@ -225,6 +259,7 @@ describe("auction", () => {
                 seller.publicKey.toBuffer(),
                 mintA.publicKey.toBuffer(),
                 mintB.publicKey.toBuffer(),
+                seed.toArrayLike(Buffer, "le", 8),
             ],
             program.programId,
         );
@@ -243,11 +278,13 @@ describe("auction", () => {
             mintB: mintB.publicKey,
             sellerAtaA: sellerAtaA,
             vault: vault,
+            ...notProgrammable,
+            sellerTokenRecord: null,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         }
-        const tx = await program.methods.initAuction(starting_price, end, amount, 6)
+        const tx = await program.methods.initAuction(seed, starting_price, new anchor.BN(0), { slot: {} }, end, amount, 6, new anchor.BN(0), new anchor.BN(0), new anchor.BN(0), new anchor.BN(0), 0, new anchor.BN(0), Array(32).fill(0), null)
             .accountsPartial({ ...accounts })
             .signers([seller])
             .rpc();
@@ -290,15 +327,17 @@ describe("auction", () => {
             mintB: mintB.publicKey,
             auctionHouse: auction_house,
             auction: auction,
-            bidderAtaB: bidderAtaB,
+            bidderMintBAta: bidderAtaB,
             bidderEscrow: bidderEscrow,
             bidState: bidState,
             vault: vault,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            gateTokenAccount: null,
+            gateMetadata: null,
         }
-        const tx = await program.methods.bid(bidPrice)
+        const tx = await program.methods.bid(bidPrice, [], null)
             .accountsPartial({ ...accounts })
             .signers([bidder])
             .rpc();
@@ -320,6 +359,7 @@ describe("auction", () => {
         console.log("bid state: {}", bidStateAccount);
         assert.ok(bidStateAccount.auction.equals(auction));
         assert.ok(bidStateAccount.bidder.equals(bidder.publicKey));
+        assert.ok(bidStateAccount.price.eq(bidPrice));
 
         // Check the bidder escrow token account
         const bidderEscrowAccount = await getAccount(provider.connection, bidderEscrow);
@@ -349,15 +389,17 @@ describe("auction", () => {
             mintB: mintB.publicKey,
             auctionHouse: auction_house,
             auction: auction,
-            bidderAtaB: bidder2AtaB,
+            bidderMintBAta: bidder2AtaB,
             bidderEscrow: bidder2Escrow,
             bidState: bid2State,
             vault: vault,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            gateTokenAccount: null,
+            gateMetadata: null,
         }
-        const tx = await program.methods.bid(bidPrice2)
+        const tx = await program.methods.bid(bidPrice2, [], null)
             .accountsPartial({ ...accounts })
             .signers([bidder2])
             .rpc();
@@ -399,7 +441,6 @@ describe("auction", () => {
             payer: seller.publicKey,
             seller: seller.publicKey,
             bidder: bidder2.publicKey,
-            admin: admin.publicKey,
             mintA: mintA.publicKey,
             mintB: mintB.publicKey,
             auctionHouse: auction_house,
@@ -407,7 +448,10 @@ describe("auction", () => {
             bidderAtaA: bidder2AtaA,
             sellerAtaB: sellerAtaB,
             sellerAtaA: sellerAtaA,
-            houseMintB: houseAtaB,
+            treasury: treasuryB,
+            payerMintBAta: null,
+            ...notProgrammable,
+            destinationTokenRecord: null,
             bidState: bid2State,
             bidderEscrow: bidder2Escrow,
             vault: vault,
@@ -431,7 +475,28 @@ describe("auction", () => {
 
         assert.deepEqual((await getAccount(provider.connection, bidder2AtaA)).amount, BigInt(50));
         assert.deepEqual((await getAccount(provider.connection, sellerAtaB)).amount, BigInt(198));
+        assert.deepEqual((await getAccount(provider.connection, treasuryB)).amount, BigInt(2));
+    })
+
+    it("sweep fees", async () => {
+        const tx = await program.methods.sweepFees()
+            .accountsPartial({
+                admin: admin.publicKey,
+                auctionHouse: auction_house,
+                mint: mintB.publicKey,
+                treasury: treasuryB,
+                destination: houseAtaB,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .preInstructions([
+                createAssociatedTokenAccountIdempotentInstruction(admin.publicKey, houseAtaB, admin.publicKey, mintB.publicKey, tokenProgram),
+            ])
+            .signers([admin])
+            .rpc();
+        console.log("Your transaction signature", tx);
+
         assert.deepEqual((await getAccount(provider.connection, houseAtaB)).amount, BigInt(2));
+        assert.deepEqual((await getAccount(provider.connection, treasuryB)).amount, BigInt(0));
     })
 
     it("withdraw", async () => {
@@ -440,7 +505,7 @@ describe("auction", () => {
             mintB: mintB.publicKey,
             auctionHouse: auction_house,
             auction: auction,
-            bidderAtaB: bidderAtaB,
+            bidderMintBAta: bidderAtaB,
             bidderEscrow: bidderEscrow,
             bidState: bidState,
             systemProgram: SystemProgram.programId,
@@ -0,0 +1,262 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Auction } from "../target/types/auction";
+import { Keypair, LAMPORTS_PER_SOL, SystemProgram, PublicKey, TransactionInstruction, ComputeBudgetProgram } from "@solana/web3.js";
+import {
+    TOKEN_2022_PROGRAM_ID,
+    ASSOCIATED_TOKEN_PROGRAM_ID,
+    ExtensionType,
+    getMintLen,
+    getAssociatedTokenAddressSync,
+    createInitializeMint2Instruction,
+    createInitializeTransferFeeConfigInstruction,
+    createInitializeNonTransferableMintInstruction,
+    createInitializePermanentDelegateInstruction,
+    createAssociatedTokenAccountIdempotentInstruction,
+    createMintToInstruction,
+    getAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+
+// auctions of Token-2022 mints with the transfer-fee, non-transferable and
+// permanent-delegate extensions
+describe("token-2022 extensions", () => {
+
+    let provider = anchor.getProvider();
+    anchor.setProvider(anchor.AnchorProvider.env());
+
+    const program = anchor.workspace.Auction as Program<Auction>;
+    const tokenProgram = TOKEN_2022_PROGRAM_ID;
+
+    const admin = Keypair.generate();
+    const seller = Keypair.generate();
+    const bidder = Keypair.generate();
+    const bidder2 = Keypair.generate();
+    const [auction_house] = PublicKey.findProgramAddressSync(
+        [Buffer.from("house"), Buffer.from("testToken2022")],
+        program.programId,
+    );
+
+    // 1% transfer fee on both sides
+    const feeMintA = Keypair.generate();
+    const feeMintB = Keypair.generate();
+    const feeBps = 100;
+
+    const seed = new anchor.BN(0);
+    const price = new anchor.BN(2000000);
+    const price2 = new anchor.BN(3000000);
+    const amount = new anchor.BN(1000);
+
+    // only needed when the listing mint is a programmable NFT
+    const notProgrammable = {
+        edition: null,
+        vaultTokenRecord: null,
+        authorizationRules: null,
+        authorizationRulesProgram: null,
+        sysvarInstructions: null,
+        tokenMetadataProgram: null,
+    };
+
+    const ata = (mint: PublicKey, owner: PublicKey) =>
+        getAssociatedTokenAddressSync(mint, owner, true, tokenProgram);
+
+    const pda = (seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+    async function createMint(mint: Keypair, extensions: ExtensionType[], init: TransactionInstruction[]) {
+        const space = getMintLen(extensions);
+        const lamports = await provider.connection.getMinimumBalanceForRentExemption(space);
+        const tx = new anchor.web3.Transaction().add(
+            SystemProgram.createAccount({
+                fromPubkey: provider.publicKey,
+                newAccountPubkey: mint.publicKey,
+                space,
+                lamports,
+                programId: tokenProgram,
+            }),
+            ...init,
+            createInitializeMint2Instruction(mint.publicKey, 6, seller.publicKey, null, tokenProgram),
+        );
+        await provider.sendAndConfirm(tx, [mint]);
+    }
+
+    async function mintTo(mint: PublicKey, owner: PublicKey, amount: number) {
+        const tx = new anchor.web3.Transaction().add(
+            createAssociatedTokenAccountIdempotentInstruction(
+                provider.publicKey, ata(mint, owner), owner, mint, tokenProgram
+            ),
+            createMintToInstruction(mint, ata(mint, owner), seller.publicKey, amount, undefined, tokenProgram),
+        );
+        await provider.sendAndConfirm(tx, [seller]);
+    }
+
+    function initAuction(mintA: PublicKey, mintB: PublicKey, end: anchor.BN) {
+        const auction = pda([Buffer.from("auction"), auction_house.toBuffer(), seller.publicKey.toBuffer(), mintA.toBuffer(), mintB.toBuffer(), seed.toArrayLike(Buffer, "le", 8)]);
+        return program.methods.initAuction(seed, price, new anchor.BN(0), { slot: {} }, end, amount, 6, new anchor.BN(0), new anchor.BN(0), new anchor.BN(0), new anchor.BN(0), 0, new anchor.BN(0), Array(32).fill(0), null)
+            .accountsPartial({
+                seller: seller.publicKey,
+                auctionHouse: auction_house,
+                auction,
+                mintA,
+                mintB,
+                sellerMintAAta: ata(mintA, seller.publicKey),
+                vault: ata(mintA, auction),
+                ...notProgrammable,
+                sellerTokenRecord: null,
+                systemProgram: SystemProgram.programId,
+                tokenProgram,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            })
+            .signers([seller])
+            .rpc();
+    }
+
+    function bid(who: Keypair, auction: PublicKey, bidPrice: anchor.BN) {
+        const bidState = pda([Buffer.from("bid"), auction.toBuffer(), who.publicKey.toBuffer()]);
+        return program.methods.bid(bidPrice, [], null)
+            .accountsPartial({
+                bidder: who.publicKey,
+                mintA: feeMintA.publicKey,
+                mintB: feeMintB.publicKey,
+                auctionHouse: auction_house,
+                auction,
+                bidderMintBAta: ata(feeMintB.publicKey, who.publicKey),
+                bidState,
+                bidderEscrow: ata(feeMintB.publicKey, bidState),
+                vault: ata(feeMintA.publicKey, auction),
+                systemProgram: SystemProgram.programId,
+                tokenProgram,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                gateTokenAccount: null,
+                gateMetadata: null,
+            })
+            .signers([who])
+            .rpc();
+    }
+
+    before("mints", async () => {
+        const tx = new anchor.web3.Transaction();
+        for (const who of [admin, seller, bidder, bidder2]) {
+            tx.add(SystemProgram.transfer({
+                fromPubkey: provider.publicKey,
+                toPubkey: who.publicKey,
+                lamports: 0.2 * LAMPORTS_PER_SOL,
+            }));
+        }
+        await provider.sendAndConfirm(tx);
+
+        for (const mint of [feeMintA, feeMintB]) {
+            await createMint(mint, [ExtensionType.TransferFeeConfig], [
+                createInitializeTransferFeeConfigInstruction(
+                    mint.publicKey, seller.publicKey, seller.publicKey, feeBps, BigInt(1e9), tokenProgram
+                ),
+            ]);
+        }
+        await mintTo(feeMintA.publicKey, seller.publicKey, 1e6);
+        await mintTo(feeMintB.publicKey, bidder.publicKey, 1e6);
+        await mintTo(feeMintB.publicKey, bidder2.publicKey, 1e6);
+
+        await program.methods.initHouse(100, 0, "testToken2022")
+            .accountsPartial({
+                admin: admin.publicKey,
+                auctionHouse: auction_house,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([admin])
+            .rpc();
+    });
+
+    it("transfer fee: vault and escrow hold the full amounts", async () => {
+        const end = new anchor.BN(await provider.connection.getSlot() + 15);
+        await initAuction(feeMintA.publicKey, feeMintB.publicKey, end);
+        const auction = pda([Buffer.from("auction"), auction_house.toBuffer(), seller.publicKey.toBuffer(), feeMintA.publicKey.toBuffer(), feeMintB.publicKey.toBuffer(), seed.toArrayLike(Buffer, "le", 8)]);
+
+        // the seller paid the fee on top
+        const vault = await getAccount(provider.connection, ata(feeMintA.publicKey, auction), undefined, tokenProgram);
+        assert.equal(vault.amount, BigInt(1000));
+
+        await bid(bidder, auction, price);
+        await bid(bidder2, auction, price2);
+
+        // 1000 units at 3 per unit, the bidder paid the fee on top
+        const bid2State = pda([Buffer.from("bid"), auction.toBuffer(), bidder2.publicKey.toBuffer()]);
+        const escrow = await getAccount(provider.connection, ata(feeMintB.publicKey, bid2State), undefined, tokenProgram);
+        assert.equal(escrow.amount, BigInt(3000));
+
+        // the outbid escrow holds withheld fees, which must not block closing it
+        const bidState = pda([Buffer.from("bid"), auction.toBuffer(), bidder.publicKey.toBuffer()]);
+        await program.methods.withdraw()
+            .accountsPartial({
+                bidder: bidder.publicKey,
+                mintB: feeMintB.publicKey,
+                auctionHouse: auction_house,
+                auction,
+                bidderMintBAta: ata(feeMintB.publicKey, bidder.publicKey),
+                bidderEscrow: ata(feeMintB.publicKey, bidState),
+                bidState,
+                systemProgram: SystemProgram.programId,
+                tokenProgram,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            })
+            .signers([bidder])
+            .rpc();
+        assert.isNull(await provider.connection.getAccountInfo(ata(feeMintB.publicKey, bidState)));
+
+        while (new anchor.BN(await provider.connection.getSlot("processed")).lte(end)) { }
+
+        const treasury = pda([Buffer.from("treasury"), auction_house.toBuffer(), feeMintB.publicKey.toBuffer()]);
+        await program.methods.finalize()
+            .accountsPartial({
+                payer: seller.publicKey,
+                seller: seller.publicKey,
+                bidder: bidder2.publicKey,
+                mintA: feeMintA.publicKey,
+                mintB: feeMintB.publicKey,
+                auctionHouse: auction_house,
+                auction,
+                bidState: bid2State,
+                bidderEscrow: ata(feeMintB.publicKey, bid2State),
+                vault: ata(feeMintA.publicKey, auction),
+                treasury,
+                payerMintBAta: null,
+                ...notProgrammable,
+                destinationTokenRecord: null,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                tokenProgram,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([seller])
+            .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 1400000 })])
+            .rpc();
+
+        // every payout loses the 1% fee (rounded up) on the way out
+        const won = await getAccount(provider.connection, ata(feeMintA.publicKey, bidder2.publicKey), undefined, tokenProgram);
+        assert.equal(won.amount, BigInt(990));
+        const proceeds = await getAccount(provider.connection, ata(feeMintB.publicKey, seller.publicKey), undefined, tokenProgram);
+        assert.equal(proceeds.amount, BigInt(2940));
+        const fees = await getAccount(provider.connection, treasury, undefined, tokenProgram);
+        assert.equal(fees.amount, BigInt(29));
+    });
+
+    it("rejects non-transferable mints", async () => {
+        const mint = Keypair.generate();
+        await createMint(mint, [ExtensionType.NonTransferable], [
+            createInitializeNonTransferableMintInstruction(mint.publicKey, tokenProgram),
+        ]);
+        await mintTo(mint.publicKey, seller.publicKey, 1e6);
+
+        const end = new anchor.BN(await provider.connection.getSlot() + 15);
+        const err = await initAuction(mint.publicKey, feeMintB.publicKey, end).then(() => null, (err) => err);
+        assert.equal(err?.error?.errorCode?.code, "NonTransferableMint");
+    });
+
+    it("rejects mints with a permanent delegate", async () => {
+        const mint = Keypair.generate();
+        await createMint(mint, [ExtensionType.PermanentDelegate], [
+            createInitializePermanentDelegateInstruction(mint.publicKey, seller.publicKey, tokenProgram),
+        ]);
+
+        const end = new anchor.BN(await provider.connection.getSlot() + 15);
+        const err = await initAuction(feeMintA.publicKey, mint.publicKey, end).then(() => null, (err) => err);
+        assert.equal(err?.error?.errorCode?.code, "PermanentDelegateMint");
+    });
+});